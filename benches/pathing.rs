@@ -0,0 +1,92 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rltk::Point;
+use roguelike_tutorial::flow_map::{FlowMaps, UNREACHABLE};
+use roguelike_tutorial::headless::HeadlessGame;
+use roguelike_tutorial::map::Map;
+
+const SEED: u64 = 7;
+/// Each case runs for about this long
+const BUDGET: Duration = Duration::from_secs(1);
+const MONSTER_COUNTS: [usize; 4] = [1, 10, 30, 60];
+
+/// The map of the first level and the player on it, with `count` monsters spread over the floor
+fn level(count: usize) -> (Map, Point, Vec<Point>) {
+    let game = HeadlessGame::with_seed(SEED);
+    let mut map: Map = (*game.state.ecs.fetch::<Map>()).clone();
+    let player = *game.state.ecs.fetch::<Point>();
+    // A* is at its slowest when there is no path, so only where the player can be reached
+    let reachable = FlowMaps::new(&map, &[player], &[]).towards;
+    let floor: Vec<usize> = (0..map.map_length)
+        .filter(|idx| !map.blocked[*idx])
+        .filter(|idx| {
+            reachable.value(Point::new(*idx as i32 % map.width, *idx as i32 / map.width))
+                != UNREACHABLE
+        })
+        .collect();
+    let monsters: Vec<Point> = floor
+        .iter()
+        .step_by((floor.len() / count).max(1))
+        .take(count)
+        .map(|idx| Point::new(*idx as i32 % map.width, *idx as i32 / map.width))
+        .collect();
+
+    for monster in monsters.iter() {
+        let idx = map.xy_idx(monster.x, monster.y);
+
+        map.blocked[idx] = true;
+    }
+
+    (map, player, monsters)
+}
+
+/// What the monsters did before flow maps: one A* search to the player each
+fn a_star_turn(map: &Map, player: Point, monsters: &[Point]) {
+    for monster in monsters {
+        black_box(rltk::a_star_search(
+            map.xy_idx(monster.x, monster.y) as i32,
+            map.xy_idx(player.x, player.y) as i32,
+            map,
+        ));
+    }
+}
+
+/// The flow maps are built once, then every monster looks at its neighbours
+fn flow_map_turn(map: &Map, player: Point, monsters: &[Point]) {
+    let flow_maps = FlowMaps::new(map, &[player], monsters);
+
+    for monster in monsters {
+        black_box(flow_maps.towards.next_step(map, *monster));
+        black_box(flow_maps.flee_step(map, *monster));
+    }
+}
+
+/// The average time of `turn`, run for `BUDGET` and at least three times
+fn time(turn: impl Fn()) -> Duration {
+    let start = Instant::now();
+    let mut turns = 0;
+
+    while turns < 3 || start.elapsed() < BUDGET {
+        turn();
+        turns += 1;
+    }
+
+    start.elapsed() / turns
+}
+
+fn main() {
+    println!(
+        "Pathing to the player, time per monster turn (seed {})",
+        SEED
+    );
+    println!("{:>8} {:>12} {:>12}", "monsters", "A*", "flow maps");
+
+    for count in MONSTER_COUNTS {
+        let (map, player, monsters) = level(count);
+        let a_star = time(|| a_star_turn(&map, player, &monsters));
+        let flow_maps = time(|| flow_map_turn(&map, player, &monsters));
+
+        println!("{:>8} {:>12?} {:>12?}", monsters.len(), a_star, flow_maps);
+    }
+}
@@ -0,0 +1,83 @@
+use specs::prelude::*;
+
+use crate::components::{Attributes, SkillKind, Skills};
+use crate::game_log::GameLog;
+
+/// The attribute value that neither helps nor hinders
+const AVERAGE_ATTRIBUTE: i32 = 10;
+/// The max HP of a player of average fitness
+const BASE_MAX_HP: i32 = 30;
+/// The max HP every point of fitness bonus adds
+const HP_PER_FITNESS: i32 = 5;
+/// How far a player of average intelligence sees
+const BASE_VISION_RANGE: i32 = 8;
+/// How long every hunger state lasts for someone of average fitness
+const BASE_HUNGER_DURATION: i32 = 200;
+/// The turns every point of fitness bonus adds to every hunger state
+const HUNGER_DURATION_PER_FITNESS: i32 = 20;
+/// The uses that take a skill from level 0 to 1, every level after takes that many more
+const FIRST_SKILL_PRACTICE: i32 = 10;
+
+/// What an attribute adds to the rolls and derived values, every 2 points from average count 1
+pub fn bonus(value: i32) -> i32 {
+    (value - AVERAGE_ATTRIBUTE).div_euclid(2)
+}
+
+pub fn max_hp(attributes: &Attributes) -> i32 {
+    i32::max(1, BASE_MAX_HP + HP_PER_FITNESS * bonus(attributes.fitness))
+}
+
+pub fn vision_range(attributes: &Attributes) -> i32 {
+    i32::max(1, BASE_VISION_RANGE + bonus(attributes.intelligence))
+}
+
+/// How long every hunger state lasts, those without attributes count as average
+pub fn hunger_duration(attributes: Option<&Attributes>) -> i32 {
+    let fitness = attributes.map_or(AVERAGE_ATTRIBUTE, |attributes| attributes.fitness);
+
+    i32::max(
+        1,
+        BASE_HUNGER_DURATION + HUNGER_DURATION_PER_FITNESS * bonus(fitness),
+    )
+}
+
+/// The uses it takes to raise a skill from the level before to this one
+pub fn practice_for_level(level: i32) -> i32 {
+    FIRST_SKILL_PRACTICE * level
+}
+
+/// The level of the skill, 0 for those without skills
+pub fn skill_level(skills: Option<&Skills>, kind: SkillKind) -> i32 {
+    skills.map_or(0, |skills| skills.get(kind).level)
+}
+
+/// Counts a use of the skill and raises it when it has had enough, telling the player when it is
+/// theirs
+pub fn practice(
+    skills: &mut WriteStorage<Skills>,
+    entity: Entity,
+    kind: SkillKind,
+    player_entity: Entity,
+    log: &mut GameLog,
+) {
+    let Some(skill) = skills.get_mut(entity).map(|skills| skills.get_mut(kind)) else {
+        return;
+    };
+
+    skill.practice += 1;
+
+    if skill.practice < practice_for_level(skill.level + 1) {
+        return;
+    }
+
+    skill.practice = 0;
+    skill.level += 1;
+
+    if entity == player_entity {
+        log.entries.push(format!(
+            "Your {} skill rises to {}.",
+            kind.name().to_lowercase(),
+            skill.level
+        ));
+    }
+}
@@ -0,0 +1,13 @@
+use roguelike_tutorial::mapgen;
+use roguelike_tutorial::raws;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let options = mapgen::parse_arguments(&args)?;
+
+    raws::load_raws()?;
+
+    print!("{}", mapgen::run(&options)?);
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use roguelike_tutorial::raws;
+use roguelike_tutorial::savetool;
+
+fn main() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().collect();
+    let options = savetool::parse_arguments(&args)?;
+
+    raws::load_raws()?;
+
+    print!("{}", savetool::run(&options)?);
+
+    Ok(())
+}
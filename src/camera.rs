@@ -43,7 +43,7 @@ pub fn render_camera(ecs: &World, ctx: &mut Rltk) {
         .join()
         .collect::<Vec<_>>();
 
-    data.sort_by(|&a, &b| b.1.render_order.cmp(&a.1.render_order));
+    data.sort_by_key(|&b| std::cmp::Reverse(b.1.render_order));
     for (pos, render, _hidden) in data.iter() {
         let idx = map.xy_idx(pos.x, pos.y);
         if map.visible_tiles[idx] {
@@ -87,6 +87,10 @@ fn get_tile_glyph(idx: usize, map: &Map) -> (rltk::FontCharType, RGB, RGB) {
             glyph = rltk::to_cp437('>');
             fg = RGB::from_f32(0., 1.0, 1.0);
         }
+        TileType::UpStairs => {
+            glyph = rltk::to_cp437('<');
+            fg = RGB::from_f32(0., 1.0, 1.0);
+        }
     }
 
     if map.bloodstains.contains(&idx) {
@@ -0,0 +1,134 @@
+use std::convert::Infallible;
+
+use specs::prelude::*;
+use specs::saveload::{
+    DeserializeComponents, SerializeComponents, SimpleMarker, SimpleMarkerAllocator,
+};
+
+use crate::components::*;
+
+/// Storages in a save, by component name
+pub type SavedComponents = serde_json::Map<String, serde_json::Value>;
+
+/// Lists every component once. Saved components are written to and read from saves under their
+/// type name, transient ones are only registered. A component has to be in one of the lists,
+/// the `component_registry` test checks that for every `Component` derive in `src`.
+macro_rules! component_registry {
+    (saved: [$( $saved:ty ),* $(,)?], transient: [$( $transient:ty ),* $(,)?] $(,)?) => {
+        pub const SAVED_COMPONENTS: &[&str] = &[$( stringify!($saved) ),*];
+
+        pub const TRANSIENT_COMPONENTS: &[&str] = &[$( stringify!($transient) ),*];
+
+        pub fn register_components(ecs: &mut World) {
+            $( ecs.register::<$saved>(); )*
+            $( ecs.register::<$transient>(); )*
+        }
+
+        /// Writes every saved storage of the marked entities into `components`
+        pub fn serialize_components(ecs: &World, components: &mut SavedComponents) {
+            let entities = ecs.entities();
+            let markers = ecs.read_storage::<SimpleMarker<SerializeMe>>();
+
+            $(
+            components.insert(
+                stringify!($saved).to_string(),
+                SerializeComponents::<Infallible, SimpleMarker<SerializeMe>>::serialize(
+                    &( ecs.read_storage::<$saved>(), ),
+                    &entities,
+                    &markers,
+                    serde_json::value::Serializer,
+                )
+                .expect("A component storage always turns into JSON"),
+            );
+            )*
+        }
+
+        /// Reads the saved storages back, a storage missing from `components` stays empty
+        pub fn deserialize_components(
+            ecs: &mut World,
+            components: &mut SavedComponents,
+        ) -> Result<(), String> {
+            let mut data = (
+                &mut ecs.entities(),
+                &mut ecs.write_storage::<SimpleMarker<SerializeMe>>(),
+                &mut ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>(),
+            );
+
+            $(
+            if let Some(storage) = components.remove(stringify!($saved)) {
+                DeserializeComponents::<Infallible, _>::deserialize(
+                    &mut ( &mut ecs.write_storage::<$saved>(), ),
+                    data.0, // entities
+                    &mut data.1, // marker
+                    &mut data.2, // allocater
+                    storage,
+                )
+                .map_err(|error| {
+                    format!("The save has a broken {}: {}", stringify!($saved), error)
+                })?;
+            }
+            )*
+
+            Ok(())
+        }
+    };
+}
+
+component_registry!(
+    saved: [
+        Position,
+        Renderable,
+        Player,
+        Viewshed,
+        Monster,
+        Name,
+        BlocksTile,
+        CombatStats,
+        SufferDamage,
+        WantsToMelee,
+        Item,
+        Consumable,
+        Ranged,
+        InflictsDamage,
+        AreaOfEffect,
+        ProvidesHealing,
+        InBackpack,
+        WantsToPickupItem,
+        WantsToUseItem,
+        WantsToDropItem,
+        SerializationHelper,
+        Equippable,
+        Equipped,
+        MeleePowerBonus,
+        DefenseBonus,
+        WantsToRemoveItem,
+        ParticleLifetime,
+        HungerClock,
+        ProvidesFood,
+        MagicMapper,
+        Hidden,
+        EntryTrigger,
+        EntityMoved,
+        SingleActivation,
+        OtherLevelPosition,
+        BlocksVisibility,
+        Door,
+        Initiative,
+        SpeedPenalty,
+        Ai,
+        Faction,
+        Provoked,
+        MeleeWeapon,
+        StatusEffects,
+        InflictsStatus,
+        Resistances,
+        Immunities,
+        Vulnerabilities,
+        Experience,
+        XpValue,
+        Attributes,
+        Skills,
+    ],
+    // The markers are how saved entities find each other, not saved data themselves
+    transient: [SimpleMarker<SerializeMe>, MyTurn],
+);
@@ -1,14 +1,16 @@
 use rltk::{Point, RGB};
 use serde::{Deserialize, Serialize};
 use specs::prelude::*;
-use specs::{
-    error::NoError,
-    saveload::{ConvertSaveload, Marker},
-};
+// The `ConvertSaveload` derive still refers to `NoError` by name
+#[allow(deprecated)]
+use specs::error::NoError;
+use specs::saveload::{ConvertSaveload, Marker};
 use specs_derive::*;
 
+use crate::dungeon::MasterDungeonMap;
 use crate::map::Map;
 use crate::render_order::RenderOrder;
+use crate::rng::RngStreams;
 
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct Position {
@@ -60,16 +62,21 @@ pub struct WantsToMelee {
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct SufferDamage {
-    pub amount: Vec<i32>,
+    pub amount: Vec<(i32, DamageType)>,
 }
 
 impl SufferDamage {
-    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32) {
+    pub fn new_damage(
+        store: &mut WriteStorage<SufferDamage>,
+        victim: Entity,
+        amount: i32,
+        damage_type: DamageType,
+    ) {
         if let Some(suffering) = store.get_mut(victim) {
-            suffering.amount.push(amount);
+            suffering.amount.push((amount, damage_type));
         } else {
             let dmg = SufferDamage {
-                amount: vec![amount],
+                amount: vec![(amount, damage_type)],
             };
             store.insert(victim, dmg).expect("Unable to insert damage");
         }
@@ -114,19 +121,54 @@ pub struct Ranged {
     pub range: i32,
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum DamageType {
+    #[default]
+    Physical,
+    Fire,
+    Cold,
+    Poison,
+}
+
+impl DamageType {
+    /// How the damage is called in the log, as in "The orc resists the fire"
+    pub fn name(self) -> &'static str {
+        match self {
+            DamageType::Physical => "blow",
+            DamageType::Fire => "fire",
+            DamageType::Cold => "cold",
+            DamageType::Poison => "poison",
+        }
+    }
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct InflictsDamage {
     pub damage: i32,
+    pub damage_type: DamageType,
 }
 
-#[derive(Component, Debug, ConvertSaveload, Clone)]
-pub struct AreaOfEffect {
-    pub radius: i32,
+/// Damage of these types is halved
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Resistances {
+    pub damage_types: Vec<DamageType>,
+}
+
+/// Damage of these types does nothing
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Immunities {
+    pub damage_types: Vec<DamageType>,
+}
+
+/// Damage of these types is doubled
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Vulnerabilities {
+    pub damage_types: Vec<DamageType>,
 }
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
-pub struct Confusion {
-    pub turns: i32,
+pub struct AreaOfEffect {
+    pub radius: i32,
 }
 
 // Serialization helper code. We need to implement ConvertSaveload for each type that contains an
@@ -137,6 +179,10 @@ pub struct SerializeMe;
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct SerializationHelper {
     pub map: Map,
+    pub dungeon: MasterDungeonMap,
+    pub seed: u64,
+    pub turns: u64,
+    pub rng_streams: RngStreams,
 }
 
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
@@ -161,6 +207,13 @@ pub struct MeleePowerBonus {
     pub power: i32,
 }
 
+/// The damage dice of a weapon, or of a monster's claws and teeth when it is on the monster itself
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct MeleeWeapon {
+    pub damage: rltk::DiceType,
+    pub damage_type: DamageType,
+}
+
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct DefenseBonus {
     pub defense: i32,
@@ -215,3 +268,245 @@ pub struct BlocksVisibility {}
 pub struct Door {
     pub open: bool,
 }
+
+/// Where an entity waits on a level the player is not on, it has no `Position` meanwhile
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct OtherLevelPosition {
+    pub x: i32,
+    pub y: i32,
+    pub depth: i32,
+}
+
+/// Acts when `energy` is not negative, every action costs energy and every tick gives `speed`
+/// back, see `initiative_system`
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Initiative {
+    pub speed: i32,
+    pub energy: i32,
+}
+
+/// Marks the monsters the scheduler picked to act this monster turn
+#[derive(Component, Debug, Clone)]
+pub struct MyTurn {}
+
+/// Speed lost while the item is equipped, e.g. heavy armour
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct SpeedPenalty {
+    pub penalty: i32,
+}
+
+/// What a monster is up to, it picks its next action from this and what it sees
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+pub enum AiState {
+    /// Stands still for `turns` more turns, then wanders off
+    Idle {
+        turns: i32,
+    },
+    Wander {
+        destination: Point,
+    },
+    Chase {
+        last_seen: Point,
+    },
+    /// Goes to where it lost sight of its target and looks around for `turns` more turns
+    Search {
+        last_seen: Point,
+        turns: i32,
+    },
+    Flee,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Ai {
+    pub state: AiState,
+}
+
+/// The side an entity is on, the name of an entry of the faction table in the raws
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Faction {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum StatusKind {
+    /// Monsters lose their turns, the player stumbles around
+    Confusion,
+    /// Damage every turn, more for every dose
+    Poison,
+    /// Healing every turn
+    Regeneration,
+    Haste,
+    Slow,
+    /// No turns at all
+    Paralysis,
+}
+
+impl StatusKind {
+    /// How the status shows in the HUD
+    pub fn name(self) -> &'static str {
+        match self {
+            StatusKind::Confusion => "Confused",
+            StatusKind::Poison => "Poisoned",
+            StatusKind::Regeneration => "Regenerating",
+            StatusKind::Haste => "Hasted",
+            StatusKind::Slow => "Slowed",
+            StatusKind::Paralysis => "Paralysed",
+        }
+    }
+
+    /// Whether another dose runs next to the ones already there, instead of restarting their
+    /// clock
+    pub fn stacks(self) -> bool {
+        self == StatusKind::Poison
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub turns: i32,
+}
+
+/// The timed effects an entity is under, ticked down by the `StatusEffectSystem`
+#[derive(Component, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.effects.iter().any(|effect| effect.kind == kind)
+    }
+
+    /// How many doses of the effect are running
+    pub fn doses(&self, kind: StatusKind) -> usize {
+        self.effects
+            .iter()
+            .filter(|effect| effect.kind == kind)
+            .count()
+    }
+
+    /// The turns until the last dose of the effect wears off, 0 when there is none
+    pub fn turns_left(&self, kind: StatusKind) -> i32 {
+        self.effects
+            .iter()
+            .filter(|effect| effect.kind == kind)
+            .map(|effect| effect.turns)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Stacks the effect with the ones already there, or refreshes them to whichever of the two
+    /// lasts longer
+    pub fn add(&mut self, effect: StatusEffect) {
+        if !effect.kind.stacks() {
+            if let Some(running) = self.effects.iter_mut().find(|e| e.kind == effect.kind) {
+                running.turns = i32::max(running.turns, effect.turns);
+                return;
+            }
+        }
+
+        self.effects.push(effect);
+    }
+}
+
+/// The effects an item puts on whoever it is used on
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct InflictsStatus {
+    pub effects: Vec<StatusEffect>,
+}
+
+/// The level of whoever earns experience, and the XP earned so far
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Experience {
+    pub level: i32,
+    pub xp: i32,
+}
+
+/// The XP whoever kills this entity earns
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct XpValue {
+    pub xp: i32,
+}
+
+/// What an entity is made of, 10 in every attribute is average and each point over it counts
+/// half, see `attributes::bonus`
+#[derive(Component, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Attributes {
+    /// Hits harder and more often
+    pub might: i32,
+    /// More hit points, and longer before hunger sets in
+    pub fitness: i32,
+    /// Harder to hit
+    pub quickness: i32,
+    /// Sees further, and gets more out of magic
+    pub intelligence: i32,
+}
+
+impl Default for Attributes {
+    fn default() -> Self {
+        Attributes {
+            might: 10,
+            fitness: 10,
+            quickness: 10,
+            intelligence: 10,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum SkillKind {
+    Melee,
+    Defense,
+    Magic,
+}
+
+impl SkillKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            SkillKind::Melee => "Melee",
+            SkillKind::Defense => "Defense",
+            SkillKind::Magic => "Magic",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct Skill {
+    pub level: i32,
+    /// Uses since the last level, see `attributes::practice_for_level`
+    pub practice: i32,
+}
+
+/// What an entity has learned by doing, every skill rises with use
+#[derive(Component, Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct Skills {
+    pub melee: Skill,
+    pub defense: Skill,
+    pub magic: Skill,
+}
+
+impl Skills {
+    pub fn get(&self, kind: SkillKind) -> Skill {
+        match kind {
+            SkillKind::Melee => self.melee,
+            SkillKind::Defense => self.defense,
+            SkillKind::Magic => self.magic,
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: SkillKind) -> &mut Skill {
+        match kind {
+            SkillKind::Melee => &mut self.melee,
+            SkillKind::Defense => &mut self.defense,
+            SkillKind::Magic => &mut self.magic,
+        }
+    }
+}
+
+/// Whoever attacked this entity last, it fights back whatever its faction thinks of theirs and
+/// they get the credit when it dies
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Provoked {
+    pub by: Entity,
+}
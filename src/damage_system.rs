@@ -1,4 +1,5 @@
 use crate::components::*;
+use crate::experience;
 use crate::game_log::GameLog;
 use crate::map::Map;
 use crate::RunState;
@@ -14,13 +15,79 @@ impl<'a> System<'a> for DamageSystem {
         ReadStorage<'a, Position>,
         WriteExpect<'a, Map>,
         Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Resistances>,
+        ReadStorage<'a, Immunities>,
+        ReadStorage<'a, Vulnerabilities>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut stats, mut damage, positions, mut map, entities) = data;
+        let (
+            mut stats,
+            mut damage,
+            positions,
+            mut map,
+            entities,
+            player_entity,
+            mut log,
+            names,
+            resistances,
+            immunities,
+            vulnerabilities,
+        ) = data;
 
-        for (entity, mut stats, damage) in (&entities, &mut stats, &damage).join() {
-            stats.hp -= damage.amount.iter().sum::<i32>();
+        for (entity, stats, damage) in (&entities, &mut stats, &damage).join() {
+            let is_player = entity == *player_entity;
+            let victim = names
+                .get(entity)
+                .map(|name| name.name.to_lowercase())
+                .unwrap_or_else(|| "something".to_string());
+
+            for (amount, damage_type) in damage.amount.iter() {
+                let has = |types: Option<&Vec<DamageType>>| {
+                    types.is_some_and(|types| types.contains(damage_type))
+                };
+                let immune = has(immunities.get(entity).map(|i| &i.damage_types));
+                let resists = has(resistances.get(entity).map(|r| &r.damage_types));
+                let vulnerable = has(vulnerabilities.get(entity).map(|v| &v.damage_types));
+
+                // Resisting and being vulnerable to the same damage cancel out
+                let taken = if immune {
+                    0
+                } else if resists && !vulnerable {
+                    amount / 2
+                } else if vulnerable && !resists {
+                    amount * 2
+                } else {
+                    *amount
+                };
+                let message = if immune {
+                    Some(match is_player {
+                        true => format!("You are immune to the {}.", damage_type.name()),
+                        false => format!("The {} is immune to the {}.", victim, damage_type.name()),
+                    })
+                } else if taken < *amount {
+                    Some(match is_player {
+                        true => format!("You resist the {}.", damage_type.name()),
+                        false => format!("The {} resists the {}.", victim, damage_type.name()),
+                    })
+                } else if taken > *amount {
+                    Some(match is_player {
+                        true => format!("The {} hurts you badly.", damage_type.name()),
+                        false => format!("The {} hurts the {} badly.", damage_type.name(), victim),
+                    })
+                } else {
+                    None
+                };
+
+                if let Some(message) = message {
+                    log.entries.push(message);
+                }
+
+                stats.hp -= taken;
+            }
 
             if let Some(Position { x, y }) = positions.get(entity) {
                 let idx = map.xy_idx(*x, *y);
@@ -35,12 +102,15 @@ impl<'a> System<'a> for DamageSystem {
 
 pub fn delete_the_dead(ecs: &mut World) {
     let mut dead: Vec<Entity> = Vec::new();
+    let mut kills: Vec<(Entity, i32)> = Vec::new();
     // Using a scope to make the borrow checker happy
     {
         let combat_stats = ecs.read_storage::<CombatStats>();
         let entities = ecs.entities();
         let players = ecs.read_storage::<Player>();
         let names = ecs.read_storage::<Name>();
+        let provoked = ecs.read_storage::<Provoked>();
+        let xp_values = ecs.read_storage::<XpValue>();
         let mut log = ecs.write_resource::<GameLog>();
 
         for (entity, stats) in (&entities, &combat_stats).join() {
@@ -54,6 +124,14 @@ pub fn delete_the_dead(ecs: &mut World) {
 
                     log.entries.push(format!("{} is dead", &victim_name.name));
 
+                    // The kill goes to whoever hurt the victim last, so a fireball or a
+                    // trap finishing off what the player wounded still counts for the player
+                    if let (Some(provoked), Some(xp_value)) =
+                        (provoked.get(entity), xp_values.get(entity))
+                    {
+                        kills.push((provoked.by, xp_value.xp));
+                    }
+
                     dead.push(entity);
                 }
                 Some(_) => {
@@ -64,7 +142,48 @@ pub fn delete_the_dead(ecs: &mut World) {
         }
     }
 
+    for (killer, xp) in kills {
+        award_xp(ecs, killer, xp);
+    }
+
+    forget_provocations_by(ecs, &dead);
+
     for victim in dead {
         ecs.delete_entity(victim).expect("Unable to delete");
     }
 }
+
+/// Whoever the dead provoked calms down, a provocation by an entity that is gone cannot be saved
+fn forget_provocations_by(ecs: &mut World, dead: &[Entity]) {
+    let entities = ecs.entities();
+    let mut provoked = ecs.write_storage::<Provoked>();
+    let calmed: Vec<Entity> = (&entities, &provoked)
+        .join()
+        .filter(|(_, provoked)| dead.contains(&provoked.by))
+        .map(|(entity, _)| entity)
+        .collect();
+
+    for entity in calmed {
+        provoked.remove(entity);
+    }
+}
+
+/// Gives the killer the XP, if it is one that earns experience, and tells the player when they
+/// level up
+fn award_xp(ecs: &mut World, killer: Entity, xp: i32) {
+    let mut experiences = ecs.write_storage::<Experience>();
+    let mut combat_stats = ecs.write_storage::<CombatStats>();
+
+    let (Some(experience), Some(stats)) =
+        (experiences.get_mut(killer), combat_stats.get_mut(killer))
+    else {
+        return;
+    };
+
+    if experience::gain_xp(experience, stats, xp) > 0 && killer == *ecs.fetch::<Entity>() {
+        ecs.write_resource::<GameLog>().entries.push(format!(
+            "Welcome to level {}! You feel stronger.",
+            experience.level
+        ));
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use rltk::Point;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use crate::components::{OtherLevelPosition, Position, Viewshed};
+use crate::map::Map;
+
+/// Every level the player has left, keyed by depth, so going back finds it as it was left
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct MasterDungeonMap {
+    levels: BTreeMap<i32, VisitedLevel>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct VisitedLevel {
+    map: Map,
+    /// Where the player took the stairs, which is also where they come back
+    left_from: (i32, i32),
+}
+
+impl MasterDungeonMap {
+    pub fn new() -> MasterDungeonMap {
+        MasterDungeonMap::default()
+    }
+
+    pub fn store_level(&mut self, map: &Map, left_from: Point) {
+        self.levels.insert(
+            map.depth,
+            VisitedLevel {
+                map: map.clone(),
+                left_from: (left_from.x, left_from.y),
+            },
+        );
+    }
+
+    /// Checks the size of every level, see `Map::check_size`
+    pub fn check_levels(&self) -> Result<(), String> {
+        for (depth, level) in self.levels.iter() {
+            level
+                .map
+                .check_size()
+                .map_err(|error| format!("Level {}: {}", depth, error))?;
+        }
+
+        Ok(())
+    }
+
+    /// The level at `depth` and where the player left it, if it was visited before
+    pub fn get_level(&self, depth: i32) -> Option<(Map, Point)> {
+        let level = self.levels.get(&depth)?;
+        let mut map = level.map.clone();
+
+        // The content index is not saved and refers to entities of that time anyway
+        map.tile_content = vec![Vec::new(); map.map_length];
+
+        Some((map, Point::new(level.left_from.0, level.left_from.1)))
+    }
+}
+
+/// Moves everything on the current level but the player out of the world, into
+/// `OtherLevelPosition`s
+pub fn freeze_level_entities(ecs: &mut World) {
+    let entities = ecs.entities();
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
+    let player_entity = ecs.fetch::<Entity>();
+    let depth = ecs.fetch::<Map>().depth;
+
+    let mut frozen = Vec::new();
+
+    for (entity, pos) in (&entities, &positions).join() {
+        if entity != *player_entity {
+            other_level_positions
+                .insert(
+                    entity,
+                    OtherLevelPosition {
+                        x: pos.x,
+                        y: pos.y,
+                        depth,
+                    },
+                )
+                .expect("Unable to insert other level position");
+            frozen.push(entity);
+        }
+    }
+
+    for entity in frozen {
+        positions.remove(entity);
+    }
+}
+
+/// Brings back everything that was frozen on the current level
+pub fn thaw_level_entities(ecs: &mut World) {
+    let entities = ecs.entities();
+    let mut positions = ecs.write_storage::<Position>();
+    let mut other_level_positions = ecs.write_storage::<OtherLevelPosition>();
+    let mut viewsheds = ecs.write_storage::<Viewshed>();
+    let depth = ecs.fetch::<Map>().depth;
+
+    let mut thawed = Vec::new();
+
+    for (entity, pos) in (&entities, &other_level_positions).join() {
+        if pos.depth == depth {
+            positions
+                .insert(entity, Position { x: pos.x, y: pos.y })
+                .expect("Unable to insert position");
+            thawed.push(entity);
+        }
+    }
+
+    for entity in thawed {
+        other_level_positions.remove(entity);
+
+        if let Some(viewshed) = viewsheds.get_mut(entity) {
+            viewshed.dirty = true;
+        }
+    }
+}
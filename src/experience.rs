@@ -0,0 +1,31 @@
+use crate::components::{CombatStats, Experience};
+
+/// The max HP every level adds
+pub const HP_PER_LEVEL: i32 = 5;
+/// The power every level adds
+pub const POWER_PER_LEVEL: i32 = 1;
+/// The XP from level 1 to 2, every level after takes that much more than the one before
+const FIRST_LEVEL_XP: i32 = 20;
+
+/// The XP it takes in all to reach the level
+pub fn xp_for_level(level: i32) -> i32 {
+    FIRST_LEVEL_XP * level * (level - 1) / 2
+}
+
+/// Adds the XP and raises the level for every threshold it passes, each level adding max HP and
+/// power and healing what it adds. Returns how many levels were gained.
+pub fn gain_xp(experience: &mut Experience, stats: &mut CombatStats, xp: i32) -> i32 {
+    let mut levels = 0;
+
+    experience.xp += xp;
+
+    while experience.xp >= xp_for_level(experience.level + 1) {
+        experience.level += 1;
+        stats.max_hp += HP_PER_LEVEL;
+        stats.hp += HP_PER_LEVEL;
+        stats.power += POWER_PER_LEVEL;
+        levels += 1;
+    }
+
+    levels
+}
@@ -0,0 +1,202 @@
+use rltk::Point;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::map::Map;
+
+/// The cost of a step along a row or column, the same ratio to diagonals as `Map` paths use
+pub const CARDINAL_COST: i32 = 100;
+pub const DIAGONAL_COST: i32 = 145;
+/// The value of tiles the target cannot be reached from
+pub const UNREACHABLE: i32 = i32::MAX;
+
+/// How much further a fleeing monster is willing to run to get somewhere with more room, in
+/// percent of the distance to what it runs from
+const FLEE_FACTOR: i32 = 120;
+
+/// A Dijkstra map: every tile holds its cost to the goal, so anything on the level finds its way by
+/// stepping to its lowest neighbour. One map is built per turn and shared by every monster, instead
+/// of each monster searching a path of its own.
+#[derive(Clone, Debug)]
+pub struct FlowMap {
+    width: i32,
+    height: i32,
+    values: Vec<i32>,
+}
+
+/// The maps toward and away from a set of targets, e.g. everyone a faction attacks, shared by
+/// every monster after them for a turn
+pub struct FlowMaps {
+    pub towards: FlowMap,
+    pub away: FlowMap,
+}
+
+impl FlowMaps {
+    /// The `movers` can walk through each other here, they only have to wait for the tile to be
+    /// free
+    pub fn new(map: &Map, targets: &[Point], movers: &[Point]) -> FlowMaps {
+        let passable = passable_tiles(map, movers.iter().chain(targets.iter()));
+        let towards = FlowMap::towards(map, targets, &passable);
+        let away = FlowMap::away_from(&towards, &passable);
+
+        FlowMaps { towards, away }
+    }
+
+    /// The step that takes the monster furthest from the targets, so one next to its attacker gets
+    /// out of reach instead of side-stepping. The flee map only settles ties, alone it would
+    /// happily lead the monster past the targets to the far end of the level.
+    pub fn flee_step(&self, map: &Map, here: Point) -> Option<Point> {
+        let distance = self.towards.value(here);
+
+        self.away
+            .free_neighbours(map, here)
+            .filter(|step| self.towards.value(*step) > distance)
+            .max_by_key(|step| (self.towards.value(*step), Reverse(self.away.value(*step))))
+    }
+}
+
+impl FlowMap {
+    /// The cost of getting to the nearest of `targets` over `passable` tiles
+    pub fn towards(map: &Map, targets: &[Point], passable: &[bool]) -> FlowMap {
+        let mut flow_map = FlowMap {
+            width: map.width,
+            height: map.height,
+            values: vec![UNREACHABLE; map.map_length],
+        };
+
+        for target in targets {
+            if let Some(idx) = flow_map.idx(*target) {
+                flow_map.values[idx] = 0;
+            }
+        }
+
+        flow_map.relax(passable);
+        flow_map
+    }
+
+    /// Lowest far from the goal of `towards`, but rescanned so that a monster running away heads
+    /// for open ground around the goal rather than into the nearest dead end
+    pub fn away_from(towards: &FlowMap, passable: &[bool]) -> FlowMap {
+        let mut flow_map = towards.clone();
+
+        for value in flow_map.values.iter_mut() {
+            if *value != UNREACHABLE {
+                *value = -*value * FLEE_FACTOR / 100;
+            }
+        }
+
+        flow_map.relax(passable);
+        flow_map
+    }
+
+    /// The value of the tile, `UNREACHABLE` off the map
+    pub fn value(&self, point: Point) -> i32 {
+        self.idx(point)
+            .map(|idx| self.values[idx])
+            .unwrap_or(UNREACHABLE)
+    }
+
+    /// The free neighbour with the lowest value, if it is lower than where the monster stands. When
+    /// the best tile is taken the next best is used, so monsters spread out around each other.
+    pub fn next_step(&self, map: &Map, here: Point) -> Option<Point> {
+        self.steps(map, here).into_iter().next()
+    }
+
+    /// The free neighbours lower than where the monster stands, lowest first
+    fn steps(&self, map: &Map, here: Point) -> Vec<Point> {
+        let current = self.value(here);
+        let mut steps: Vec<Point> = self
+            .free_neighbours(map, here)
+            .filter(|point| self.value(*point) < current)
+            .collect();
+
+        steps.sort_by_key(|point| self.value(*point));
+        steps
+    }
+
+    /// The neighbours nothing blocks
+    fn free_neighbours<'a>(
+        &'a self,
+        map: &'a Map,
+        here: Point,
+    ) -> impl Iterator<Item = Point> + 'a {
+        self.neighbours(here)
+            .map(|(point, _)| point)
+            .filter(|point| !map.blocked[map.xy_idx(point.x, point.y)])
+    }
+
+    fn idx(&self, point: Point) -> Option<usize> {
+        if point.x < 0 || point.x >= self.width || point.y < 0 || point.y >= self.height {
+            return None;
+        }
+
+        Some((point.y * self.width + point.x) as usize)
+    }
+
+    /// The neighbours inside the map border, with the cost of stepping there
+    fn neighbours(&self, here: Point) -> impl Iterator<Item = (Point, i32)> + '_ {
+        (-1..=1)
+            .flat_map(move |dy| (-1..=1).map(move |dx| (dx, dy)))
+            .filter(|(dx, dy)| *dx != 0 || *dy != 0)
+            .map(move |(dx, dy)| {
+                let cost = if dx != 0 && dy != 0 {
+                    DIAGONAL_COST
+                } else {
+                    CARDINAL_COST
+                };
+
+                (Point::new(here.x + dx, here.y + dy), cost)
+            })
+            .filter(move |(point, _)| {
+                point.x > 0 && point.x < self.width - 1 && point.y > 0 && point.y < self.height - 1
+            })
+    }
+
+    /// Dijkstra from every tile that already has a value, until no neighbour can be lowered
+    fn relax(&mut self, passable: &[bool]) {
+        let mut open: BinaryHeap<Reverse<(i32, usize)>> = self
+            .values
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value != UNREACHABLE)
+            .map(|(idx, value)| Reverse((*value, idx)))
+            .collect();
+
+        while let Some(Reverse((value, idx))) = open.pop() {
+            if value > self.values[idx] {
+                continue;
+            }
+
+            let here = Point::new(idx as i32 % self.width, idx as i32 / self.width);
+            let lowered: Vec<(usize, i32)> = self
+                .neighbours(here)
+                .filter_map(|(point, cost)| {
+                    let neighbour = self.idx(point)?;
+
+                    (passable[neighbour] && value + cost < self.values[neighbour])
+                        .then_some((neighbour, value + cost))
+                })
+                .collect();
+
+            for (neighbour, value) in lowered {
+                self.values[neighbour] = value;
+                open.push(Reverse((value, neighbour)));
+            }
+        }
+    }
+}
+
+/// Tiles that are not blocked, or blocked only by one of `movers`, which will get out of the way
+pub fn passable_tiles<'a>(map: &Map, movers: impl Iterator<Item = &'a Point>) -> Vec<bool> {
+    let mut passable: Vec<bool> = map.blocked.iter().map(|blocked| !blocked).collect();
+
+    for mover in movers {
+        let idx = map.xy_idx(mover.x, mover.y);
+
+        if idx < passable.len() {
+            passable[idx] = true;
+        }
+    }
+
+    passable
+}
@@ -4,11 +4,16 @@ use specs::prelude::*;
 use crate::components::*;
 use crate::game_log::GameLog;
 use crate::map::Map;
-use crate::{camera, State};
+use crate::player::PlayerCommand;
+use crate::rng::GameSeed;
+use crate::save_slots::SaveSlot;
+use crate::saveload_system::SaveEncoding;
+use crate::{attributes, camera, experience, State};
 
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Copy, Clone, Debug)]
 pub enum MainMenuSelection {
     NewGame,
+    NewGameWithSeed,
     LoadGame,
     Quit,
 }
@@ -40,11 +45,32 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
         &depth,
     );
 
+    let seed = format!("Seed: {}", ecs.fetch::<GameSeed>().0);
+
+    // Inside the bottom right corner, where the log lines rarely reach
+    ctx.print_color(
+        78 - seed.len() as i32,
+        48,
+        RGB::named(rltk::GRAY),
+        RGB::named(rltk::BLACK),
+        &seed,
+    );
+
     let combat_stats = ecs.read_storage::<CombatStats>();
     let players = ecs.read_storage::<Player>();
     let hunger = ecs.read_storage::<HungerClock>();
-
-    for (_player, stats, hunger_clock) in (&players, &combat_stats, &hunger).join() {
+    let statuses = ecs.read_storage::<StatusEffects>();
+    let experiences = ecs.read_storage::<Experience>();
+
+    for (_player, stats, hunger_clock, player_statuses, experience) in (
+        &players,
+        &combat_stats,
+        &hunger,
+        statuses.maybe(),
+        experiences.maybe(),
+    )
+        .join()
+    {
         let health = format!(" HP: {} / {} ", stats.hp, stats.max_hp);
 
         ctx.print_color(
@@ -90,13 +116,35 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
             ),
         }
 
+        if let Some(experience) = experience {
+            let level = format!(
+                " Level: {}  XP: {} / {} ",
+                experience.level,
+                experience.xp,
+                experience::xp_for_level(experience.level + 1)
+            );
+
+            ctx.print_color(
+                50,
+                49,
+                RGB::named(rltk::GOLD),
+                RGB::named(rltk::BLACK),
+                &level,
+            );
+        }
+
+        let mut x = 1;
+
+        for (kind, label) in player_statuses.map(status_labels).unwrap_or_default() {
+            ctx.print_color(x, 42, status_color(kind), RGB::named(rltk::BLACK), &label);
+
+            x += label.len() as i32 + 1;
+        }
+
         let log = ecs.fetch::<GameLog>();
-        let mut y = 44;
 
-        for s in log.entries.iter().rev().take(5) {
+        for (y, s) in (44..).zip(log.entries.iter().rev().take(5)) {
             ctx.print(2, y, s);
-
-            y += 1;
         }
     }
 
@@ -107,6 +155,94 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
     draw_tooltips(ecs, ctx);
 }
 
+/// Every effect the entity is under with the turns it has left, like `Poisoned x2 (5)` when more
+/// than one dose is running
+pub fn status_labels(statuses: &StatusEffects) -> Vec<(StatusKind, String)> {
+    let mut labels: Vec<(StatusKind, String)> = Vec::new();
+
+    for effect in statuses.effects.iter() {
+        if labels.iter().any(|(kind, _)| *kind == effect.kind) {
+            continue;
+        }
+
+        let doses = statuses.doses(effect.kind);
+        let label = if doses > 1 {
+            format!(
+                "{} x{} ({})",
+                effect.kind.name(),
+                doses,
+                statuses.turns_left(effect.kind)
+            )
+        } else {
+            format!("{} ({})", effect.kind.name(), effect.turns)
+        };
+
+        labels.push((effect.kind, label));
+    }
+
+    labels
+}
+
+fn status_color(kind: StatusKind) -> RGB {
+    match kind {
+        StatusKind::Confusion => RGB::named(rltk::MAGENTA),
+        StatusKind::Poison => RGB::named(rltk::GREEN),
+        StatusKind::Regeneration => RGB::named(rltk::PINK),
+        StatusKind::Haste => RGB::named(rltk::YELLOW),
+        StatusKind::Slow => RGB::named(rltk::LIGHT_BLUE),
+        StatusKind::Paralysis => RGB::named(rltk::GRAY),
+    }
+}
+
+/// Debug view of what every monster on the level is up to: a letter on the monster and an `x` on
+/// where it is heading, also for monsters the player cannot see
+pub fn draw_ai_overlay(ecs: &World, ctx: &mut Rltk) {
+    let (min_x, max_x, min_y, max_y) = camera::get_screen_bounds(ecs, ctx);
+    let positions = ecs.read_storage::<Position>();
+    let ais = ecs.read_storage::<Ai>();
+    let on_screen = |point: Point| {
+        point.x >= min_x && point.x < max_x && point.y >= min_y && point.y < i32::min(max_y, 43)
+    };
+
+    ctx.print_color(
+        1,
+        0,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+        "AI: I idle, W wander, C chase, S search, F flee",
+    );
+
+    for (position, ai) in (&positions, &ais).join() {
+        let (letter, color, target) = match ai.state {
+            AiState::Idle { .. } => ('I', rltk::GRAY, None),
+            AiState::Wander { destination } => ('W', rltk::GREEN, Some(destination)),
+            AiState::Chase { last_seen } => ('C', rltk::RED, Some(last_seen)),
+            AiState::Search { last_seen, .. } => ('S', rltk::YELLOW, Some(last_seen)),
+            AiState::Flee => ('F', rltk::CYAN, None),
+        };
+
+        if let Some(target) = target.filter(|target| on_screen(*target)) {
+            ctx.set(
+                target.x - min_x,
+                target.y - min_y,
+                RGB::named(color),
+                RGB::named(rltk::BLACK),
+                rltk::to_cp437('x'),
+            );
+        }
+
+        if on_screen(Point::new(position.x, position.y)) {
+            ctx.set(
+                position.x - min_x,
+                position.y - min_y,
+                RGB::named(rltk::BLACK),
+                RGB::named(color),
+                rltk::to_cp437(letter),
+            );
+        }
+    }
+}
+
 fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     let (min_x, _max_x, min_y, _max_y) = camera::get_screen_bounds(ecs, ctx);
     let map = ecs.fetch::<Map>();
@@ -151,8 +287,7 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
         if mouse_pos.0 > 40 {
             let arrow_pos = Point::new(mouse_pos.0 - 2, mouse_pos.1);
             let left_x = mouse_pos.0 - width;
-            let mut y = mouse_pos.1;
-            for s in tooltip.iter() {
+            for (y, s) in (mouse_pos.1..).zip(tooltip.iter()) {
                 ctx.print_color(
                     left_x,
                     y,
@@ -167,23 +302,21 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
                         y,
                         RGB::named(rltk::WHITE),
                         RGB::named(rltk::DARKSLATEGREY),
-                        &" ".to_string(),
+                        " ",
                     );
                 }
-                y += 1;
             }
             ctx.print_color(
                 arrow_pos.x,
                 arrow_pos.y,
                 RGB::named(rltk::WHITE),
                 RGB::named(rltk::DARKSLATEGREY),
-                &"->".to_string(),
+                "->",
             );
         } else {
             let arrow_pos = Point::new(mouse_pos.0 + 1, mouse_pos.1);
             let left_x = mouse_pos.0 + 3;
-            let mut y = mouse_pos.1;
-            for s in tooltip.iter() {
+            for (y, s) in (mouse_pos.1..).zip(tooltip.iter()) {
                 ctx.print_color(
                     left_x + 1,
                     y,
@@ -198,41 +331,74 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
                         y,
                         RGB::named(rltk::WHITE),
                         RGB::named(rltk::DARKSLATEGREY),
-                        &" ".to_string(),
+                        " ",
                     );
                 }
-                y += 1;
             }
             ctx.print_color(
                 arrow_pos.x,
                 arrow_pos.y,
                 RGB::named(rltk::WHITE),
                 RGB::named(rltk::DARKSLATEGREY),
-                &"<-".to_string(),
+                "<-",
             );
         }
     }
 }
 
-#[derive(PartialEq, Copy, Clone)]
-pub enum ItemMenuResult {
-    Cancel,
-    NoResponse,
-    Selected,
+/// Items in the player's backpack, in the order the inventory menus list them
+pub fn backpack_items(ecs: &World) -> Vec<Entity> {
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let entities = ecs.entities();
+
+    (&entities, &backpack, &names)
+        .join()
+        .filter(|item| item.1.owner == *player_entity)
+        .map(|item| item.0)
+        .collect()
 }
 
-pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let names = gs.ecs.read_storage::<Name>();
-    let backpack = gs.ecs.read_storage::<InBackpack>();
-    let entities = gs.ecs.entities();
+/// Items the player has equipped, in the order the remove menu lists them
+pub fn equipped_items(ecs: &World) -> Vec<Entity> {
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let entities = ecs.entities();
 
-    let inventory = (&backpack, &names)
+    (&entities, &equipped, &names)
         .join()
-        .filter(|item| item.0.owner == *player_entity);
-    let count = inventory.count();
+        .filter(|item| item.1.owner == *player_entity)
+        .map(|item| item.0)
+        .collect()
+}
+
+/// Tiles the player can see that are within `range`, i.e. valid ranged targets
+pub fn targetable_tiles(ecs: &World, range: i32) -> Option<Vec<Point>> {
+    let player_entity = ecs.fetch::<Entity>();
+    let player_pos = ecs.fetch::<Point>();
+    let viewsheds = ecs.read_storage::<Viewshed>();
+
+    let visible = viewsheds.get(*player_entity)?;
+
+    Some(
+        visible
+            .visible_tiles
+            .iter()
+            .filter(|idx| {
+                rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, **idx) <= range as f32
+            })
+            .copied()
+            .collect(),
+    )
+}
 
-    let mut y = (25 - (count / 2)) as i32;
+fn item_menu(ecs: &World, ctx: &mut Rltk, title: &str, items: &[Entity]) -> Option<PlayerCommand> {
+    let names = ecs.read_storage::<Name>();
+    let count = items.len();
+
+    let y = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
         y - 2,
@@ -246,7 +412,7 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
         y - 2,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "Inventory",
+        title,
     );
     ctx.print_color(
         18,
@@ -256,12 +422,7 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
         "ESCAPE to cancel",
     );
 
-    let mut equippable: Vec<Entity> = Vec::new();
-    for (j, (entity, _pack, name)) in (&entities, &backpack, &names)
-        .join()
-        .filter(|item| item.1.owner == *player_entity)
-        .enumerate()
-    {
+    for (j, (y, item)) in (y..).zip(items.iter()).enumerate() {
         ctx.set(
             17,
             y,
@@ -284,48 +445,122 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
             rltk::to_cp437(')'),
         );
 
-        ctx.print(21, y, &name.name.to_string());
-        equippable.push(entity);
-        y += 1;
+        if let Some(name) = names.get(*item) {
+            ctx.print(21, y, &name.name);
+        }
     }
 
     match ctx.key {
-        None => (ItemMenuResult::NoResponse, None),
+        None => None,
         Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
+            VirtualKeyCode::Escape => Some(PlayerCommand::Cancel),
             _ => {
                 let selection = rltk::letter_to_option(key);
 
                 if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
+                    return Some(PlayerCommand::SelectItem {
+                        index: selection as usize,
+                    });
                 }
 
-                (ItemMenuResult::NoResponse, None)
+                None
             }
         },
     }
 }
 
-pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let names = gs.ecs.read_storage::<Name>();
-    let backpack = gs.ecs.read_storage::<InBackpack>();
-    let entities = gs.ecs.entities();
+pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> Option<PlayerCommand> {
+    let items = backpack_items(&gs.ecs);
 
-    let inventory = (&backpack, &names)
-        .join()
-        .filter(|item| item.0.owner == *player_entity);
-    let count = inventory.count();
+    item_menu(&gs.ecs, ctx, "Inventory", &items)
+}
+
+pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> Option<PlayerCommand> {
+    let items = backpack_items(&gs.ecs);
+
+    item_menu(&gs.ecs, ctx, "Drop Which Item?", &items)
+}
+
+pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> Option<PlayerCommand> {
+    let items = equipped_items(&gs.ecs);
+
+    item_menu(&gs.ecs, ctx, "Remove Which Item?", &items)
+}
+
+/// The lines of the character screen: level, combat stats, attributes with their bonus, and skills
+/// with the practice towards their next level
+pub fn character_sheet(ecs: &World) -> Vec<String> {
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut lines = Vec::new();
+
+    if let Some(experience) = ecs.read_storage::<Experience>().get(player_entity) {
+        lines.push(format!(
+            "Level {} (XP {} / {})",
+            experience.level,
+            experience.xp,
+            experience::xp_for_level(experience.level + 1)
+        ));
+    }
+
+    if let Some(stats) = ecs.read_storage::<CombatStats>().get(player_entity) {
+        lines.push(format!(
+            "HP {} / {}, Power {}, Defense {}",
+            stats.hp, stats.max_hp, stats.power, stats.defense
+        ));
+    }
+
+    if let Some(player_attributes) = ecs.read_storage::<Attributes>().get(player_entity) {
+        lines.push(String::new());
+
+        for (name, value) in [
+            ("Might", player_attributes.might),
+            ("Fitness", player_attributes.fitness),
+            ("Quickness", player_attributes.quickness),
+            ("Intelligence", player_attributes.intelligence),
+        ] {
+            lines.push(format!(
+                "{:<13}{:>3} ({:+})",
+                name,
+                value,
+                attributes::bonus(value)
+            ));
+        }
+
+        lines.push(format!(
+            "Sees {} tiles, hunger every {} turns",
+            attributes::vision_range(player_attributes),
+            attributes::hunger_duration(Some(player_attributes))
+        ));
+    }
+
+    if let Some(skills) = ecs.read_storage::<Skills>().get(player_entity) {
+        lines.push(String::new());
+
+        for kind in [SkillKind::Melee, SkillKind::Defense, SkillKind::Magic] {
+            let skill = skills.get(kind);
+
+            lines.push(format!(
+                "{:<13}{:>3} ({} / {})",
+                kind.name(),
+                skill.level,
+                skill.practice,
+                attributes::practice_for_level(skill.level + 1)
+            ));
+        }
+    }
+
+    lines
+}
+
+pub fn show_character(gs: &mut State, ctx: &mut Rltk) -> Option<PlayerCommand> {
+    let lines = character_sheet(&gs.ecs);
+    let y = 25 - (lines.len() / 2) as i32;
 
-    let mut y = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
         y - 2,
-        31,
-        (count + 3) as i32,
+        41,
+        lines.len() as i32 + 3,
         RGB::named(rltk::WHITE),
         RGB::named(rltk::BLACK),
     );
@@ -334,76 +569,28 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
         y - 2,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "Drop Which Item?",
+        "Character",
     );
     ctx.print_color(
         18,
-        y + count as i32 + 1,
+        y + lines.len() as i32 + 1,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "ESCAPE to cancel",
+        "ESCAPE to close",
     );
 
-    let mut equippable: Vec<Entity> = Vec::new();
-    for (j, (entity, _pack, name)) in (&entities, &backpack, &names)
-        .join()
-        .filter(|item| item.1.owner == *player_entity)
-        .enumerate()
-    {
-        ctx.set(
-            17,
-            y,
-            RGB::named(rltk::WHITE),
-            RGB::named(rltk::BLACK),
-            rltk::to_cp437('('),
-        );
-        ctx.set(
-            18,
-            y,
-            RGB::named(rltk::YELLOW),
-            RGB::named(rltk::BLACK),
-            97 + j as rltk::FontCharType,
-        );
-        ctx.set(
-            19,
-            y,
-            RGB::named(rltk::WHITE),
-            RGB::named(rltk::BLACK),
-            rltk::to_cp437(')'),
-        );
-
-        ctx.print(21, y, &name.name.to_string());
-        equippable.push(entity);
-        y += 1;
+    for (y, line) in (y..).zip(lines.iter()) {
+        ctx.print(17, y, line);
     }
 
     match ctx.key {
-        None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            _ => {
-                let selection = rltk::letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
-                }
-                (ItemMenuResult::NoResponse, None)
-            }
-        },
+        Some(VirtualKeyCode::Escape) => Some(PlayerCommand::Cancel),
+        _ => None,
     }
 }
 
-pub fn ranged_target(
-    gs: &mut State,
-    ctx: &mut Rltk,
-    range: i32,
-) -> (ItemMenuResult, Option<Point>) {
+pub fn ranged_target(gs: &mut State, ctx: &mut Rltk, range: i32) -> Option<PlayerCommand> {
     let (min_x, max_x, min_y, max_y) = camera::get_screen_bounds(&gs.ecs, ctx);
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let player_pos = gs.ecs.fetch::<Point>();
-    let viewsheds = gs.ecs.read_storage::<Viewshed>();
 
     ctx.print_color(
         5,
@@ -415,29 +602,23 @@ pub fn ranged_target(
 
     // Highlight available target cells
     let mut available_cells = Vec::new();
-    let visible = viewsheds.get(*player_entity);
-
-    if let Some(visible) = visible {
-        // We have a viewshed
-        for idx in visible.visible_tiles.iter() {
-            let distance = rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, *idx);
-
-            if distance <= range as f32 {
-                let screen_x = idx.x - min_x;
-                let screen_y = idx.y - min_y;
-
-                if screen_x > 1
-                    && screen_x < (max_x - min_x) - 1
-                    && screen_y > 1
-                    && screen_y < (max_y - min_y) - 1
-                {
-                    ctx.set_bg(screen_x, screen_y, RGB::named(rltk::BLUE));
-                    available_cells.push(idx);
-                }
+
+    if let Some(targetable) = targetable_tiles(&gs.ecs, range) {
+        for idx in targetable.iter() {
+            let screen_x = idx.x - min_x;
+            let screen_y = idx.y - min_y;
+
+            if screen_x > 1
+                && screen_x < (max_x - min_x) - 1
+                && screen_y > 1
+                && screen_y < (max_y - min_y) - 1
+            {
+                ctx.set_bg(screen_x, screen_y, RGB::named(rltk::BLUE));
+                available_cells.push(*idx);
             }
         }
     } else {
-        return (ItemMenuResult::Cancel, None);
+        return Some(PlayerCommand::Cancel);
     }
 
     // Draw mouse cursor
@@ -459,106 +640,21 @@ pub fn ranged_target(
         ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::CYAN));
 
         if ctx.left_click {
-            return (
-                ItemMenuResult::Selected,
-                Some(Point::new(mouse_pos.0, mouse_pos.1)),
-            );
+            // The target is a map position, not a screen one
+            return Some(PlayerCommand::Target {
+                x: mouse_map_pos.0,
+                y: mouse_map_pos.1,
+            });
         }
     } else {
         ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::RED));
 
         if ctx.left_click {
-            return (ItemMenuResult::Cancel, None);
+            return Some(PlayerCommand::Cancel);
         }
     }
 
-    (ItemMenuResult::NoResponse, None)
-}
-
-pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let names = gs.ecs.read_storage::<Name>();
-    let backpack = gs.ecs.read_storage::<Equipped>();
-    let entities = gs.ecs.entities();
-
-    let inventory = (&backpack, &names)
-        .join()
-        .filter(|item| item.0.owner == *player_entity);
-    let count = inventory.count();
-
-    let mut y = (25 - (count / 2)) as i32;
-    ctx.draw_box(
-        15,
-        y - 2,
-        31,
-        (count + 3) as i32,
-        RGB::named(rltk::WHITE),
-        RGB::named(rltk::BLACK),
-    );
-    ctx.print_color(
-        18,
-        y - 2,
-        RGB::named(rltk::YELLOW),
-        RGB::named(rltk::BLACK),
-        "Remove Which Item?",
-    );
-    ctx.print_color(
-        18,
-        y + count as i32 + 1,
-        RGB::named(rltk::YELLOW),
-        RGB::named(rltk::BLACK),
-        "ESCAPE to cancel",
-    );
-
-    let mut equippable: Vec<Entity> = Vec::new();
-    for (j, (entity, _pack, name)) in (&entities, &backpack, &names)
-        .join()
-        .filter(|item| item.1.owner == *player_entity)
-        .enumerate()
-    {
-        ctx.set(
-            17,
-            y,
-            RGB::named(rltk::WHITE),
-            RGB::named(rltk::BLACK),
-            rltk::to_cp437('('),
-        );
-        ctx.set(
-            18,
-            y,
-            RGB::named(rltk::YELLOW),
-            RGB::named(rltk::BLACK),
-            97 + j as rltk::FontCharType,
-        );
-        ctx.set(
-            19,
-            y,
-            RGB::named(rltk::WHITE),
-            RGB::named(rltk::BLACK),
-            rltk::to_cp437(')'),
-        );
-
-        ctx.print(21, y, &name.name.to_string());
-        equippable.push(entity);
-        y += 1;
-    }
-
-    match ctx.key {
-        None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            _ => {
-                let selection = rltk::letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
-                }
-                (ItemMenuResult::NoResponse, None)
-            }
-        },
-    }
+    None
 }
 
 #[derive(PartialEq, Copy, Clone)]
@@ -599,3 +695,231 @@ pub fn game_over(ctx: &mut Rltk) -> GameOverResult {
         Some(_) => GameOverResult::QuitToMenu,
     }
 }
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum SeedEntryResult {
+    NoSelection { seed: u64 },
+    Cancel,
+    Selected { seed: u64 },
+}
+
+/// Lets the player type in the seed for a new game, digit by digit
+pub fn seed_entry(ctx: &mut Rltk, seed: u64) -> SeedEntryResult {
+    ctx.draw_box_double(
+        20,
+        20,
+        39,
+        6,
+        RGB::named(rltk::WHEAT),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color_centered(
+        21,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "New Game With Seed",
+    );
+    ctx.print_color_centered(
+        23,
+        RGB::named(rltk::MAGENTA),
+        RGB::named(rltk::BLACK),
+        seed.to_string(),
+    );
+    ctx.print_color_centered(
+        25,
+        RGB::named(rltk::GRAY),
+        RGB::named(rltk::BLACK),
+        "Type digits, Enter to start, ESC to go back",
+    );
+
+    let digit = match ctx.key {
+        Some(VirtualKeyCode::Key0) | Some(VirtualKeyCode::Numpad0) => Some(0),
+        Some(VirtualKeyCode::Key1) | Some(VirtualKeyCode::Numpad1) => Some(1),
+        Some(VirtualKeyCode::Key2) | Some(VirtualKeyCode::Numpad2) => Some(2),
+        Some(VirtualKeyCode::Key3) | Some(VirtualKeyCode::Numpad3) => Some(3),
+        Some(VirtualKeyCode::Key4) | Some(VirtualKeyCode::Numpad4) => Some(4),
+        Some(VirtualKeyCode::Key5) | Some(VirtualKeyCode::Numpad5) => Some(5),
+        Some(VirtualKeyCode::Key6) | Some(VirtualKeyCode::Numpad6) => Some(6),
+        Some(VirtualKeyCode::Key7) | Some(VirtualKeyCode::Numpad7) => Some(7),
+        Some(VirtualKeyCode::Key8) | Some(VirtualKeyCode::Numpad8) => Some(8),
+        Some(VirtualKeyCode::Key9) | Some(VirtualKeyCode::Numpad9) => Some(9),
+        _ => None,
+    };
+
+    if let Some(digit) = digit {
+        // Digits that would overflow the seed are ignored
+        let seed = seed
+            .checked_mul(10)
+            .and_then(|seed| seed.checked_add(digit))
+            .unwrap_or(seed);
+
+        return SeedEntryResult::NoSelection { seed };
+    }
+
+    match ctx.key {
+        Some(VirtualKeyCode::Escape) => SeedEntryResult::Cancel,
+        Some(VirtualKeyCode::Back) => SeedEntryResult::NoSelection { seed: seed / 10 },
+        Some(VirtualKeyCode::Return) => SeedEntryResult::Selected { seed },
+        _ => SeedEntryResult::NoSelection { seed },
+    }
+}
+
+/// How many rows of the slot picker fit on the screen at once, each takes two lines
+const VISIBLE_SLOT_ROWS: usize = 8;
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum SlotPickerResult {
+    NoSelection {
+        selection: usize,
+        confirm_delete: bool,
+    },
+    Cancel,
+    Selected {
+        selection: usize,
+    },
+    Delete {
+        selection: usize,
+    },
+    SwitchEncoding {
+        selection: usize,
+    },
+}
+
+/// Lists the save slots to pick one. Saving passes the `encoding` the save gets, which F switches,
+/// and gets a "New slot" row on top that `selection` counts; deleting a slot takes pressing D
+/// twice.
+pub fn slot_picker(
+    ctx: &mut Rltk,
+    title: &str,
+    slots: &[SaveSlot],
+    encoding: Option<SaveEncoding>,
+    selection: usize,
+    confirm_delete: bool,
+    message: Option<&str>,
+) -> SlotPickerResult {
+    let mut rows: Vec<(String, String)> = Vec::new();
+    let new_slot_row = encoding.is_some();
+
+    if new_slot_row {
+        rows.push(("New slot".to_string(), String::new()));
+    }
+    rows.extend(slots.iter().map(|slot| (slot.title(), slot.describe())));
+
+    let first_row = selection.saturating_sub(VISIBLE_SLOT_ROWS - 1);
+    let top = 10;
+    let bottom = top + 3 + 2 * VISIBLE_SLOT_ROWS as i32;
+
+    ctx.draw_box_double(
+        1,
+        top,
+        77,
+        bottom - top + 3,
+        RGB::named(rltk::WHEAT),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color_centered(
+        top + 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        title,
+    );
+
+    if let Some(encoding) = encoding {
+        ctx.print_color_centered(
+            top + 2,
+            RGB::named(rltk::GRAY),
+            RGB::named(rltk::BLACK),
+            format!("Format: {} (F to switch)", encoding.name()),
+        );
+    }
+
+    if first_row > 0 {
+        ctx.print_color(
+            76,
+            top + 2,
+            RGB::named(rltk::GRAY),
+            RGB::named(rltk::BLACK),
+            "^",
+        );
+    }
+    if first_row + VISIBLE_SLOT_ROWS < rows.len() {
+        ctx.print_color(
+            76,
+            bottom - 1,
+            RGB::named(rltk::GRAY),
+            RGB::named(rltk::BLACK),
+            "v",
+        );
+    }
+
+    let visible_rows = rows
+        .iter()
+        .enumerate()
+        .skip(first_row)
+        .take(VISIBLE_SLOT_ROWS);
+
+    for (y, (i, (row_title, description))) in (top + 3..).step_by(2).zip(visible_rows) {
+        let color = if i == selection {
+            RGB::named(rltk::MAGENTA)
+        } else {
+            RGB::named(rltk::WHITE)
+        };
+
+        ctx.print_color(3, y, color, RGB::named(rltk::BLACK), row_title);
+        ctx.print_color(5, y + 1, color, RGB::named(rltk::BLACK), description);
+    }
+
+    let is_slot = selection < rows.len() && !(new_slot_row && selection == 0);
+    let help = if confirm_delete && is_slot {
+        format!("Press D again to delete {}", rows[selection].0)
+    } else {
+        "Up/Down to choose, Enter to pick, D to delete, ESC to go back".to_string()
+    };
+
+    ctx.print_color_centered(
+        bottom + 1,
+        RGB::named(rltk::GRAY),
+        RGB::named(rltk::BLACK),
+        help,
+    );
+
+    if let Some(message) = message {
+        ctx.print_color_centered(
+            bottom + 2,
+            RGB::named(rltk::RED),
+            RGB::named(rltk::BLACK),
+            message,
+        );
+    }
+
+    let no_selection = |selection| SlotPickerResult::NoSelection {
+        selection,
+        confirm_delete: false,
+    };
+
+    match ctx.key {
+        None => SlotPickerResult::NoSelection {
+            selection,
+            confirm_delete,
+        },
+        Some(VirtualKeyCode::Escape) => SlotPickerResult::Cancel,
+        Some(VirtualKeyCode::Up) => no_selection(selection.saturating_sub(1)),
+        Some(VirtualKeyCode::Down) => {
+            no_selection(usize::min(selection + 1, rows.len().saturating_sub(1)))
+        }
+        Some(VirtualKeyCode::Return) if selection < rows.len() => {
+            SlotPickerResult::Selected { selection }
+        }
+        Some(VirtualKeyCode::D) if is_slot && confirm_delete => {
+            SlotPickerResult::Delete { selection }
+        }
+        Some(VirtualKeyCode::D) if is_slot => SlotPickerResult::NoSelection {
+            selection,
+            confirm_delete: true,
+        },
+        Some(VirtualKeyCode::F) if encoding.is_some() => {
+            SlotPickerResult::SwitchEncoding { selection }
+        }
+        Some(_) => no_selection(selection),
+    }
+}
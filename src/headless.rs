@@ -0,0 +1,296 @@
+use specs::prelude::*;
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+use crate::components::{CombatStats, Experience, StatusEffects};
+use crate::game_log::GameLog;
+use crate::gui::{self, MainMenuSelection};
+use crate::map::Map;
+use crate::player::PlayerCommand;
+use crate::replay::Recorder;
+use crate::rng::GameSeed;
+use crate::{experience, particle_system, rng, RunState, State};
+
+/// How much time a headless step pretends to take, so particles still age out
+const FRAME_TIME_MS: f32 = 1000.0 / 60.0;
+
+/// Drives the `RunState` machine from a stream of commands, without ever opening a window.
+pub struct HeadlessGame {
+    pub state: State,
+}
+
+impl Default for HeadlessGame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HeadlessGame {
+    pub fn new() -> HeadlessGame {
+        HeadlessGame::with_seed(rng::random_seed())
+    }
+
+    pub fn with_seed(seed: u64) -> HeadlessGame {
+        let mut state = State::with_seed(seed);
+
+        state.commit_run_state(RunState::PreRun);
+
+        let mut game = HeadlessGame { state };
+
+        game.run_until_input();
+
+        game
+    }
+
+    pub fn run_state(&self) -> RunState {
+        *self.state.ecs.fetch::<RunState>()
+    }
+
+    /// The game is over once the player has died or has saved and left to the main menu
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.run_state(),
+            RunState::GameOver | RunState::MainMenu { .. }
+        )
+    }
+
+    /// Feeds a single command to the game and runs it until it waits for the next one
+    pub fn apply(&mut self, command: PlayerCommand) -> RunState {
+        if self.is_finished() {
+            return self.run_state();
+        }
+
+        let run_state = self.run_state();
+        let new_run_state = self.state.handle_command(run_state, command);
+
+        self.state.commit_run_state(new_run_state);
+        self.run_until_input();
+
+        self.run_state()
+    }
+
+    /// Starts over with a new game, just like picking a seed from the main menu
+    pub fn new_game(&mut self, seed: u64) {
+        self.state.start_new_game(seed);
+        self.state.commit_run_state(RunState::PreRun);
+        self.run_until_input();
+    }
+
+    /// Applies commands until they run out or the game is finished, returns how many were used
+    pub fn run<I: IntoIterator<Item = PlayerCommand>>(&mut self, commands: I) -> usize {
+        let mut applied = 0;
+
+        for command in commands {
+            if self.is_finished() {
+                break;
+            }
+
+            self.apply(command);
+            applied += 1;
+        }
+
+        applied
+    }
+
+    fn run_until_input(&mut self) {
+        loop {
+            particle_system::cull_dead_particles(&mut self.state.ecs, FRAME_TIME_MS);
+
+            let run_state = self.run_state();
+            let new_run_state = match run_state {
+                RunState::AwaitingInput
+                | RunState::ShowInventory
+                | RunState::ShowDropItem
+                | RunState::ShowRemoveItem
+                | RunState::ShowCharacter
+                | RunState::ShowTargeting { .. }
+                | RunState::MainMenu { .. }
+                | RunState::EnterSeed { .. }
+                | RunState::SaveSlots { .. }
+                | RunState::LoadSlots { .. }
+                | RunState::GameOver => return,
+                // There is no slot picker without a window, every save gets a new slot
+                RunState::SaveGame => {
+                    if let Err(error) = self.state.save_slots.save_new(&mut self.state.ecs) {
+                        eprintln!("Unable to save the game. {}", error);
+                    }
+
+                    RunState::MainMenu {
+                        menu_selection: MainMenuSelection::LoadGame,
+                    }
+                }
+                RunState::MapGeneration => RunState::PreRun,
+                _ => self.state.advance(run_state),
+            };
+
+            self.state.commit_run_state(new_run_state);
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let ecs = &self.state.ecs;
+        let player_entity = *ecs.fetch::<Entity>();
+        let map = ecs.fetch::<Map>();
+        let combat_stats = ecs.read_storage::<CombatStats>();
+        let log = ecs.fetch::<GameLog>();
+
+        let mut lines = vec![
+            format!("Run state: {:?}", self.run_state()),
+            format!("Seed: {}", ecs.fetch::<GameSeed>().0),
+            format!("Depth: {}", map.depth),
+        ];
+
+        if let Some(stats) = combat_stats.get(player_entity) {
+            lines.push(format!("HP: {} / {}", stats.hp, stats.max_hp));
+        }
+
+        if let Some(experience) = ecs.read_storage::<Experience>().get(player_entity) {
+            lines.push(format!(
+                "Level: {} (XP: {} / {})",
+                experience.level,
+                experience.xp,
+                experience::xp_for_level(experience.level + 1)
+            ));
+        }
+
+        if let Some(statuses) = ecs.read_storage::<StatusEffects>().get(player_entity) {
+            let labels: Vec<String> = gui::status_labels(statuses)
+                .into_iter()
+                .map(|(_, label)| label)
+                .collect();
+
+            lines.push(format!("Status: {}", labels.join(", ")));
+        }
+
+        // The character screen only shows while it is open, like in the game
+        if self.run_state() == RunState::ShowCharacter {
+            lines.push("Character:".to_string());
+
+            for line in gui::character_sheet(ecs) {
+                lines.push(format!("  {}", line).trim_end().to_string());
+            }
+        }
+
+        lines.push("Log:".to_string());
+
+        for entry in log.entries.iter() {
+            lines.push(format!("  {}", entry));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Script lines look like `move 1 0`, `wait`, `pickup`, `inventory`, `drop`, `remove`,
+/// `character`, `save`, `descend`, `ascend`, `select b`, `target 10 12` and `cancel`.
+impl FromStr for PlayerCommand {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let number = |word: &str| {
+            word.parse::<i32>()
+                .map_err(|_| format!("Expected a number, got '{}'", word))
+        };
+
+        let command = match words.as_slice() {
+            ["move", delta_x, delta_y] => PlayerCommand::Move {
+                delta_x: number(delta_x)?,
+                delta_y: number(delta_y)?,
+            },
+            ["wait"] => PlayerCommand::SkipTurn,
+            ["pickup"] => PlayerCommand::PickUp,
+            ["inventory"] => PlayerCommand::ShowInventory,
+            ["drop"] => PlayerCommand::ShowDropItem,
+            ["remove"] => PlayerCommand::ShowRemoveItem,
+            ["character"] => PlayerCommand::ShowCharacter,
+            ["save"] => PlayerCommand::SaveGame,
+            ["descend"] => PlayerCommand::Descend,
+            ["ascend"] => PlayerCommand::Ascend,
+            ["select", letter] => match letter.as_bytes() {
+                [letter @ b'a'..=b'z'] => PlayerCommand::SelectItem {
+                    index: (letter - b'a') as usize,
+                },
+                _ => match letter.parse::<usize>() {
+                    Ok(index) => PlayerCommand::SelectItem { index },
+                    Err(_) => return Err(format!("Expected a menu letter, got '{}'", letter)),
+                },
+            },
+            ["target", x, y] => PlayerCommand::Target {
+                x: number(x)?,
+                y: number(y)?,
+            },
+            ["cancel"] => PlayerCommand::Cancel,
+            _ => return Err(format!("Unknown command '{}'", line.trim())),
+        };
+
+        Ok(command)
+    }
+}
+
+/// Writes a command back in the script syntax, so that it can be parsed again
+impl fmt::Display for PlayerCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayerCommand::Move { delta_x, delta_y } => write!(f, "move {} {}", delta_x, delta_y),
+            PlayerCommand::SkipTurn => write!(f, "wait"),
+            PlayerCommand::PickUp => write!(f, "pickup"),
+            PlayerCommand::ShowInventory => write!(f, "inventory"),
+            PlayerCommand::ShowDropItem => write!(f, "drop"),
+            PlayerCommand::ShowRemoveItem => write!(f, "remove"),
+            PlayerCommand::ShowCharacter => write!(f, "character"),
+            PlayerCommand::SaveGame => write!(f, "save"),
+            PlayerCommand::Descend => write!(f, "descend"),
+            PlayerCommand::Ascend => write!(f, "ascend"),
+            PlayerCommand::SelectItem { index } => match u8::try_from(*index) {
+                Ok(index @ 0..=25) => write!(f, "select {}", (b'a' + index) as char),
+                _ => write!(f, "select {}", index),
+            },
+            PlayerCommand::Target { x, y } => write!(f, "target {} {}", x, y),
+            PlayerCommand::Cancel => write!(f, "cancel"),
+        }
+    }
+}
+
+/// Parses a script, one command per line. Blank lines and lines starting with `#` are skipped.
+pub fn parse_script(script: &str) -> Result<Vec<PlayerCommand>, String> {
+    script
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| {
+            line.parse::<PlayerCommand>()
+                .map_err(|error| format!("Line {}: {}", i + 1, error))
+        })
+        .collect()
+}
+
+/// Returns the script path given with `--headless <script>`, if any
+pub fn script_argument(args: &[String]) -> Option<String> {
+    let position = args.iter().position(|arg| arg == "--headless")?;
+
+    args.get(position + 1).cloned()
+}
+
+/// Runs a script on a new game from `seed`, recording it as a replay to `record_path` if given
+pub fn run_script_file(path: &str, seed: u64, record_path: Option<&str>) -> Result<String, String> {
+    let script = fs::read_to_string(path)
+        .map_err(|error| format!("Unable to read script {}: {}", path, error))?;
+    let commands = parse_script(&script)?;
+
+    let mut game = HeadlessGame::with_seed(seed);
+
+    if let Some(record_path) = record_path {
+        game.state.record_to(Recorder::create(record_path, seed)?);
+    }
+
+    let applied = game.run(commands.iter().copied());
+
+    Ok(format!(
+        "Commands run: {} of {}\n{}",
+        applied,
+        commands.len(),
+        game.summary()
+    ))
+}
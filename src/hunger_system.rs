@@ -1,5 +1,6 @@
 use specs::prelude::*;
 
+use crate::attributes;
 use crate::components::*;
 use crate::game_log::GameLog;
 use crate::RunState;
@@ -14,6 +15,7 @@ impl<'a> System<'a> for HungerSystem {
         ReadExpect<'a, RunState>,
         WriteStorage<'a, SufferDamage>,
         WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Attributes>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -24,9 +26,10 @@ impl<'a> System<'a> for HungerSystem {
             run_state,
             mut inflict_damage,
             mut game_log,
+            attributes,
         ) = data;
 
-        for (entity, mut clock) in (&entities, &mut hunger_clock).join() {
+        for (entity, clock) in (&entities, &mut hunger_clock).join() {
             let is_player_entity = entity == *player_entity;
             let proceed = match *run_state {
                 RunState::PlayerTurn => is_player_entity,
@@ -44,10 +47,12 @@ impl<'a> System<'a> for HungerSystem {
                 continue;
             }
 
+            let duration = attributes::hunger_duration(attributes.get(entity));
+
             match clock.state {
                 HungerState::WellFed => {
                     clock.state = HungerState::Normal;
-                    clock.duration = 200;
+                    clock.duration = duration;
 
                     if is_player_entity {
                         game_log
@@ -57,7 +62,7 @@ impl<'a> System<'a> for HungerSystem {
                 }
                 HungerState::Normal => {
                     clock.state = HungerState::Hungry;
-                    clock.duration = 200;
+                    clock.duration = duration;
 
                     if is_player_entity {
                         game_log.entries.push("You are hungry.".to_string());
@@ -65,7 +70,7 @@ impl<'a> System<'a> for HungerSystem {
                 }
                 HungerState::Hungry => {
                     clock.state = HungerState::Starving;
-                    clock.duration = 200;
+                    clock.duration = duration;
 
                     if is_player_entity {
                         game_log.entries.push("You are starving!".to_string());
@@ -81,7 +86,7 @@ impl<'a> System<'a> for HungerSystem {
                         );
                     }
 
-                    SufferDamage::new_damage(&mut inflict_damage, entity, 1);
+                    SufferDamage::new_damage(&mut inflict_damage, entity, 1, DamageType::Physical);
                 }
             }
         }
@@ -0,0 +1,150 @@
+use specs::prelude::*;
+
+use crate::components::*;
+use crate::player::PlayerCommand;
+use crate::status_effect_system::StatusEffectSystem;
+use crate::RunState;
+
+/// What moving, attacking, waiting and using an item cost
+pub const ACTION_COST: i32 = 100;
+/// What picking up, dropping and taking off an item cost
+pub const QUICK_ACTION_COST: i32 = 50;
+/// The speed of the player, and of mobs the raws give no speed
+pub const NORMAL_SPEED: i32 = 100;
+/// No amount of armour or slowing takes anything below this
+const MINIMUM_SPEED: i32 = 20;
+
+/// The energy the player pays for a command that ends their turn
+pub fn action_cost(run_state: RunState, command: PlayerCommand) -> i32 {
+    match (run_state, command) {
+        (RunState::AwaitingInput, PlayerCommand::PickUp)
+        | (RunState::ShowDropItem, _)
+        | (RunState::ShowRemoveItem, _) => QUICK_ACTION_COST,
+        _ => ACTION_COST,
+    }
+}
+
+pub fn spend_energy(ecs: &World, entity: Entity, cost: i32) {
+    if let Some(initiative) = ecs.write_storage::<Initiative>().get_mut(entity) {
+        initiative.energy -= cost;
+    }
+}
+
+/// The energy the entity gains every tick, 0 when it has no initiative
+pub fn speed_of(ecs: &World, entity: Entity) -> i32 {
+    let Some(initiative) = ecs.read_storage::<Initiative>().get(entity).cloned() else {
+        return 0;
+    };
+    let penalty = equipment_penalty(
+        entity,
+        &ecs.read_storage::<Equipped>(),
+        &ecs.read_storage::<SpeedPenalty>(),
+    );
+
+    effective_speed(
+        &initiative,
+        penalty,
+        ecs.read_storage::<StatusEffects>().get(entity),
+    )
+}
+
+fn equipment_penalty(
+    entity: Entity,
+    equipped: &ReadStorage<Equipped>,
+    penalties: &ReadStorage<SpeedPenalty>,
+) -> i32 {
+    (penalties, equipped)
+        .join()
+        .filter(|(_, equipped_by)| equipped_by.owner == entity)
+        .map(|(penalty, _)| penalty.penalty)
+        .sum()
+}
+
+fn effective_speed(initiative: &Initiative, penalty: i32, statuses: Option<&StatusEffects>) -> i32 {
+    let mut speed = initiative.speed - penalty;
+
+    if let Some(statuses) = statuses {
+        if statuses.has(StatusKind::Haste) {
+            speed *= 2;
+        }
+        if statuses.has(StatusKind::Slow) {
+            speed /= 2;
+        }
+    }
+
+    i32::max(speed, MINIMUM_SPEED)
+}
+
+/// Hands out energy, and ticks the status effects with the clock, until the player or some
+/// monsters on the level can act. Returns true when the player can, otherwise the monsters that
+/// can are marked with `MyTurn` and pay for their action up front. The player goes first when
+/// both can.
+pub fn schedule(ecs: &mut World) -> bool {
+    loop {
+        let player_entity = *ecs.fetch::<Entity>();
+
+        // A player without initiative would wait forever
+        match ecs.read_storage::<Initiative>().get(player_entity) {
+            Some(initiative) if initiative.energy < 0 => {}
+            _ => return true,
+        }
+
+        if mark_ready_monsters(ecs) {
+            return false;
+        }
+
+        let mut tick = InitiativeSystem {};
+        tick.run_now(ecs);
+
+        let mut statuses = StatusEffectSystem {};
+        statuses.run_now(ecs);
+    }
+}
+
+fn mark_ready_monsters(ecs: &mut World) -> bool {
+    let entities = ecs.entities();
+    let monsters = ecs.read_storage::<Monster>();
+    let positions = ecs.read_storage::<Position>();
+    let mut initiatives = ecs.write_storage::<Initiative>();
+    let mut my_turn = ecs.write_storage::<MyTurn>();
+    let mut any_ready = false;
+
+    for (entity, _monster, _position, initiative) in
+        (&entities, &monsters, &positions, &mut initiatives).join()
+    {
+        if initiative.energy >= 0 {
+            initiative.energy -= ACTION_COST;
+            any_ready = true;
+
+            my_turn
+                .insert(entity, MyTurn {})
+                .expect("Unable to insert turn");
+        }
+    }
+
+    any_ready
+}
+
+/// One tick of the clock: everything on the level gains its speed in energy
+pub struct InitiativeSystem {}
+
+impl<'a> System<'a> for InitiativeSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, Initiative>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, SpeedPenalty>,
+        ReadStorage<'a, StatusEffects>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, positions, mut initiatives, equipped, penalties, statuses) = data;
+
+        for (entity, _position, initiative) in (&entities, &positions, &mut initiatives).join() {
+            let penalty = equipment_penalty(entity, &equipped, &penalties);
+
+            initiative.energy += effective_speed(initiative, penalty, statuses.get(entity));
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use specs::prelude::*;
 
+use crate::attributes;
 use crate::components::*;
 use crate::game_log::GameLog;
 use crate::map::Map;
 use crate::particle_system::ParticleBuilder;
+use crate::status_effect_system;
 use crate::RunState;
 
 pub struct ItemCollectionSystem {}
@@ -131,7 +133,7 @@ impl<'a> System<'a> for ItemUseSystem {
         WriteStorage<'a, SufferDamage>,
         ReadStorage<'a, Consumable>,
         ReadStorage<'a, AreaOfEffect>,
-        WriteStorage<'a, Confusion>,
+        ReadStorage<'a, InflictsStatus>,
         ReadStorage<'a, Equippable>,
         WriteStorage<'a, Equipped>,
         WriteStorage<'a, InBackpack>,
@@ -141,6 +143,10 @@ impl<'a> System<'a> for ItemUseSystem {
         WriteStorage<'a, HungerClock>,
         ReadStorage<'a, MagicMapper>,
         WriteExpect<'a, RunState>,
+        WriteStorage<'a, StatusEffects>,
+        WriteStorage<'a, Provoked>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, Skills>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -157,7 +163,7 @@ impl<'a> System<'a> for ItemUseSystem {
             mut suffer_damage,
             consumables,
             aoe,
-            mut confused,
+            inflicts_status,
             equippable,
             mut equipped,
             mut backpack,
@@ -167,10 +173,15 @@ impl<'a> System<'a> for ItemUseSystem {
             mut hunger_clocks,
             magic_mapper,
             mut run_state,
+            mut statuses,
+            mut provoked,
+            attributes,
+            mut skills,
         ) = data;
 
         for (entity, use_item) in (&entities, &wants_use_item).join() {
             let mut used_item = true;
+            let mut cast_spell = false;
 
             // Targeting
             let mut targets = Vec::<Entity>::new();
@@ -325,8 +336,20 @@ impl<'a> System<'a> for ItemUseSystem {
             if let Some(damage) = item_damages {
                 used_item = false;
 
+                let amount = damage.damage
+                    + attributes::skill_level(skills.get(entity), SkillKind::Magic)
+                    + attributes
+                        .get(entity)
+                        .map_or(0, |caster| attributes::bonus(caster.intelligence));
+
                 for mob in targets.iter() {
-                    SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage);
+                    SufferDamage::new_damage(&mut suffer_damage, *mob, amount, damage.damage_type);
+
+                    if *mob != entity {
+                        provoked
+                            .insert(*mob, Provoked { by: entity })
+                            .expect("Unable to insert provocation");
+                    }
 
                     if entity == *player_entity {
                         let mob_name = names.get(*mob).unwrap();
@@ -334,7 +357,7 @@ impl<'a> System<'a> for ItemUseSystem {
 
                         game_log.entries.push(format!(
                             "You use {} on {}, inflicting {} hp.",
-                            item_name.name, mob_name.name, damage.damage
+                            item_name.name, mob_name.name, amount
                         ));
                     }
 
@@ -350,47 +373,67 @@ impl<'a> System<'a> for ItemUseSystem {
                     }
 
                     used_item = true;
+                    cast_spell = true;
                 }
             }
 
-            // Can it pass along confusion? Note the use of scopes to escape from the borrow checker!
-            let mut add_confusion = Vec::new();
-            {
-                let causes_confusion = confused.get(use_item.item);
+            if let Some(inflicts) = inflicts_status.get(use_item.item) {
+                used_item = false;
 
-                if let Some(confusion) = causes_confusion {
-                    used_item = false;
+                let item_name = &names.get(use_item.item).unwrap().name;
 
-                    for mob in targets.iter() {
-                        add_confusion.push((*mob, confusion.turns));
-
-                        if entity == *player_entity {
-                            let mob_name = names.get(*mob).unwrap();
-                            let item_name = names.get(use_item.item).unwrap();
+                // Items on the target tile are in `targets` too
+                for target in targets.iter().filter(|t| combat_stats.contains(**t)) {
+                    for effect in inflicts.effects.iter() {
+                        status_effect_system::apply_status(&mut statuses, *target, *effect);
 
+                        if *target == *player_entity {
+                            game_log.entries.push(format!(
+                                "You use the {}, and {}.",
+                                item_name,
+                                onset(effect.kind)
+                            ));
+                        } else if entity == *player_entity {
                             game_log.entries.push(format!(
-                                "You use {} on {}, confusing them.",
-                                item_name.name, mob_name.name
+                                "You use {} on {}, {} them.",
+                                item_name,
+                                names.get(*target).unwrap().name,
+                                inflicting(effect.kind)
                             ));
                         }
+                    }
 
-                        if let Some(Position { x, y }) = positions.get(*mob) {
-                            particle_builder.request(
-                                *x,
-                                *y,
-                                rltk::RGB::named(rltk::MAGENTA),
-                                rltk::RGB::named(rltk::BLACK),
-                                rltk::to_cp437('?'),
-                                200.0,
-                            );
-                        }
+                    if *target != entity && inflicts.effects.iter().any(|e| is_harmful(e.kind)) {
+                        provoked
+                            .insert(*target, Provoked { by: entity })
+                            .expect("Unable to insert provocation");
                     }
+
+                    if let Some(Position { x, y }) = positions.get(*target) {
+                        particle_builder.request(
+                            *x,
+                            *y,
+                            rltk::RGB::named(rltk::MAGENTA),
+                            rltk::RGB::named(rltk::BLACK),
+                            rltk::to_cp437('?'),
+                            200.0,
+                        );
+                    }
+
+                    used_item = true;
+                    cast_spell = true;
                 }
             }
-            for mob in add_confusion.iter() {
-                confused
-                    .insert(mob.0, Confusion { turns: mob.1 })
-                    .expect("Unable to insert status");
+
+            // Only what is aimed takes magic, drinking a potion is no skill
+            if cast_spell && use_item.target.is_some() {
+                attributes::practice(
+                    &mut skills,
+                    entity,
+                    SkillKind::Magic,
+                    *player_entity,
+                    &mut game_log,
+                );
             }
 
             if used_item {
@@ -405,3 +448,30 @@ impl<'a> System<'a> for ItemUseSystem {
         wants_use_item.clear();
     }
 }
+
+/// What the player notices when an effect takes hold of them
+fn onset(kind: StatusKind) -> &'static str {
+    match kind {
+        StatusKind::Confusion => "your head starts to spin",
+        StatusKind::Poison => "you feel sick",
+        StatusKind::Regeneration => "your wounds begin to close",
+        StatusKind::Haste => "everything around you slows down",
+        StatusKind::Slow => "everything around you speeds up",
+        StatusKind::Paralysis => "you cannot move a muscle",
+    }
+}
+
+fn inflicting(kind: StatusKind) -> &'static str {
+    match kind {
+        StatusKind::Confusion => "confusing",
+        StatusKind::Poison => "poisoning",
+        StatusKind::Regeneration => "mending",
+        StatusKind::Haste => "hastening",
+        StatusKind::Slow => "slowing",
+        StatusKind::Paralysis => "paralysing",
+    }
+}
+
+fn is_harmful(kind: StatusKind) -> bool {
+    !matches!(kind, StatusKind::Regeneration | StatusKind::Haste)
+}
@@ -0,0 +1,977 @@
+pub mod attributes;
+mod camera;
+pub mod component_registry;
+pub mod components;
+pub mod damage_system;
+pub mod dungeon;
+pub mod experience;
+pub mod flow_map;
+pub mod game_log;
+pub mod gui;
+pub mod headless;
+pub mod hunger_system;
+pub mod initiative_system;
+pub mod inventory_system;
+pub mod map;
+pub mod map_builders;
+pub mod map_indexing_system;
+pub mod mapgen;
+pub mod melee_combat_system;
+mod menu;
+pub mod monster_ai_system;
+mod particle_system;
+pub mod player;
+pub mod random_table;
+pub mod raws;
+pub mod rect;
+pub mod render_order;
+pub mod replay;
+mod rex_assets;
+pub mod rng;
+pub mod save_slots;
+pub mod save_storage;
+pub mod saveload_system;
+pub mod savetool;
+pub mod spawner;
+pub mod status_effect_system;
+pub mod tile_encoding;
+pub mod trigger_system;
+pub mod visibility_system;
+
+use rltk::{GameState, Point, Rltk, VirtualKeyCode};
+use specs::prelude::*;
+use specs::saveload::SimpleMarkerAllocator;
+
+use crate::components::*;
+use crate::damage_system::DamageSystem;
+use crate::dungeon::MasterDungeonMap;
+use crate::game_log::GameLog;
+use crate::inventory_system::{
+    ItemCollectionSystem, ItemDropSystem, ItemRemoveSystem, ItemUseSystem,
+};
+use crate::map::*;
+use crate::map_indexing_system::MapIndexingSystem;
+use crate::melee_combat_system::MeleeCombatSystem;
+use crate::monster_ai_system::MonsterAI;
+use crate::player::*;
+use crate::replay::Recorder;
+use crate::rng::{AiRng, CombatRng, GameSeed, PerceptionRng, StatusRng};
+use crate::save_slots::{AutosaveSettings, SaveSlot, SaveSlots, SlotId};
+use crate::saveload_system::SaveEncoding;
+use crate::visibility_system::*;
+
+const SHOW_MAPGEN_VISUALIZER: bool = false;
+const SHOW_MAP_AFTER_GENERATION: bool = false;
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum RunState {
+    AwaitingInput,
+    PreRun,
+    PlayerTurn,
+    MonsterTurn,
+    ShowInventory,
+    ShowDropItem,
+    ShowTargeting {
+        range: i32,
+        item: Entity,
+    },
+    MainMenu {
+        menu_selection: gui::MainMenuSelection,
+    },
+    SaveGame,
+    SaveSlots {
+        selection: usize,
+        confirm_delete: bool,
+        encoding: SaveEncoding,
+    },
+    LoadSlots {
+        selection: usize,
+        confirm_delete: bool,
+    },
+    NextLevel,
+    PreviousLevel,
+    ShowRemoveItem,
+    ShowCharacter,
+    GameOver,
+    MagicMapReveal {
+        row: i32,
+    },
+    MapGeneration,
+    EnterSeed {
+        seed: u64,
+    },
+}
+
+/// How many turns the player has taken in this game
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct TurnCount(pub u64);
+
+pub struct State {
+    pub ecs: World,
+
+    mapgen_next_state: Option<RunState>,
+    mapgen_history: Vec<Map>,
+    mapgen_index: usize,
+    mapgen_timer: f32,
+
+    recorder: Option<Recorder>,
+    /// Shown under the main menu, e.g. why the save could not be loaded
+    main_menu_message: Option<String>,
+
+    save_slots: SaveSlots,
+    autosave: AutosaveSettings,
+    /// Set when a turn is due an autosave, which waits until the dead are gone
+    autosave_due: bool,
+    /// The slots shown by the slot pickers, read when one opens
+    slot_list: Vec<SaveSlot>,
+    /// Shown under the slot pickers, e.g. why a slot could not be deleted
+    slot_message: Option<String>,
+    /// Toggled with the backtick key, see `gui::draw_ai_overlay`
+    show_ai_overlay: bool,
+}
+
+impl State {
+    pub fn run_systems(&mut self) {
+        let mut vis = VisibilitySystem {};
+        vis.run_now(&self.ecs);
+
+        let mut mob = MonsterAI {};
+        mob.run_now(&self.ecs);
+
+        let mut triggers = trigger_system::TriggerSystem {};
+        triggers.run_now(&self.ecs);
+
+        let mut map_index = MapIndexingSystem {};
+        map_index.run_now(&self.ecs);
+
+        let mut melee_combat_system = MeleeCombatSystem {};
+        melee_combat_system.run_now(&self.ecs);
+
+        let mut damage_system = DamageSystem {};
+        damage_system.run_now(&self.ecs);
+
+        let mut pickup = ItemCollectionSystem {};
+        pickup.run_now(&self.ecs);
+
+        let mut use_items = ItemUseSystem {};
+        use_items.run_now(&self.ecs);
+
+        let mut drop_items = ItemDropSystem {};
+        drop_items.run_now(&self.ecs);
+
+        let mut item_remove = ItemRemoveSystem {};
+        item_remove.run_now(&self.ecs);
+
+        let mut hunger = hunger_system::HungerSystem {};
+        hunger.run_now(&self.ecs);
+
+        let mut particles = particle_system::ParticleSpawnSystem {};
+        particles.run_now(&self.ecs);
+
+        self.ecs.maintain();
+    }
+
+    /// Takes the player `offset` levels down (or up when negative), leaving the current level
+    /// in the master dungeon
+    fn goto_level(&mut self, offset: i32) {
+        let current_depth = self.ecs.fetch::<Map>().depth;
+
+        self.travel_to_depth(current_depth + offset);
+
+        let mut game_log = self.ecs.fetch_mut::<GameLog>();
+
+        if offset < 0 {
+            game_log
+                .entries
+                .push("You ascend to the previous level.".to_string());
+            return;
+        }
+
+        // Notify the player and give them some health
+        game_log
+            .entries
+            .push("You descend to the next level, and take a moment to heal.".to_string());
+
+        let mut player_health_store = self.ecs.write_storage::<CombatStats>();
+        let player_entity = *self.ecs.fetch::<Entity>();
+
+        if let Some(player_health) = player_health_store.get_mut(player_entity) {
+            player_health.hp = i32::max(player_health.hp, player_health.max_hp / 2);
+        }
+    }
+
+    /// Leaves the current level in the master dungeon and puts the player on the level at
+    /// `depth`, without any of the messages or healing of the stairs
+    pub fn travel_to_depth(&mut self, depth: i32) {
+        dungeon::freeze_level_entities(&mut self.ecs);
+
+        {
+            let map = self.ecs.fetch::<Map>();
+            let player_pos = *self.ecs.fetch::<Point>();
+
+            self.ecs
+                .fetch_mut::<MasterDungeonMap>()
+                .store_level(&map, player_pos);
+        }
+
+        self.change_level(depth);
+    }
+
+    /// Puts the player on a level reached through the stairs: back on the stairs they left it
+    /// through when it was visited before, or at the start of a newly generated level
+    fn change_level(&mut self, new_depth: i32) {
+        let visited_level = self.ecs.fetch::<MasterDungeonMap>().get_level(new_depth);
+
+        let Some((map, left_from)) = visited_level else {
+            self.generate_world_map(new_depth);
+            return;
+        };
+
+        self.mapgen_history = Vec::new();
+        *self.ecs.write_resource::<Map>() = map;
+
+        dungeon::thaw_level_entities(&mut self.ecs);
+        self.place_player(left_from.x, left_from.y);
+    }
+
+    fn game_over_cleanup(&mut self) {
+        self.start_new_game(rng::random_seed());
+    }
+
+    /// Throws the current game away and starts over on the first level of the `seed` dungeon
+    pub fn start_new_game(&mut self, seed: u64) {
+        // Delete everything
+        let mut to_delete = Vec::new();
+        for e in self.ecs.entities().join() {
+            to_delete.push(e);
+        }
+        for del in to_delete.iter() {
+            self.ecs.delete_entity(*del).expect("Deletion failed");
+        }
+
+        // Spawn a new player
+        {
+            let player_entity = spawner::player(&mut self.ecs, 0, 0);
+            let mut player_entity_writer = self.ecs.write_resource::<Entity>();
+            *player_entity_writer = player_entity;
+        }
+
+        self.ecs.insert(GameSeed(seed));
+        self.ecs.insert(TurnCount(0));
+        self.ecs.insert(MasterDungeonMap::new());
+        self.ecs.insert(CombatRng::new(seed));
+        self.ecs.insert(PerceptionRng::new(seed));
+        self.ecs.insert(AiRng::new(seed));
+        self.ecs.insert(StatusRng::new(seed));
+
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.new_game(seed);
+        }
+
+        // Build a new map and place the player
+        self.generate_world_map(1);
+    }
+
+    fn generate_world_map(&mut self, new_depth: i32) {
+        self.mapgen_index = 0;
+        self.mapgen_timer = 0.0;
+        // NOTE(DP): we do not need clear() since there is reassignment later
+        // self.mapgen_history.clear();
+
+        // The map gets its own generators, so a level only depends on the seed and depth
+        let seed = self.ecs.fetch::<GameSeed>().0;
+        let mut rng = rng::map_rng(seed, new_depth);
+        let spawn_rng = rng::spawn_rng(seed, new_depth);
+        let mut builder = map_builders::random_builder(new_depth, &mut rng, spawn_rng, 80, 50);
+        builder.build_map(&mut rng);
+
+        self.mapgen_history = builder.build_data.history.clone();
+
+        let player_start = {
+            let mut map_resource = self.ecs.write_resource::<Map>();
+
+            *map_resource = builder.build_data.map.clone();
+
+            builder
+                .build_data
+                .starting_position
+                .as_mut()
+                .unwrap()
+                .clone()
+        };
+
+        // Spawn bad guys
+        builder.spawn_entities(&mut self.ecs);
+
+        self.place_player(player_start.x, player_start.y);
+    }
+
+    fn place_player(&mut self, player_x: i32, player_y: i32) {
+        {
+            let mut player_position = self.ecs.write_resource::<Point>();
+
+            *player_position = Point::new(player_x, player_y);
+        }
+
+        let player_entity = *self.ecs.fetch::<Entity>();
+        let mut position_components = self.ecs.write_storage::<Position>();
+
+        if let Some(player_entity_position) = position_components.get_mut(player_entity) {
+            player_entity_position.x = player_x;
+            player_entity_position.y = player_y;
+        }
+
+        // Mark the player's visibility as dirty
+        let mut viewshed_components = self.ecs.write_storage::<Viewshed>();
+
+        if let Some(vs) = viewshed_components.get_mut(player_entity) {
+            vs.dirty = true;
+        }
+    }
+}
+
+impl State {
+    /// Creates the game world with every component registered and the first level generated
+    pub fn new() -> State {
+        State::with_seed(rng::random_seed())
+    }
+
+    /// Same as `new`, but the dungeon and everything random in it come from `seed`
+    pub fn with_seed(seed: u64) -> State {
+        // NOTE(DP): added to see generated map
+        let mapgen_next_state = Some(if SHOW_MAP_AFTER_GENERATION {
+            RunState::MapGeneration
+        } else {
+            RunState::MainMenu {
+                menu_selection: gui::MainMenuSelection::NewGame,
+            }
+        });
+
+        let mut gs = State {
+            ecs: World::new(),
+            mapgen_next_state,
+            mapgen_index: 0,
+            mapgen_history: Vec::new(),
+            mapgen_timer: 0.0,
+            recorder: None,
+            main_menu_message: None,
+            save_slots: SaveSlots::for_platform(),
+            autosave: AutosaveSettings::default(),
+            autosave_due: false,
+            slot_list: Vec::new(),
+            slot_message: None,
+            show_ai_overlay: false,
+        };
+
+        component_registry::register_components(&mut gs.ecs);
+
+        // Placeholders for Map and player position
+        gs.ecs.insert(Map::new(1, 64, 64));
+        gs.ecs.insert(Point::new(0, 0));
+
+        gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+        gs.ecs.insert(GameSeed(seed));
+        gs.ecs.insert(TurnCount(0));
+        gs.ecs.insert(MasterDungeonMap::new());
+        gs.ecs.insert(CombatRng::new(seed));
+        gs.ecs.insert(PerceptionRng::new(seed));
+        gs.ecs.insert(AiRng::new(seed));
+        gs.ecs.insert(StatusRng::new(seed));
+
+        let player_entity = spawner::player(&mut gs.ecs, 0, 0);
+
+        gs.ecs.insert(player_entity);
+        gs.ecs.insert(particle_system::ParticleBuilder::new());
+        gs.ecs.insert(rex_assets::RexAssets::new());
+        gs.ecs.insert(RunState::MapGeneration);
+
+        gs.generate_world_map(1);
+
+        gs.ecs.insert(GameLog {
+            entries: vec!["Welcome to Rusty Roguelike".to_string()],
+        });
+
+        gs
+    }
+
+    /// Runs one step of the states that need no input from the player
+    fn advance(&mut self, run_state: RunState) -> RunState {
+        match run_state {
+            RunState::PreRun => {
+                self.run_systems();
+                RunState::AwaitingInput
+            }
+            RunState::PlayerTurn => {
+                self.run_systems();
+                self.ecs.fetch_mut::<TurnCount>().0 += 1;
+
+                match *self.ecs.fetch::<RunState>() {
+                    RunState::MagicMapReveal { .. } => RunState::MagicMapReveal { row: 0 },
+                    _ => RunState::MonsterTurn,
+                }
+            }
+            // The first monster turn after the player's only settles what they did,
+            // the scheduler picks who acts next at the end of every monster turn
+            RunState::MonsterTurn => {
+                self.run_systems();
+                self.next_turn()
+            }
+            RunState::NextLevel => {
+                self.goto_level(1);
+
+                if self.autosave.on_next_level {
+                    self.autosave();
+                }
+
+                RunState::PreRun
+            }
+            RunState::PreviousLevel => {
+                self.goto_level(-1);
+
+                RunState::PreRun
+            }
+            RunState::MagicMapReveal { row } => {
+                let mut map = self.ecs.fetch_mut::<Map>();
+
+                for x in 0..map.width {
+                    let idx = map.xy_idx(x, row);
+
+                    map.revealed_tiles[idx] = true;
+                }
+
+                if row == map.height - 1 {
+                    RunState::MonsterTurn
+                } else {
+                    RunState::MagicMapReveal { row: row + 1 }
+                }
+            }
+            _ => run_state,
+        }
+    }
+
+    /// Lets the monsters act until the player's initiative comes up again
+    fn next_turn(&mut self) -> RunState {
+        if !initiative_system::schedule(&mut self.ecs) {
+            return RunState::MonsterTurn;
+        }
+
+        let turns = self.ecs.fetch::<TurnCount>().0;
+
+        if let Some(every_turns) = self.autosave.every_turns {
+            if turns.is_multiple_of(every_turns) {
+                self.autosave_due = true;
+            }
+        }
+
+        // A paralysed player's turn passes without asking them
+        let player_entity = *self.ecs.fetch::<Entity>();
+
+        if status_effect_system::has_status(&self.ecs, player_entity, StatusKind::Paralysis) {
+            initiative_system::spend_energy(
+                &self.ecs,
+                player_entity,
+                initiative_system::ACTION_COST,
+            );
+
+            return RunState::PlayerTurn;
+        }
+
+        RunState::AwaitingInput
+    }
+
+    pub fn save_slots(&self) -> &SaveSlots {
+        &self.save_slots
+    }
+
+    /// Saves go to `save_slots` from now on instead of the user's data directory
+    pub fn use_save_slots(&mut self, save_slots: SaveSlots) {
+        self.save_slots = save_slots;
+    }
+
+    /// The game autosaves as `settings` say from now on, it never does by default
+    pub fn set_autosave(&mut self, settings: AutosaveSettings) {
+        self.autosave = settings;
+    }
+
+    fn autosave(&mut self) {
+        if let Err(error) = self.save_slots.autosave(&mut self.ecs) {
+            self.ecs
+                .fetch_mut::<GameLog>()
+                .entries
+                .push(format!("Unable to autosave. {}", error));
+        }
+    }
+
+    /// Records every command the game accepts from now on, see `replay`
+    pub fn record_to(&mut self, recorder: Recorder) {
+        self.recorder = Some(recorder);
+    }
+
+    /// Applies a player command to the state that is waiting for it. Only commands that state
+    /// takes are recorded, the rest change nothing.
+    fn handle_command(&mut self, run_state: RunState, command: PlayerCommand) -> RunState {
+        let new_run_state = match (run_state, command) {
+            (
+                RunState::AwaitingInput,
+                PlayerCommand::SelectItem { .. }
+                | PlayerCommand::Target { .. }
+                | PlayerCommand::Cancel,
+            ) => None,
+            (RunState::AwaitingInput, _) => Some(perform_command(self, command)),
+            (
+                RunState::ShowInventory
+                | RunState::ShowDropItem
+                | RunState::ShowRemoveItem
+                | RunState::ShowCharacter
+                | RunState::ShowTargeting { .. },
+                PlayerCommand::Cancel,
+            ) => Some(RunState::AwaitingInput),
+            (RunState::ShowInventory, PlayerCommand::SelectItem { index }) => {
+                let item = gui::backpack_items(&self.ecs).get(index).copied();
+
+                item.map(|item| self.use_item(item))
+            }
+            (RunState::ShowDropItem, PlayerCommand::SelectItem { index }) => {
+                let item = gui::backpack_items(&self.ecs).get(index).copied();
+
+                item.map(|item| self.drop_item(item))
+            }
+            (RunState::ShowRemoveItem, PlayerCommand::SelectItem { index }) => {
+                let item = gui::equipped_items(&self.ecs).get(index).copied();
+
+                item.map(|item| self.remove_item(item))
+            }
+            (RunState::ShowTargeting { range, item }, PlayerCommand::Target { x, y }) => {
+                let target = Point::new(x, y);
+                let is_valid_target = gui::targetable_tiles(&self.ecs, range)
+                    .map(|tiles| tiles.contains(&target))
+                    .unwrap_or(false);
+
+                if is_valid_target {
+                    Some(self.use_item_on(item, Some(target)))
+                } else {
+                    Some(RunState::AwaitingInput)
+                }
+            }
+            _ => None,
+        };
+
+        let Some(new_run_state) = new_run_state else {
+            return run_state;
+        };
+
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(command);
+        }
+
+        if new_run_state == RunState::PlayerTurn {
+            let player_entity = *self.ecs.fetch::<Entity>();
+
+            initiative_system::spend_energy(
+                &self.ecs,
+                player_entity,
+                initiative_system::action_cost(run_state, command),
+            );
+        }
+
+        new_run_state
+    }
+
+    fn use_item(&mut self, item_entity: Entity) -> RunState {
+        let ranged = self.ecs.read_storage::<Ranged>().get(item_entity).cloned();
+
+        if let Some(ranged) = ranged {
+            RunState::ShowTargeting {
+                range: ranged.range,
+                item: item_entity,
+            }
+        } else {
+            self.use_item_on(item_entity, None)
+        }
+    }
+
+    fn use_item_on(&mut self, item: Entity, target: Option<Point>) -> RunState {
+        let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+
+        intent
+            .insert(*self.ecs.fetch::<Entity>(), WantsToUseItem { item, target })
+            .expect("Unable to insert intent");
+
+        RunState::PlayerTurn
+    }
+
+    fn drop_item(&mut self, item: Entity) -> RunState {
+        let mut intent = self.ecs.write_storage::<WantsToDropItem>();
+
+        intent
+            .insert(*self.ecs.fetch::<Entity>(), WantsToDropItem { item })
+            .expect("Unable to insert intent");
+
+        RunState::PlayerTurn
+    }
+
+    fn remove_item(&mut self, item: Entity) -> RunState {
+        let mut intent = self.ecs.write_storage::<WantsToRemoveItem>();
+
+        intent
+            .insert(*self.ecs.fetch::<Entity>(), WantsToRemoveItem { item })
+            .expect("Unable to insert intent");
+
+        RunState::PlayerTurn
+    }
+
+    /// Loads a save slot, a save that cannot be loaded is reported on the main menu
+    fn load_saved_game(&mut self, slot: SlotId) -> RunState {
+        match self.save_slots.load(&mut self.ecs, slot) {
+            Ok(()) => {
+                // A replay can only follow games it has seen from the start
+                if let Some(recorder) = self.recorder.as_mut() {
+                    recorder
+                        .pause("A saved game was loaded, the recording goes on with a new game");
+                }
+
+                RunState::AwaitingInput
+            }
+            Err(error) => {
+                // The world may be half loaded, a fresh game keeps "Begin New Game" working
+                let seed = self.ecs.fetch::<GameSeed>().0;
+
+                self.start_new_game(seed);
+                self.main_menu_message = Some(format!("Unable to load {}. {}", slot, error));
+
+                RunState::MainMenu {
+                    menu_selection: gui::MainMenuSelection::LoadGame,
+                }
+            }
+        }
+    }
+
+    /// Saves to the slot, or to a new one, and leaves to the main menu
+    fn save_to_slot(
+        &mut self,
+        slot: Option<SlotId>,
+        selection: usize,
+        encoding: SaveEncoding,
+    ) -> RunState {
+        let saved = match slot {
+            Some(slot) => self.save_slots.save_as(&mut self.ecs, slot, encoding),
+            None => self
+                .save_slots
+                .save_new_as(&mut self.ecs, encoding)
+                .map(|_| ()),
+        };
+
+        match saved {
+            Ok(()) => RunState::MainMenu {
+                menu_selection: gui::MainMenuSelection::LoadGame,
+            },
+            Err(error) => {
+                self.slot_message = Some(format!("Unable to save the game. {}", error));
+
+                RunState::SaveSlots {
+                    selection,
+                    confirm_delete: false,
+                    encoding,
+                }
+            }
+        }
+    }
+
+    /// Reads the slots again for a slot picker that opens
+    fn refresh_slot_list(&mut self) {
+        self.slot_list = self.save_slots.list();
+        self.slot_message = None;
+    }
+
+    fn delete_slot(&mut self, slot: SlotId) {
+        let deleted = self.save_slots.delete(slot);
+
+        self.refresh_slot_list();
+        self.slot_message = deleted.err();
+    }
+
+    fn save_slot_menu(
+        &mut self,
+        ctx: &mut Rltk,
+        selection: usize,
+        confirm_delete: bool,
+        encoding: SaveEncoding,
+    ) -> RunState {
+        // The autosaves can be loaded but not saved over
+        let slots: Vec<SaveSlot> = self
+            .slot_list
+            .iter()
+            .filter(|slot| matches!(slot.id, SlotId::Numbered(_)))
+            .cloned()
+            .collect();
+        let result = gui::slot_picker(
+            ctx,
+            "Save Game",
+            &slots,
+            Some(encoding),
+            selection,
+            confirm_delete,
+            self.slot_message.as_deref(),
+        );
+
+        match result {
+            gui::SlotPickerResult::NoSelection {
+                selection,
+                confirm_delete,
+            } => RunState::SaveSlots {
+                selection,
+                confirm_delete,
+                encoding,
+            },
+            gui::SlotPickerResult::SwitchEncoding { selection } => RunState::SaveSlots {
+                selection,
+                confirm_delete: false,
+                encoding: encoding.other(),
+            },
+            gui::SlotPickerResult::Cancel => RunState::AwaitingInput,
+            gui::SlotPickerResult::Selected { selection: 0 } => {
+                self.save_to_slot(None, 0, encoding)
+            }
+            gui::SlotPickerResult::Selected { selection } => {
+                self.save_to_slot(Some(slots[selection - 1].id), selection, encoding)
+            }
+            gui::SlotPickerResult::Delete { selection } => {
+                self.delete_slot(slots[selection - 1].id);
+
+                RunState::SaveSlots {
+                    selection: usize::min(selection, slots.len() - 1),
+                    confirm_delete: false,
+                    encoding,
+                }
+            }
+        }
+    }
+
+    fn load_slot_menu(
+        &mut self,
+        ctx: &mut Rltk,
+        selection: usize,
+        confirm_delete: bool,
+    ) -> RunState {
+        let result = gui::slot_picker(
+            ctx,
+            "Load Game",
+            &self.slot_list,
+            None,
+            selection,
+            confirm_delete,
+            self.slot_message.as_deref(),
+        );
+
+        match result {
+            gui::SlotPickerResult::NoSelection {
+                selection,
+                confirm_delete,
+            } => RunState::LoadSlots {
+                selection,
+                confirm_delete,
+            },
+            gui::SlotPickerResult::Cancel => RunState::MainMenu {
+                menu_selection: gui::MainMenuSelection::LoadGame,
+            },
+            gui::SlotPickerResult::SwitchEncoding { selection } => RunState::LoadSlots {
+                selection,
+                confirm_delete: false,
+            },
+            gui::SlotPickerResult::Selected { selection } => {
+                self.load_saved_game(self.slot_list[selection].id)
+            }
+            gui::SlotPickerResult::Delete { selection } => {
+                self.delete_slot(self.slot_list[selection].id);
+
+                if self.slot_list.is_empty() {
+                    return RunState::MainMenu {
+                        menu_selection: gui::MainMenuSelection::NewGame,
+                    };
+                }
+
+                RunState::LoadSlots {
+                    selection: usize::min(selection, self.slot_list.len() - 1),
+                    confirm_delete: false,
+                }
+            }
+        }
+    }
+
+    fn commit_run_state(&mut self, new_run_state: RunState) {
+        {
+            let mut run_writer = self.ecs.write_resource::<RunState>();
+
+            *run_writer = new_run_state;
+        }
+
+        damage_system::delete_the_dead(&mut self.ecs);
+
+        // The dead are only gone now, and a game that is over is not worth saving
+        if std::mem::take(&mut self.autosave_due)
+            && *self.ecs.fetch::<RunState>() != RunState::GameOver
+        {
+            self.autosave();
+        }
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameState for State {
+    fn tick(&mut self, ctx: &mut Rltk) {
+        ctx.cls();
+        particle_system::cull_dead_particles(&mut self.ecs, ctx.frame_time_ms);
+
+        let run_state = *self.ecs.fetch::<RunState>();
+
+        match run_state {
+            RunState::MainMenu { .. }
+            | RunState::LoadSlots { .. }
+            | RunState::GameOver
+            | RunState::EnterSeed { .. } => {}
+            _ => {
+                camera::render_camera(&self.ecs, ctx);
+
+                if self.show_ai_overlay {
+                    gui::draw_ai_overlay(&self.ecs, ctx);
+                }
+
+                gui::draw_ui(&self.ecs, ctx)
+            }
+        }
+
+        if run_state == RunState::AwaitingInput && ctx.key == Some(VirtualKeyCode::Grave) {
+            self.show_ai_overlay = !self.show_ai_overlay;
+        }
+
+        let command = match run_state {
+            RunState::AwaitingInput => player_input(ctx),
+            RunState::ShowInventory => gui::show_inventory(self, ctx),
+            RunState::ShowDropItem => gui::drop_item_menu(self, ctx),
+            RunState::ShowRemoveItem => gui::remove_item_menu(self, ctx),
+            RunState::ShowCharacter => gui::show_character(self, ctx),
+            RunState::ShowTargeting { range, .. } => gui::ranged_target(self, ctx, range),
+            _ => None,
+        };
+
+        let new_run_state = match run_state {
+            RunState::AwaitingInput
+            | RunState::ShowInventory
+            | RunState::ShowDropItem
+            | RunState::ShowRemoveItem
+            | RunState::ShowCharacter
+            | RunState::ShowTargeting { .. } => match command {
+                Some(command) => self.handle_command(run_state, command),
+                None => run_state,
+            },
+            RunState::MainMenu { .. } => {
+                let result = menu::main_menu(self, ctx);
+
+                match result {
+                    gui::MainMenuResult::NoSelection { selected } => RunState::MainMenu {
+                        menu_selection: selected,
+                    },
+                    gui::MainMenuResult::Selected { selected } => {
+                        self.main_menu_message = None;
+
+                        match selected {
+                            gui::MainMenuSelection::NewGame => RunState::PreRun,
+                            gui::MainMenuSelection::NewGameWithSeed => {
+                                RunState::EnterSeed { seed: 0 }
+                            }
+                            gui::MainMenuSelection::LoadGame => {
+                                self.refresh_slot_list();
+
+                                RunState::LoadSlots {
+                                    selection: 0,
+                                    confirm_delete: false,
+                                }
+                            }
+                            gui::MainMenuSelection::Quit => std::process::exit(0),
+                        }
+                    }
+                }
+            }
+            RunState::EnterSeed { seed } => match gui::seed_entry(ctx, seed) {
+                gui::SeedEntryResult::NoSelection { seed } => RunState::EnterSeed { seed },
+                gui::SeedEntryResult::Cancel => RunState::MainMenu {
+                    menu_selection: gui::MainMenuSelection::NewGameWithSeed,
+                },
+                gui::SeedEntryResult::Selected { seed } => {
+                    self.start_new_game(seed);
+
+                    RunState::PreRun
+                }
+            },
+            RunState::SaveGame => {
+                self.refresh_slot_list();
+
+                RunState::SaveSlots {
+                    selection: 0,
+                    confirm_delete: false,
+                    encoding: self.save_slots.encoding(),
+                }
+            }
+            RunState::SaveSlots {
+                selection,
+                confirm_delete,
+                encoding,
+            } => self.save_slot_menu(ctx, selection, confirm_delete, encoding),
+            RunState::LoadSlots {
+                selection,
+                confirm_delete,
+            } => self.load_slot_menu(ctx, selection, confirm_delete),
+            RunState::GameOver => {
+                let result = gui::game_over(ctx);
+                match result {
+                    gui::GameOverResult::NoSelection => run_state,
+                    gui::GameOverResult::QuitToMenu => {
+                        self.game_over_cleanup();
+
+                        RunState::MainMenu {
+                            menu_selection: gui::MainMenuSelection::NewGame,
+                        }
+                    }
+                }
+            }
+            RunState::MapGeneration => {
+                let stop_map_generation_visualization =
+                    !SHOW_MAPGEN_VISUALIZER || self.mapgen_index >= self.mapgen_history.len();
+
+                if stop_map_generation_visualization {
+                    if SHOW_MAP_AFTER_GENERATION {
+                        let mut map = self.ecs.fetch_mut::<Map>();
+
+                        map.revealed_tiles.fill(true);
+
+                        camera::render_debug_map(&map, ctx);
+
+                        map.revealed_tiles.fill(false);
+                    }
+
+                    self.mapgen_next_state.unwrap()
+                } else {
+                    ctx.cls();
+                    camera::render_debug_map(&self.mapgen_history[self.mapgen_index], ctx);
+
+                    self.mapgen_timer += ctx.frame_time_ms;
+
+                    if self.mapgen_timer > 300.0 {
+                        self.mapgen_timer = 0.0;
+                        self.mapgen_index += 1;
+                    }
+
+                    run_state
+                }
+            }
+            _ => self.advance(run_state),
+        };
+
+        self.commit_run_state(new_run_state);
+    }
+}
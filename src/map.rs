@@ -1,23 +1,30 @@
-use rltk::{Algorithm2D, BaseMap, Point, Rltk, RGB};
+use rltk::{Algorithm2D, BaseMap, Point};
 use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 use std::collections::HashSet;
 
-#[derive(PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize)]
+use crate::tile_encoding;
+
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum TileType {
     Wall,
     Floor,
     DownStairs,
+    UpStairs,
 }
 
 #[derive(Default, Serialize, Deserialize, Clone)]
 pub struct Map {
+    #[serde(with = "tile_encoding::run_length")]
     pub tiles: Vec<TileType>,
     pub width: i32,
     pub height: i32,
     pub map_length: usize,
+    #[serde(with = "tile_encoding::bit_packed")]
     pub revealed_tiles: Vec<bool>,
+    #[serde(with = "tile_encoding::bit_packed")]
     pub visible_tiles: Vec<bool>,
+    #[serde(with = "tile_encoding::bit_packed")]
     pub blocked: Vec<bool>,
     pub depth: i32,
     pub bloodstains: HashSet<usize>,
@@ -38,6 +45,35 @@ impl Map {
         idx > 0 && idx < (self.map_length - 1)
     }
 
+    /// Checks that every vector of a map read from a save has one entry for each tile of its size
+    pub fn check_size(&self) -> Result<(), String> {
+        if self.width < 1
+            || self.height < 1
+            || (self.width * self.height) as usize != self.map_length
+        {
+            return Err(format!(
+                "A {}x{} map cannot have {} tiles",
+                self.width, self.height, self.map_length
+            ));
+        }
+
+        for (field, len) in [
+            ("tiles", self.tiles.len()),
+            ("revealed_tiles", self.revealed_tiles.len()),
+            ("visible_tiles", self.visible_tiles.len()),
+            ("blocked", self.blocked.len()),
+        ] {
+            if len != self.map_length {
+                return Err(format!(
+                    "The map has {} {} instead of {}",
+                    len, field, self.map_length
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     fn is_exit_valid(&self, x: i32, y: i32) -> bool {
         if x < 1 || x > self.width - 1 || y < 1 || y > self.height - 1 {
             return false;
@@ -0,0 +1,547 @@
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use rltk::RandomNumberGenerator;
+use serde::Deserialize;
+
+use crate::map_builders::area_starting_points::{AreaStartingPosition, XStart, YStart};
+use crate::map_builders::bsp_dungeon::BspDungeonBuilder;
+use crate::map_builders::bsp_interior::BspInteriorBuilder;
+use crate::map_builders::cellular_automata::CellularAutomataBuilder;
+use crate::map_builders::cull_unreachable::CullUnreachable;
+use crate::map_builders::distant_exit::DistantExit;
+use crate::map_builders::dla::DLABuilder;
+use crate::map_builders::door_placement::DoorPlacement;
+use crate::map_builders::drunkard::DrunkardsWalkBuilder;
+use crate::map_builders::maze::MazeBuilder;
+use crate::map_builders::prefab_builder::{prefab_levels, prefab_sections, PrefabBuilder};
+use crate::map_builders::room_based_spawner::RoomBasedSpawner;
+use crate::map_builders::room_based_stairs::RoomBasedStairs;
+use crate::map_builders::room_based_starting_position::RoomBasedStartingPosition;
+use crate::map_builders::room_corner_rounding::RoomCornerRounder;
+use crate::map_builders::room_corridor_spawner::CorridorSpawner;
+use crate::map_builders::room_draw::RoomDrawer;
+use crate::map_builders::room_exploder::RoomExploder;
+use crate::map_builders::room_sorter::{RoomSort, RoomSorter};
+use crate::map_builders::rooms_corridors_bsp::BspCorridors;
+use crate::map_builders::rooms_corridors_dogleg::DoglegCorridors;
+use crate::map_builders::rooms_corridors_lines::StraightLineCorridors;
+use crate::map_builders::rooms_corridors_nearest::NearestCorridors;
+use crate::map_builders::simple_map::SimpleMapBuilder;
+use crate::map_builders::voronoi::VoronoiCellBuilder;
+use crate::map_builders::voronoi_spawning::VoronoiSpawning;
+use crate::map_builders::waveform_collapse::WaveformCollapseBuilder;
+use crate::map_builders::BuilderChain;
+
+/// Read at startup when it exists, so the chains can be tuned without rebuilding the game
+pub const BUILDER_CHAINS_FILE: &str = "./raws/builder_chains.json";
+
+/// Used when there is no `BUILDER_CHAINS_FILE` to read, e.g. on the web
+const EMBEDDED_BUILDER_CHAINS: &str = include_str!("../../raws/builder_chains.json");
+
+static BUILDER_CHAINS: OnceLock<BuilderChains> = OnceLock::new();
+
+/// Every chain the dungeon can be built with, plus the builders that finish any of them
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct BuilderChains {
+    pub chains: Vec<ChainDefinition>,
+    #[serde(default)]
+    pub finishers: Vec<MetaBuilderConfig>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ChainDefinition {
+    pub name: String,
+    pub weights: Vec<DepthWeight>,
+    pub starter: StarterConfig,
+    #[serde(default)]
+    pub builders: Vec<MetaBuilderConfig>,
+}
+
+/// How likely a chain is picked on the depths from `min_depth` to `max_depth` (inclusive,
+/// no `max_depth` means every depth below `min_depth`)
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DepthWeight {
+    pub min_depth: i32,
+    pub max_depth: Option<i32>,
+    pub weight: i32,
+}
+
+#[derive(Deserialize, Debug)]
+pub enum StarterConfig {
+    SimpleMap,
+    BspDungeon,
+    BspInterior,
+    CellularAutomata,
+    DrunkardsWalk(DrunkardsWalkKind),
+    Maze,
+    Dla(DlaKind),
+    Voronoi(VoronoiKind),
+    PrefabLevel(PrefabLevelName),
+    /// One of the starters, picked with equal odds
+    OneOf(Vec<StarterConfig>),
+}
+
+#[derive(Deserialize, Debug)]
+pub enum DrunkardsWalkKind {
+    OpenArea,
+    OpenHalls,
+    WindingPassages,
+    FatPassages,
+    FearfulSymmetry,
+}
+
+#[derive(Deserialize, Debug)]
+pub enum DlaKind {
+    WalkInwards,
+    WalkOutwards,
+    CentralAttractor,
+    Insectoid,
+    HeavyErosion,
+}
+
+#[derive(Deserialize, Debug)]
+pub enum VoronoiKind {
+    Pythagoras,
+    Manhattan,
+}
+
+#[derive(Deserialize, Debug)]
+pub enum PrefabLevelName {
+    WfcPopulated,
+}
+
+#[derive(Deserialize, Debug)]
+pub enum PrefabSectionName {
+    UndergroundFort,
+}
+
+#[derive(Deserialize, Debug)]
+pub enum StartX {
+    Left,
+    Center,
+    Right,
+    Random,
+}
+
+#[derive(Deserialize, Debug)]
+pub enum StartY {
+    Top,
+    Center,
+    Bottom,
+    Random,
+}
+
+#[derive(Deserialize, Debug)]
+pub enum MetaBuilderConfig {
+    RoomSorter(RoomSort),
+    RoomDrawer,
+    DoglegCorridors,
+    NearestCorridors,
+    StraightLineCorridors,
+    BspCorridors,
+    CorridorSpawner,
+    RoomExploder,
+    RoomCornerRounder,
+    RoomBasedStartingPosition,
+    AreaStartingPosition {
+        x: StartX,
+        y: StartY,
+    },
+    RoomBasedStairs,
+    DistantExit,
+    RoomBasedSpawner,
+    VoronoiSpawning,
+    CullUnreachable,
+    WaveformCollapse,
+    PrefabSection(PrefabSectionName),
+    RoomVaults,
+    DoorPlacement,
+    /// One of the lists of builders, picked with equal odds
+    OneOf(Vec<Vec<MetaBuilderConfig>>),
+    /// The builders only run one time in `one_in`
+    Chance {
+        one_in: i32,
+        builders: Vec<MetaBuilderConfig>,
+    },
+}
+
+/// What the builders so far have certainly left in `BuilderMap`, some builders need it
+#[derive(Copy, Clone)]
+struct ChainState {
+    rooms: bool,
+    corridors: bool,
+    start: bool,
+    exit: bool,
+}
+
+impl ChainState {
+    fn both(self, other: ChainState) -> ChainState {
+        ChainState {
+            rooms: self.rooms && other.rooms,
+            corridors: self.corridors && other.corridors,
+            start: self.start && other.start,
+            exit: self.exit && other.exit,
+        }
+    }
+}
+
+impl BuilderChains {
+    /// Parses the chains and checks that every one of them can actually build a level
+    pub fn load(config: &str) -> Result<BuilderChains, String> {
+        let chains: BuilderChains = serde_json::from_str(config)
+            .map_err(|error| format!("Unable to parse the builder chains: {}", error))?;
+
+        chains.validate()?;
+
+        Ok(chains)
+    }
+
+    pub fn chain(&self, name: &str) -> Option<&ChainDefinition> {
+        self.chains.iter().find(|chain| chain.name == name)
+    }
+
+    /// Picks one of the chains allowed at `depth`, the higher its weight the more likely
+    pub fn pick(&self, depth: i32, rng: &mut RandomNumberGenerator) -> &ChainDefinition {
+        let total_weight: i32 = self.chains.iter().map(|chain| chain.weight(depth)).sum();
+        let mut roll = rng.roll_dice(1, total_weight);
+
+        for chain in self.chains.iter() {
+            roll -= chain.weight(depth);
+
+            if roll <= 0 {
+                return chain;
+            }
+        }
+
+        unreachable!("`validate` makes sure every depth has a chain")
+    }
+
+    /// Turns a chain definition, followed by the finishers, into a `BuilderChain`
+    pub fn build(
+        &self,
+        chain: &ChainDefinition,
+        new_depth: i32,
+        rng: &mut RandomNumberGenerator,
+        spawn_rng: RandomNumberGenerator,
+        width: i32,
+        height: i32,
+    ) -> BuilderChain {
+        let mut builder = BuilderChain::new(&chain.name, new_depth, width, height, spawn_rng);
+
+        chain.starter.add_to(&mut builder, rng);
+
+        for meta_builder in chain.builders.iter().chain(self.finishers.iter()) {
+            meta_builder.add_to(&mut builder, rng);
+        }
+
+        builder
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.chains.is_empty() {
+            return Err("There has to be at least one builder chain".to_string());
+        }
+
+        for chain in self.chains.iter() {
+            chain
+                .validate(&self.finishers)
+                .map_err(|error| format!("Builder chain '{}': {}", chain.name, error))?;
+        }
+
+        // Past the last `max_depth` the weights no longer change, so checking one more depth
+        // covers all of the deeper ones
+        let last_depth = self
+            .chains
+            .iter()
+            .flat_map(|chain| chain.weights.iter())
+            .map(|weight| weight.max_depth.unwrap_or(weight.min_depth))
+            .max()
+            .unwrap_or(1);
+
+        for depth in 1..=last_depth + 1 {
+            if self.chains.iter().all(|chain| chain.weight(depth) == 0) {
+                return Err(format!("No builder chain can build depth {}", depth));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ChainDefinition {
+    pub fn weight(&self, depth: i32) -> i32 {
+        self.weights
+            .iter()
+            .filter(|weight| {
+                depth >= weight.min_depth && weight.max_depth.is_none_or(|max| depth <= max)
+            })
+            .map(|weight| weight.weight)
+            .sum()
+    }
+
+    fn validate(&self, finishers: &[MetaBuilderConfig]) -> Result<(), String> {
+        for weight in self.weights.iter() {
+            if weight.weight < 0 || weight.max_depth.is_some_and(|max| max < weight.min_depth) {
+                return Err(format!("Invalid weight {:?}", weight));
+            }
+        }
+
+        let state = self.starter.validate()?;
+        let state = validate_builders(&self.builders, state)?;
+        let state = validate_builders(finishers, state)?;
+
+        if !state.start {
+            return Err("Nothing places the player's starting position".to_string());
+        }
+        if !state.exit {
+            return Err("Nothing places the stairs down".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+impl StarterConfig {
+    fn add_to(&self, builder: &mut BuilderChain, rng: &mut RandomNumberGenerator) {
+        match self {
+            StarterConfig::SimpleMap => builder.start_with(SimpleMapBuilder::new()),
+            StarterConfig::BspDungeon => builder.start_with(BspDungeonBuilder::new()),
+            StarterConfig::BspInterior => builder.start_with(BspInteriorBuilder::new()),
+            StarterConfig::CellularAutomata => builder.start_with(CellularAutomataBuilder::new()),
+            StarterConfig::DrunkardsWalk(kind) => builder.start_with(match kind {
+                DrunkardsWalkKind::OpenArea => DrunkardsWalkBuilder::open_area(),
+                DrunkardsWalkKind::OpenHalls => DrunkardsWalkBuilder::open_halls(),
+                DrunkardsWalkKind::WindingPassages => DrunkardsWalkBuilder::winding_passages(),
+                DrunkardsWalkKind::FatPassages => DrunkardsWalkBuilder::fat_passages(),
+                DrunkardsWalkKind::FearfulSymmetry => DrunkardsWalkBuilder::fearful_symmetry(),
+            }),
+            StarterConfig::Maze => builder.start_with(MazeBuilder::new()),
+            StarterConfig::Dla(kind) => builder.start_with(match kind {
+                DlaKind::WalkInwards => DLABuilder::walk_inwards(),
+                DlaKind::WalkOutwards => DLABuilder::walk_outwards(),
+                DlaKind::CentralAttractor => DLABuilder::central_attractor(),
+                DlaKind::Insectoid => DLABuilder::insectoid(),
+                DlaKind::HeavyErosion => DLABuilder::heavy_erosion(),
+            }),
+            StarterConfig::Voronoi(kind) => builder.start_with(match kind {
+                VoronoiKind::Pythagoras => VoronoiCellBuilder::pythagoras(),
+                VoronoiKind::Manhattan => VoronoiCellBuilder::manhattan(),
+            }),
+            StarterConfig::PrefabLevel(name) => {
+                builder.start_with(PrefabBuilder::constant(match name {
+                    PrefabLevelName::WfcPopulated => prefab_levels::WFC_POPULATED,
+                }))
+            }
+            StarterConfig::OneOf(starters) => {
+                let i = rng.roll_dice(1, starters.len() as i32) - 1;
+
+                starters[i as usize].add_to(builder, rng);
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<ChainState, String> {
+        let rooms = matches!(
+            self,
+            StarterConfig::SimpleMap | StarterConfig::BspDungeon | StarterConfig::BspInterior
+        );
+
+        match self {
+            StarterConfig::OneOf(starters) => {
+                let mut states = starters.iter().map(|starter| starter.validate());
+                let first = states
+                    .next()
+                    .ok_or_else(|| "OneOf needs at least one starter".to_string())??;
+
+                states.try_fold(first, |state, other| Ok(state.both(other?)))
+            }
+            _ => Ok(ChainState {
+                rooms,
+                corridors: false,
+                start: false,
+                exit: false,
+            }),
+        }
+    }
+}
+
+impl MetaBuilderConfig {
+    fn add_to(&self, builder: &mut BuilderChain, rng: &mut RandomNumberGenerator) {
+        match self {
+            MetaBuilderConfig::RoomSorter(sort) => builder.with(RoomSorter::new(*sort)),
+            MetaBuilderConfig::RoomDrawer => builder.with(RoomDrawer::new()),
+            MetaBuilderConfig::DoglegCorridors => builder.with(DoglegCorridors::new()),
+            MetaBuilderConfig::NearestCorridors => builder.with(NearestCorridors::new()),
+            MetaBuilderConfig::StraightLineCorridors => builder.with(StraightLineCorridors::new()),
+            MetaBuilderConfig::BspCorridors => builder.with(BspCorridors::new()),
+            MetaBuilderConfig::CorridorSpawner => builder.with(CorridorSpawner::new()),
+            MetaBuilderConfig::RoomExploder => builder.with(RoomExploder::new()),
+            MetaBuilderConfig::RoomCornerRounder => builder.with(RoomCornerRounder::new()),
+            MetaBuilderConfig::RoomBasedStartingPosition => {
+                builder.with(RoomBasedStartingPosition::new())
+            }
+            MetaBuilderConfig::AreaStartingPosition { x, y } => {
+                let x = match x {
+                    StartX::Left => XStart::Left,
+                    StartX::Center => XStart::Center,
+                    StartX::Right => XStart::Right,
+                    StartX::Random => match rng.roll_dice(1, 3) {
+                        1 => XStart::Left,
+                        2 => XStart::Center,
+                        _ => XStart::Right,
+                    },
+                };
+                let y = match y {
+                    StartY::Top => YStart::Top,
+                    StartY::Center => YStart::Center,
+                    StartY::Bottom => YStart::Bottom,
+                    StartY::Random => match rng.roll_dice(1, 3) {
+                        1 => YStart::Bottom,
+                        2 => YStart::Center,
+                        _ => YStart::Top,
+                    },
+                };
+
+                builder.with(AreaStartingPosition::new(x, y));
+            }
+            MetaBuilderConfig::RoomBasedStairs => builder.with(RoomBasedStairs::new()),
+            MetaBuilderConfig::DistantExit => builder.with(DistantExit::new()),
+            MetaBuilderConfig::RoomBasedSpawner => builder.with(RoomBasedSpawner::new()),
+            MetaBuilderConfig::VoronoiSpawning => builder.with(VoronoiSpawning::new()),
+            MetaBuilderConfig::CullUnreachable => builder.with(CullUnreachable::new()),
+            MetaBuilderConfig::WaveformCollapse => builder.with(WaveformCollapseBuilder::new()),
+            MetaBuilderConfig::PrefabSection(name) => {
+                builder.with(PrefabBuilder::sectional(match name {
+                    PrefabSectionName::UndergroundFort => prefab_sections::UNDERGROUND_FORT,
+                }))
+            }
+            MetaBuilderConfig::RoomVaults => builder.with(PrefabBuilder::vaults()),
+            MetaBuilderConfig::DoorPlacement => builder.with(DoorPlacement::new()),
+            MetaBuilderConfig::OneOf(options) => {
+                let i = rng.roll_dice(1, options.len() as i32) - 1;
+
+                for meta_builder in options[i as usize].iter() {
+                    meta_builder.add_to(builder, rng);
+                }
+            }
+            MetaBuilderConfig::Chance { one_in, builders } => {
+                if rng.roll_dice(1, *one_in) == 1 {
+                    for meta_builder in builders.iter() {
+                        meta_builder.add_to(builder, rng);
+                    }
+                }
+            }
+        }
+    }
+
+    fn validate(&self, state: ChainState) -> Result<ChainState, String> {
+        let needs_rooms = matches!(
+            self,
+            MetaBuilderConfig::RoomSorter(_)
+                | MetaBuilderConfig::RoomDrawer
+                | MetaBuilderConfig::DoglegCorridors
+                | MetaBuilderConfig::NearestCorridors
+                | MetaBuilderConfig::StraightLineCorridors
+                | MetaBuilderConfig::BspCorridors
+                | MetaBuilderConfig::RoomExploder
+                | MetaBuilderConfig::RoomCornerRounder
+                | MetaBuilderConfig::RoomBasedStartingPosition
+                | MetaBuilderConfig::RoomBasedStairs
+                | MetaBuilderConfig::RoomBasedSpawner
+        );
+        let needs_start = matches!(
+            self,
+            MetaBuilderConfig::DistantExit | MetaBuilderConfig::CullUnreachable
+        );
+
+        if needs_rooms && !state.rooms {
+            return Err(format!("{:?} needs a builder that makes rooms first", self));
+        }
+        if needs_start && !state.start {
+            return Err(format!(
+                "{:?} needs a builder that places the starting position first",
+                self
+            ));
+        }
+        if matches!(self, MetaBuilderConfig::CorridorSpawner) && !state.corridors {
+            return Err("CorridorSpawner needs a builder that makes corridors first".to_string());
+        }
+
+        let mut state = state;
+
+        match self {
+            MetaBuilderConfig::DoglegCorridors
+            | MetaBuilderConfig::NearestCorridors
+            | MetaBuilderConfig::StraightLineCorridors
+            | MetaBuilderConfig::BspCorridors => state.corridors = true,
+            MetaBuilderConfig::RoomBasedStartingPosition
+            | MetaBuilderConfig::AreaStartingPosition { .. } => state.start = true,
+            MetaBuilderConfig::RoomBasedStairs | MetaBuilderConfig::DistantExit => {
+                state.exit = true
+            }
+            MetaBuilderConfig::OneOf(options) => {
+                let mut states = options
+                    .iter()
+                    .map(|builders| validate_builders(builders, state));
+                let first = states
+                    .next()
+                    .ok_or_else(|| "OneOf needs at least one option".to_string())??;
+
+                state = states.try_fold(first, |state, other| {
+                    Ok::<ChainState, String>(state.both(other?))
+                })?;
+            }
+            MetaBuilderConfig::Chance { one_in, builders } => {
+                if *one_in < 1 {
+                    return Err(format!(
+                        "Chance needs one_in to be at least 1, got {}",
+                        one_in
+                    ));
+                }
+
+                state = state.both(validate_builders(builders, state)?);
+            }
+            _ => {}
+        }
+
+        Ok(state)
+    }
+}
+
+fn validate_builders(
+    builders: &[MetaBuilderConfig],
+    state: ChainState,
+) -> Result<ChainState, String> {
+    builders
+        .iter()
+        .try_fold(state, |state, meta_builder| meta_builder.validate(state))
+}
+
+/// Loads and checks the builder chains on first use, later calls return the same ones
+pub fn load_builder_chains() -> Result<&'static BuilderChains, String> {
+    if let Some(chains) = BUILDER_CHAINS.get() {
+        return Ok(chains);
+    }
+
+    let chains = if Path::new(BUILDER_CHAINS_FILE).exists() {
+        let config = fs::read_to_string(BUILDER_CHAINS_FILE)
+            .map_err(|error| format!("Unable to read {}: {}", BUILDER_CHAINS_FILE, error))?;
+
+        BuilderChains::load(&config)
+            .map_err(|error| format!("{}: {}", BUILDER_CHAINS_FILE, error))?
+    } else {
+        BuilderChains::load(EMBEDDED_BUILDER_CHAINS)?
+    };
+
+    Ok(BUILDER_CHAINS.get_or_init(|| chains))
+}
+
+/// The loaded builder chains, `main` loads them up front to report a broken file
+pub fn builder_chains() -> &'static BuilderChains {
+    load_builder_chains().unwrap_or_else(|error| panic!("{}", error))
+}
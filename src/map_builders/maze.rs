@@ -85,7 +85,7 @@ struct Grid<'a> {
 }
 
 impl<'a> Grid<'a> {
-    fn new(width: i32, height: i32, rng: &mut rltk::RandomNumberGenerator) -> Grid {
+    fn new(width: i32, height: i32, rng: &mut rltk::RandomNumberGenerator) -> Grid<'_> {
         let mut grid = Grid {
             width,
             height,
@@ -2,6 +2,7 @@ mod area_starting_points;
 mod bsp_dungeon;
 mod bsp_interior;
 mod cellular_automata;
+pub mod chain_config;
 pub mod common;
 mod cull_unreachable;
 mod distant_exit;
@@ -30,38 +31,22 @@ mod waveform_collapse;
 use specs::World;
 
 use crate::components::Position;
-use crate::map::Map;
-use crate::map_builders::area_starting_points::{AreaStartingPosition, XStart, YStart};
-use crate::map_builders::bsp_dungeon::BspDungeonBuilder;
-use crate::map_builders::bsp_interior::BspInteriorBuilder;
-use crate::map_builders::cellular_automata::CellularAutomataBuilder;
+use crate::map::{Map, TileType};
 use crate::map_builders::common::Corridor;
-use crate::map_builders::cull_unreachable::CullUnreachable;
-use crate::map_builders::distant_exit::DistantExit;
-use crate::map_builders::dla::DLABuilder;
-use crate::map_builders::door_placement::DoorPlacement;
-use crate::map_builders::drunkard::DrunkardsWalkBuilder;
-use crate::map_builders::maze::MazeBuilder;
-use crate::map_builders::prefab_builder::PrefabBuilder;
-use crate::map_builders::room_based_spawner::RoomBasedSpawner;
-use crate::map_builders::room_based_stairs::RoomBasedStairs;
-use crate::map_builders::room_based_starting_position::RoomBasedStartingPosition;
-use crate::map_builders::room_corner_rounding::RoomCornerRounder;
-use crate::map_builders::room_corridor_spawner::CorridorSpawner;
-use crate::map_builders::room_draw::RoomDrawer;
-use crate::map_builders::room_exploder::RoomExploder;
-use crate::map_builders::room_sorter::{RoomSort, RoomSorter};
-use crate::map_builders::rooms_corridors_bsp::BspCorridors;
-use crate::map_builders::rooms_corridors_dogleg::DoglegCorridors;
-use crate::map_builders::rooms_corridors_lines::StraightLineCorridors;
-use crate::map_builders::rooms_corridors_nearest::NearestCorridors;
-use crate::map_builders::simple_map::SimpleMapBuilder;
-use crate::map_builders::voronoi::VoronoiCellBuilder;
-use crate::map_builders::voronoi_spawning::VoronoiSpawning;
-use crate::map_builders::waveform_collapse::WaveformCollapseBuilder;
 use crate::rect::Rect;
 use crate::{spawner, SHOW_MAPGEN_VISUALIZER};
 
+/// Entities the builders place by name instead of rolling them on the spawn table, the raws have
+/// to define all of them
+pub const NAMED_SPAWNS: [&str; 6] = [
+    "Goblin",
+    "Orc",
+    "Bear Trap",
+    "Rations",
+    "Health Potion",
+    "Door",
+];
+
 pub trait InitialMapBuilder {
     fn build_map(&mut self, rng: &mut rltk::RandomNumberGenerator, build_data: &mut BuilderMap);
 }
@@ -77,11 +62,16 @@ pub struct BuilderMap {
     pub rooms: Option<Vec<Rect>>,
     pub corridors: Option<Vec<Corridor>>,
     pub history: Vec<Map>,
+    /// Spawners roll with this instead of the layout generator, so what is spawned never
+    /// changes the shape of the map
+    pub spawn_rng: rltk::RandomNumberGenerator,
+    /// Whether the builders record a snapshot of the map after each step, see `history`
+    pub keep_history: bool,
 }
 
 impl BuilderMap {
     fn take_snapshot(&mut self) {
-        if !SHOW_MAPGEN_VISUALIZER {
+        if !self.keep_history {
             return;
         }
 
@@ -94,6 +84,8 @@ impl BuilderMap {
 }
 
 pub struct BuilderChain {
+    /// The name of the chain in `raws/builder_chains.json` it was made from
+    pub name: String,
     starter: Option<Box<dyn InitialMapBuilder>>,
     builders: Vec<Box<dyn MetaMapBuilder>>,
     pub build_data: BuilderMap,
@@ -101,8 +93,15 @@ pub struct BuilderChain {
 
 // TODO(DP): use fluent interface for methods
 impl BuilderChain {
-    pub fn new(new_depth: i32, width: i32, height: i32) -> BuilderChain {
+    pub fn new(
+        name: &str,
+        new_depth: i32,
+        width: i32,
+        height: i32,
+        spawn_rng: rltk::RandomNumberGenerator,
+    ) -> BuilderChain {
         BuilderChain {
+            name: name.to_string(),
             starter: None,
             builders: Vec::new(),
             build_data: BuilderMap {
@@ -112,6 +111,8 @@ impl BuilderChain {
                 rooms: None,
                 corridors: None,
                 history: Vec::new(),
+                spawn_rng,
+                keep_history: SHOW_MAPGEN_VISUALIZER,
             },
         }
     }
@@ -141,164 +142,59 @@ impl BuilderChain {
         for meta_builder in self.builders.iter_mut() {
             meta_builder.build_map(rng, &mut self.build_data);
         }
-    }
 
-    pub fn spawn_entities(&mut self, ecs: &mut World) {
-        for (map_idx, entity_name) in self.build_data.spawn_list.iter() {
-            spawner::spawn_entity(ecs, &(map_idx, entity_name));
-        }
+        self.place_up_stairs();
     }
-}
-
-fn random_start_position(rng: &mut rltk::RandomNumberGenerator) -> (XStart, YStart) {
-    let x_roll = rng.roll_dice(1, 3);
-    let x = match x_roll {
-        1 => XStart::Left,
-        2 => XStart::Center,
-        _ => XStart::Right,
-    };
 
-    let y_roll = rng.roll_dice(1, 3);
-    let y = match y_roll {
-        1 => YStart::Bottom,
-        2 => YStart::Center,
-        _ => YStart::Top,
-    };
+    /// Below the first level the way back up is where the player arrives
+    fn place_up_stairs(&mut self) {
+        let map = &mut self.build_data.map;
 
-    (x, y)
-}
-
-fn random_room_builder(rng: &mut rltk::RandomNumberGenerator, builder: &mut BuilderChain) {
-    let build_roll = rng.roll_dice(1, 3);
-    match build_roll {
-        1 => builder.start_with(SimpleMapBuilder::new()),
-        2 => builder.start_with(BspDungeonBuilder::new()),
-        _ => builder.start_with(BspInteriorBuilder::new()),
-    }
-
-    // BSP Interior still makes holes in the walls
-    if build_roll != 3 {
-        // Sort by one of the 5 available algorithms
-        let sort_roll = rng.roll_dice(1, 5);
-        match sort_roll {
-            1 => builder.with(RoomSorter::new(RoomSort::Leftmost)),
-            2 => builder.with(RoomSorter::new(RoomSort::Rightmost)),
-            3 => builder.with(RoomSorter::new(RoomSort::Topmost)),
-            4 => builder.with(RoomSorter::new(RoomSort::Bottommost)),
-            _ => builder.with(RoomSorter::new(RoomSort::Central)),
-        }
-
-        builder.with(RoomDrawer::new());
-
-        let corridor_roll = rng.roll_dice(1, 4);
-        match corridor_roll {
-            1 => builder.with(DoglegCorridors::new()),
-            2 => builder.with(NearestCorridors::new()),
-            3 => builder.with(StraightLineCorridors::new()),
-            _ => builder.with(BspCorridors::new()),
+        if map.depth <= 1 {
+            return;
         }
 
-        let spawn_roll = rng.roll_dice(1, 2);
-        if spawn_roll == 1 {
-            builder.with(CorridorSpawner::new())
-        }
+        if let Some(start) = &self.build_data.starting_position {
+            let idx = map.xy_idx(start.x, start.y);
 
-        let modifier_roll = rng.roll_dice(1, 6);
-        match modifier_roll {
-            1 => builder.with(RoomExploder::new()),
-            2 => builder.with(RoomCornerRounder::new()),
-            _ => {}
+            map.tiles[idx] = TileType::UpStairs;
         }
     }
 
-    let start_roll = rng.roll_dice(1, 2);
-    match start_roll {
-        1 => builder.with(RoomBasedStartingPosition::new()),
-        _ => {
-            let (start_x, start_y) = random_start_position(rng);
-            builder.with(AreaStartingPosition::new(start_x, start_y));
+    pub fn spawn_entities(&mut self, ecs: &mut World) {
+        for (map_idx, entity_name) in self.build_data.spawn_list.iter() {
+            spawner::spawn_entity(ecs, &(map_idx, entity_name));
         }
     }
-
-    let exit_roll = rng.roll_dice(1, 2);
-    match exit_roll {
-        1 => builder.with(RoomBasedStairs::new()),
-        _ => builder.with(DistantExit::new()),
-    }
-
-    let spawn_roll = rng.roll_dice(1, 2);
-    match spawn_roll {
-        1 => builder.with(RoomBasedSpawner::new()),
-        _ => builder.with(VoronoiSpawning::new()),
-    }
-}
-
-fn random_shape_builder(rng: &mut rltk::RandomNumberGenerator, builder: &mut BuilderChain) {
-    let builder_roll = rng.roll_dice(1, 16);
-    match builder_roll {
-        1 => builder.start_with(CellularAutomataBuilder::new()),
-        2 => builder.start_with(DrunkardsWalkBuilder::open_area()),
-        3 => builder.start_with(DrunkardsWalkBuilder::open_halls()),
-        4 => builder.start_with(DrunkardsWalkBuilder::winding_passages()),
-        5 => builder.start_with(DrunkardsWalkBuilder::fat_passages()),
-        6 => builder.start_with(DrunkardsWalkBuilder::fearful_symmetry()),
-        7 => builder.start_with(MazeBuilder::new()),
-        8 => builder.start_with(DLABuilder::walk_inwards()),
-        9 => builder.start_with(DLABuilder::walk_outwards()),
-        10 => builder.start_with(DLABuilder::central_attractor()),
-        11 => builder.start_with(DLABuilder::insectoid()),
-        12 => builder.start_with(VoronoiCellBuilder::pythagoras()),
-        13 => builder.start_with(VoronoiCellBuilder::manhattan()),
-        _ => builder.start_with(PrefabBuilder::constant(
-            prefab_builder::prefab_levels::WFC_POPULATED,
-        )),
-    }
-
-    // Set the start to the center and cull
-    builder.with(AreaStartingPosition::new(XStart::Center, YStart::Center));
-    builder.with(CullUnreachable::new());
-
-    // Now set the start to a random starting area
-    let (start_x, start_y) = random_start_position(rng);
-    builder.with(AreaStartingPosition::new(start_x, start_y));
-
-    // Setup an exit and spawn mobs
-    builder.with(VoronoiSpawning::new());
-    builder.with(DistantExit::new());
 }
 
+/// Builds a level with one of the chains from `raws/builder_chains.json` allowed at `new_depth`
 pub fn random_builder(
     new_depth: i32,
     rng: &mut rltk::RandomNumberGenerator,
+    spawn_rng: rltk::RandomNumberGenerator,
     width: i32,
     height: i32,
 ) -> BuilderChain {
-    let mut builder = BuilderChain::new(new_depth, width, height);
-    match rng.roll_dice(1, 2) {
-        1 => random_room_builder(rng, &mut builder),
-        _ => random_shape_builder(rng, &mut builder),
-    }
+    let chains = chain_config::builder_chains();
+    let chain = chains.pick(new_depth, rng);
 
-    if rng.roll_dice(1, 3) == 1 {
-        builder.with(WaveformCollapseBuilder::new());
-
-        // Now set the start to a random starting area
-        let (start_x, start_y) = random_start_position(rng);
-        builder.with(AreaStartingPosition::new(start_x, start_y));
-
-        // Setup an exit and spawn mobs
-        builder.with(VoronoiSpawning::new());
-        builder.with(DistantExit::new());
-    }
-
-    if rng.roll_dice(1, 20) == 1 {
-        builder.with(PrefabBuilder::sectional(
-            prefab_builder::prefab_sections::UNDERGROUND_FORT,
-        ));
-    }
+    chains.build(chain, new_depth, rng, spawn_rng, width, height)
+}
 
-    builder.with(DoorPlacement::new());
-    builder.with(PrefabBuilder::vaults());
+/// Builds a level with the chain called `name` in `raws/builder_chains.json`, whatever the depth
+pub fn named_builder(
+    name: &str,
+    new_depth: i32,
+    rng: &mut rltk::RandomNumberGenerator,
+    spawn_rng: rltk::RandomNumberGenerator,
+    width: i32,
+    height: i32,
+) -> Result<BuilderChain, String> {
+    let chains = chain_config::builder_chains();
+    let chain = chains
+        .chain(name)
+        .ok_or_else(|| format!("There is no builder chain named '{}'", name))?;
 
-    builder
+    Ok(chains.build(chain, new_depth, rng, spawn_rng, width, height))
 }
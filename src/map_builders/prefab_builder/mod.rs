@@ -150,7 +150,10 @@ impl PrefabBuilder {
                 if tx < build_data.map.width as usize && ty < build_data.map.height as usize {
                     let idx = build_data.map.xy_idx(tx as i32, ty as i32);
 
-                    self.char_to_map(string_vec[i], idx, build_data);
+                    // The template is short of `width` x `height`, what is missing stays a wall
+                    if let Some(ch) = string_vec.get(i) {
+                        self.char_to_map(*ch, idx, build_data);
+                    }
                 }
 
                 i += 1;
@@ -266,7 +269,7 @@ impl PrefabBuilder {
         }
 
         // Note that this is a place-holder and will be moved out of this function
-        let master_vault_list = vec![TOTALLY_NOT_A_TRAP, CHECKERBOARD, SILLY_SMILE];
+        let master_vault_list = [TOTALLY_NOT_A_TRAP, CHECKERBOARD, SILLY_SMILE];
 
         // Filter the vault list down to ones that are applicable to the current depth
         let mut possible_vaults: Vec<&PrefabRoom> = master_vault_list
@@ -360,7 +363,10 @@ impl PrefabBuilder {
                             .map
                             .xy_idx(tx as i32 + chunk_x, ty as i32 + chunk_y);
 
-                        self.char_to_map(string_vec[i], idx, build_data);
+                        // The template is short of `width` x `height`, what is missing stays a wall
+                        if let Some(ch) = string_vec.get(i) {
+                            self.char_to_map(*ch, idx, build_data);
+                        }
 
                         used_tiles.insert(idx);
                         i += 1;
@@ -17,12 +17,12 @@ impl RoomBasedSpawner {
         Box::new(RoomBasedSpawner {})
     }
 
-    fn build(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+    fn build(&mut self, _rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
         if let Some(rooms) = &build_data.rooms {
             for room in rooms.iter().skip(1) {
                 spawner::spawn_room(
                     &build_data.map,
-                    rng,
+                    &mut build_data.spawn_rng,
                     room,
                     build_data.map.depth,
                     &mut build_data.spawn_list,
@@ -15,13 +15,13 @@ impl CorridorSpawner {
         Box::new(CorridorSpawner {})
     }
 
-    fn build(&mut self, rng: &mut rltk::RandomNumberGenerator, build_data: &mut BuilderMap) {
+    fn build(&mut self, _rng: &mut rltk::RandomNumberGenerator, build_data: &mut BuilderMap) {
         if let Some(corridors) = &build_data.corridors {
             for corridor in corridors.iter() {
                 let depth = build_data.map.depth;
                 spawner::spawn_region(
                     &build_data.map,
-                    rng,
+                    &mut build_data.spawn_rng,
                     corridor,
                     depth,
                     &mut build_data.spawn_list,
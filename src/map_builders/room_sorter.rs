@@ -1,6 +1,9 @@
+use serde::Deserialize;
+
 use crate::map::Map;
 use crate::map_builders::{BuilderMap, MetaMapBuilder};
 
+#[derive(Deserialize, Copy, Clone, Debug)]
 pub enum RoomSort {
     Leftmost,
     Rightmost,
@@ -30,10 +33,10 @@ impl RoomSorter {
         let rooms = build_data.rooms.as_mut().unwrap();
 
         match self.sort_by {
-            RoomSort::Leftmost => rooms.sort_by(|a, b| a.x1.cmp(&b.x1)),
-            RoomSort::Rightmost => rooms.sort_by(|a, b| b.x2.cmp(&a.x2)),
-            RoomSort::Topmost => rooms.sort_by(|a, b| a.y1.cmp(&b.y1)),
-            RoomSort::Bottommost => rooms.sort_by(|a, b| b.y2.cmp(&a.y2)),
+            RoomSort::Leftmost => rooms.sort_by_key(|a| a.x1),
+            RoomSort::Rightmost => rooms.sort_by_key(|a| std::cmp::Reverse(a.x2)),
+            RoomSort::Topmost => rooms.sort_by_key(|a| a.y1),
+            RoomSort::Bottommost => rooms.sort_by_key(|a| std::cmp::Reverse(a.y2)),
             RoomSort::Central => {
                 let Map { width, height, .. } = build_data.map;
                 let map_center = rltk::Point::new(width / 2, height / 2);
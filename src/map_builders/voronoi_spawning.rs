@@ -1,5 +1,5 @@
 use crate::map::TileType;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::map_builders::{BuilderMap, MetaMapBuilder};
 use crate::spawner;
@@ -19,9 +19,9 @@ impl VoronoiSpawning {
     }
 
     #[allow(clippy::map_entry)]
-    fn build(&mut self, rng: &mut rltk::RandomNumberGenerator, build_data: &mut BuilderMap) {
-        let mut noise_areas: HashMap<i32, Vec<usize>> = HashMap::new();
-        let mut noise = rltk::FastNoise::seeded(rng.roll_dice(1, 65536) as u64);
+    fn build(&mut self, _rng: &mut rltk::RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let mut noise_areas: BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+        let mut noise = rltk::FastNoise::seeded(build_data.spawn_rng.roll_dice(1, 65536) as u64);
 
         noise.set_noise_type(rltk::NoiseType::Cellular);
         noise.set_frequency(0.08);
@@ -50,7 +50,7 @@ impl VoronoiSpawning {
         for area in noise_areas.iter() {
             spawner::spawn_region(
                 &build_data.map,
-                rng,
+                &mut build_data.spawn_rng,
                 area.1,
                 build_data.map.depth,
                 &mut build_data.spawn_list,
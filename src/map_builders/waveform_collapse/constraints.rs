@@ -74,8 +74,10 @@ pub fn build_patterns(
             patterns.len()
         ));
 
-        let set: HashSet<Vec<TileType>> = patterns.drain(..).collect(); // dedup
-        patterns.extend(set.into_iter());
+        // Keeps the first of each pattern in place, a plain set would shuffle them differently on
+        // every run and break seeded map generation
+        let mut seen: HashSet<Vec<TileType>> = HashSet::new();
+        patterns.retain(|pattern| seen.insert(pattern.clone()));
         rltk::console::log(format!("There are {} patterns", patterns.len()));
     }
 
@@ -57,11 +57,15 @@ impl WaveformCollapseBuilder {
 
     fn render_tile_gallery(
         &mut self,
-        patterns: &Vec<MapChunk>,
+        patterns: &[MapChunk],
         chunk_size: i32,
         build_data: &mut BuilderMap,
     ) {
-        build_data.map = Map::new(0, build_data.map.width, build_data.map.height);
+        build_data.map = Map::new(
+            build_data.map.depth,
+            build_data.map.width,
+            build_data.map.height,
+        );
 
         let mut counter = 0;
         let mut x = 1;
@@ -80,7 +84,11 @@ impl WaveformCollapseBuilder {
                 if y + chunk_size > build_data.map.height {
                     // Move to the next page
                     build_data.take_snapshot();
-                    build_data.map = Map::new(0, build_data.map.width, build_data.map.height);
+                    build_data.map = Map::new(
+                        build_data.map.depth,
+                        build_data.map.width,
+                        build_data.map.height,
+                    );
 
                     x = 1;
                     y = 1;
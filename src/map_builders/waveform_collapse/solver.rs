@@ -97,7 +97,7 @@ impl Solver {
             *r = (r.0, neighbor_count);
         }
 
-        remain_copy.sort_by(|a, b| b.1.cmp(&a.1));
+        remain_copy.sort_by_key(|b| std::cmp::Reverse(b.1));
 
         self.remaining = remain_copy;
 
@@ -0,0 +1,193 @@
+use std::fmt::Write;
+
+use crate::components::Position;
+use crate::map::{Map, TileType};
+use crate::map_builders::{self, chain_config, BuilderChain};
+use crate::{raws, rng};
+
+/// What `mapgen` builds, read from its command line
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct MapgenOptions {
+    pub seed: u64,
+    pub depth: i32,
+    pub width: i32,
+    pub height: i32,
+    /// Builds with this chain from `raws/builder_chains.json` instead of picking one by depth
+    pub chain: Option<String>,
+    pub history: bool,
+}
+
+impl Default for MapgenOptions {
+    fn default() -> Self {
+        MapgenOptions {
+            seed: 0,
+            depth: 1,
+            width: 80,
+            height: 50,
+            chain: None,
+            history: false,
+        }
+    }
+}
+
+/// The smallest level every builder chain can lay out, smaller ones run out of room for the rooms
+/// and the start
+pub const MIN_WIDTH: i32 = 40;
+pub const MIN_HEIGHT: i32 = 30;
+
+pub const USAGE: &str =
+    "Usage: mapgen [--seed <number>] [--depth <number>] [--size <width>x<height>] \
+[--chain <name>] [--history]";
+
+/// Reads the `mapgen` options, a missing `--seed` picks a random one
+pub fn parse_arguments(args: &[String]) -> Result<MapgenOptions, String> {
+    let mut options = MapgenOptions {
+        seed: rng::random_seed(),
+        ..MapgenOptions::default()
+    };
+    let mut args = args.iter().skip(1);
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Expected a value after {}", arg))
+        };
+
+        match arg.as_str() {
+            "--seed" => options.seed = parse_number(arg, value()?)?,
+            "--depth" => options.depth = parse_number(arg, value()?)?,
+            "--size" => {
+                let size = value()?;
+                let (width, height) = size.split_once('x').ok_or_else(|| {
+                    format!("Expected <width>x<height> after --size, got '{}'", size)
+                })?;
+
+                options.width = parse_number(arg, width)?;
+                options.height = parse_number(arg, height)?;
+            }
+            "--chain" => options.chain = Some(value()?.clone()),
+            "--history" => options.history = true,
+            _ => return Err(format!("Unknown argument '{}'\n{}", arg, USAGE)),
+        }
+    }
+
+    if options.depth < 1 {
+        return Err("The depth has to be positive".to_string());
+    }
+
+    if options.width < MIN_WIDTH || options.height < MIN_HEIGHT {
+        return Err(format!(
+            "The size has to be at least {}x{}, got {}x{}",
+            MIN_WIDTH, MIN_HEIGHT, options.width, options.height
+        ));
+    }
+
+    Ok(options)
+}
+
+fn parse_number<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value
+        .parse::<T>()
+        .map_err(|_| format!("Expected a number after {}, got '{}'", arg, value))
+}
+
+/// Builds the level the options describe, the chain it was built with is in `name`
+pub fn generate(options: &MapgenOptions) -> Result<BuilderChain, String> {
+    // Reports a broken chains file instead of panicking in `random_builder`
+    chain_config::load_builder_chains()?;
+
+    // The same generators and builders as `State::generate_world_map`, so a seed and depth show
+    // the level the game would build
+    let mut rng = rng::map_rng(options.seed, options.depth);
+    let spawn_rng = rng::spawn_rng(options.seed, options.depth);
+    let (width, height) = (options.width, options.height);
+    let mut builder = match &options.chain {
+        Some(name) => {
+            map_builders::named_builder(name, options.depth, &mut rng, spawn_rng, width, height)?
+        }
+        None => map_builders::random_builder(options.depth, &mut rng, spawn_rng, width, height),
+    };
+
+    builder.build_data.keep_history = options.history;
+    builder.build_map(&mut rng);
+
+    Ok(builder)
+}
+
+/// Draws the map as text: `#` walls, `.` floor, `>` and `<` stairs, `@` the start and the glyphs of
+/// whatever is spawned
+pub fn render_map(
+    map: &Map,
+    starting_position: Option<&Position>,
+    spawn_list: &[(usize, String)],
+) -> String {
+    let mut glyphs: Vec<char> = map
+        .tiles
+        .iter()
+        .map(|tile| match tile {
+            TileType::Wall => '#',
+            TileType::Floor => '.',
+            TileType::DownStairs => '>',
+            TileType::UpStairs => '<',
+        })
+        .collect();
+
+    for (idx, name) in spawn_list.iter() {
+        if let Some(glyph) = glyphs.get_mut(*idx) {
+            *glyph = raws::raws().glyph(name).unwrap_or('?');
+        }
+    }
+
+    if let Some(start) = starting_position {
+        let idx = map.xy_idx(start.x, start.y);
+
+        glyphs[idx] = '@';
+    }
+
+    glyphs
+        .chunks(map.width as usize)
+        .map(|row| row.iter().collect::<String>() + "\n")
+        .collect()
+}
+
+/// Everything `mapgen` prints: a header, the history if asked for, the map and the spawn list
+pub fn run(options: &MapgenOptions) -> Result<String, String> {
+    let builder = generate(options)?;
+    let build_data = &builder.build_data;
+    let mut output = String::new();
+
+    // Writing to a `String` cannot fail
+    writeln!(
+        output,
+        "Seed: {} Depth: {} Size: {}x{} Chain: {}",
+        options.seed, options.depth, options.width, options.height, builder.name
+    )
+    .unwrap();
+
+    for (step, snapshot) in build_data.history.iter().enumerate() {
+        writeln!(output, "\nStep {}", step + 1).unwrap();
+        output.push_str(&render_map(snapshot, None, &[]));
+    }
+
+    output.push('\n');
+    output.push_str(&render_map(
+        &build_data.map,
+        build_data.starting_position.as_ref(),
+        &build_data.spawn_list,
+    ));
+
+    if let Some(start) = &build_data.starting_position {
+        writeln!(output, "\nStart: {},{}", start.x, start.y).unwrap();
+    }
+
+    writeln!(output, "Spawns:").unwrap();
+
+    for (idx, name) in build_data.spawn_list.iter() {
+        let x = *idx as i32 % build_data.map.width;
+        let y = *idx as i32 / build_data.map.width;
+
+        writeln!(output, "  {},{} {}", x, y, name).unwrap();
+    }
+
+    Ok(output)
+}
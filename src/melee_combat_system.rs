@@ -1,8 +1,23 @@
 use specs::prelude::*;
 
+use crate::attributes;
 use crate::components::*;
 use crate::game_log::GameLog;
 use crate::particle_system::ParticleBuilder;
+use crate::rng::CombatRng;
+
+/// What an attack roll has to beat before defense and armour are added
+pub const BASE_ARMOUR_CLASS: i32 = 10;
+/// Fists, for anyone without a weapon or natural attack
+pub const UNARMED_DAMAGE: rltk::DiceType = rltk::DiceType {
+    n_dice: 1,
+    die_type: 3,
+    bonus: 0,
+};
+/// A natural roll of this on the d20 always hits, with double damage dice
+const CRITICAL_ROLL: i32 = 20;
+/// A natural roll of this on the d20 always misses
+const FUMBLE_ROLL: i32 = 1;
 
 pub struct MeleeCombatSystem {}
 
@@ -20,6 +35,12 @@ impl<'a> System<'a> for MeleeCombatSystem {
         WriteExpect<'a, ParticleBuilder>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, HungerClock>,
+        WriteStorage<'a, Provoked>,
+        ReadStorage<'a, MeleeWeapon>,
+        WriteExpect<'a, CombatRng>,
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, Skills>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -36,6 +57,12 @@ impl<'a> System<'a> for MeleeCombatSystem {
             mut particle_builder,
             positions,
             hunger_clock,
+            mut provoked,
+            melee_weapons,
+            mut rng,
+            player_entity,
+            attributes,
+            mut skills,
         ) = data;
 
         for (entity, wants_melee, name, stats) in
@@ -45,13 +72,25 @@ impl<'a> System<'a> for MeleeCombatSystem {
                 continue;
             }
 
-            let target_stats = combat_stats.get(wants_melee.target).unwrap();
+            // Anyone can attack anyone, and the target may have died earlier this turn
+            let Some(target_stats) = combat_stats.get(wants_melee.target) else {
+                continue;
+            };
 
             if target_stats.hp <= 0 {
                 continue;
             }
 
+            provoked
+                .insert(wants_melee.target, Provoked { by: entity })
+                .expect("Unable to insert provocation");
+
             let mut offensive_bonus = 0;
+            // A wielded weapon replaces the natural attack of whoever holds it
+            let (mut damage_dice, mut damage_type) = melee_weapons
+                .get(entity)
+                .map(|weapon| (weapon.damage, weapon.damage_type))
+                .unwrap_or((UNARMED_DAMAGE, DamageType::Physical));
 
             for (_, power_bonus, equipped_by) in (&entities, &melee_power_bonuses, &equipped).join()
             {
@@ -60,13 +99,29 @@ impl<'a> System<'a> for MeleeCombatSystem {
                 }
             }
 
+            for (weapon, equipped_by) in (&melee_weapons, &equipped).join() {
+                if equipped_by.owner == entity {
+                    damage_dice = weapon.damage;
+                    damage_type = weapon.damage_type;
+                }
+            }
+
+            if let Some(attacker_attributes) = attributes.get(entity) {
+                offensive_bonus += attributes::bonus(attacker_attributes.might);
+            }
+
+            offensive_bonus += attributes::skill_level(skills.get(entity), SkillKind::Melee);
+
             if let Some(HungerClock { state, .. }) = hunger_clock.get(entity) {
                 if *state == HungerState::WellFed {
                     offensive_bonus += 1;
                 }
             }
 
-            let target_name = names.get(wants_melee.target).unwrap();
+            let target_name = names
+                .get(wants_melee.target)
+                .map(|name| name.name.as_str())
+                .unwrap_or("something");
 
             let mut defensive_bonus = 0;
 
@@ -76,34 +131,86 @@ impl<'a> System<'a> for MeleeCombatSystem {
                 }
             }
 
+            if let Some(target_attributes) = attributes.get(wants_melee.target) {
+                defensive_bonus += attributes::bonus(target_attributes.quickness);
+            }
+
+            defensive_bonus +=
+                attributes::skill_level(skills.get(wants_melee.target), SkillKind::Defense);
+
+            // Skills rise with every attack, hit or miss, from the next attack on
+            attributes::practice(
+                &mut skills,
+                entity,
+                SkillKind::Melee,
+                *player_entity,
+                &mut log,
+            );
+            attributes::practice(
+                &mut skills,
+                wants_melee.target,
+                SkillKind::Defense,
+                *player_entity,
+                &mut log,
+            );
+
+            let natural_roll = rng.roll_dice(1, 20);
+            let armour_class = BASE_ARMOUR_CLASS + target_stats.defense + defensive_bonus;
+            let critical = natural_roll == CRITICAL_ROLL;
+            let hit = critical
+                || (natural_roll != FUMBLE_ROLL
+                    && natural_roll + stats.power + offensive_bonus >= armour_class);
+
+            if !hit {
+                if natural_roll == FUMBLE_ROLL {
+                    log.entries.push(format!(
+                        "{} fumbles the attack on {}.",
+                        &name.name, target_name
+                    ));
+                } else {
+                    log.entries
+                        .push(format!("{} misses {}.", &name.name, target_name));
+                }
+
+                continue;
+            }
+
+            let dice_rolled = if critical {
+                damage_dice.n_dice * 2
+            } else {
+                damage_dice.n_dice
+            };
+            let damage = i32::max(
+                1,
+                rng.roll_dice(dice_rolled, damage_dice.die_type)
+                    + damage_dice.bonus
+                    + offensive_bonus,
+            );
+
             if let Some(Position { x, y }) = positions.get(wants_melee.target) {
                 particle_builder.request(
                     *x,
                     *y,
-                    rltk::RGB::named(rltk::ORANGE),
+                    rltk::RGB::named(if critical { rltk::RED } else { rltk::ORANGE }),
                     rltk::RGB::named(rltk::BLACK),
                     rltk::to_cp437('‼'),
                     200.0,
                 );
             }
 
-            let damage = i32::max(
-                0,
-                (stats.power + offensive_bonus) - (target_stats.defense + defensive_bonus),
-            );
-
-            if damage == 0 {
+            if critical {
                 log.entries.push(format!(
-                    "{} is unable to hurt {}",
-                    &name.name, &target_name.name
+                    "{} critically hits {}, for {} hp!",
+                    &name.name, target_name, damage
                 ));
             } else {
                 log.entries.push(format!(
                     "{} hits {}, for {} hp.",
-                    &name.name, &target_name.name, damage
+                    &name.name, target_name, damage
                 ));
-                SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage);
             }
+
+            SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage, damage_type);
         }
 
         wants_melee.clear();
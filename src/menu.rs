@@ -2,7 +2,7 @@ use rltk::{Rltk, VirtualKeyCode, RGB};
 
 use crate::gui::{MainMenuResult, MainMenuSelection};
 use crate::rex_assets::RexAssets;
-use crate::{saveload_system, RunState, State};
+use crate::{RunState, State};
 
 pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
     let assets = gs.ecs.fetch::<RexAssets>();
@@ -37,7 +37,7 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
     );
 
     let run_state = *gs.ecs.fetch::<RunState>();
-    let save_exists = saveload_system::does_save_exist();
+    let save_exists = gs.save_slots.has_saves();
     let mut y = 24;
 
     match run_state {
@@ -61,6 +61,23 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
             }
             y += 1;
 
+            if selection == MainMenuSelection::NewGameWithSeed {
+                ctx.print_color_centered(
+                    y,
+                    RGB::named(rltk::MAGENTA),
+                    RGB::named(rltk::BLACK),
+                    "New Game With Seed",
+                );
+            } else {
+                ctx.print_color_centered(
+                    y,
+                    RGB::named(rltk::WHITE),
+                    RGB::named(rltk::BLACK),
+                    "New Game With Seed",
+                );
+            }
+            y += 1;
+
             if save_exists {
                 if selection == MainMenuSelection::LoadGame {
                     ctx.print_color_centered(
@@ -96,6 +113,17 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
                 );
             }
 
+            if let Some(message) = &gs.main_menu_message {
+                for (i, line) in wrap(message, 60).iter().take(4).enumerate() {
+                    ctx.print_color_centered(
+                        31 + i as i32,
+                        RGB::named(rltk::RED),
+                        RGB::named(rltk::BLACK),
+                        line,
+                    );
+                }
+            }
+
             match ctx.key {
                 None => MainMenuResult::NoSelection {
                     selected: selection,
@@ -107,12 +135,13 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
                     VirtualKeyCode::Up => {
                         let mut new_selection = match selection {
                             MainMenuSelection::NewGame => MainMenuSelection::Quit,
-                            MainMenuSelection::LoadGame => MainMenuSelection::NewGame,
+                            MainMenuSelection::NewGameWithSeed => MainMenuSelection::NewGame,
+                            MainMenuSelection::LoadGame => MainMenuSelection::NewGameWithSeed,
                             MainMenuSelection::Quit => MainMenuSelection::LoadGame,
                         };
 
                         if new_selection == MainMenuSelection::LoadGame && !save_exists {
-                            new_selection = MainMenuSelection::NewGame;
+                            new_selection = MainMenuSelection::NewGameWithSeed;
                         }
 
                         MainMenuResult::NoSelection {
@@ -121,7 +150,8 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
                     }
                     VirtualKeyCode::Down => {
                         let mut new_selection = match selection {
-                            MainMenuSelection::NewGame => MainMenuSelection::LoadGame,
+                            MainMenuSelection::NewGame => MainMenuSelection::NewGameWithSeed,
+                            MainMenuSelection::NewGameWithSeed => MainMenuSelection::LoadGame,
                             MainMenuSelection::LoadGame => MainMenuSelection::Quit,
                             MainMenuSelection::Quit => MainMenuSelection::NewGame,
                         };
@@ -148,3 +178,20 @@ pub fn main_menu(gs: &mut State, ctx: &mut Rltk) -> MainMenuResult {
         },
     }
 }
+
+/// Splits a message into lines of at most `width` characters, at spaces where possible
+fn wrap(message: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for word in message.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.chars().take(width).collect()),
+        }
+    }
+
+    lines
+}
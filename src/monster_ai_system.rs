@@ -1,115 +1,241 @@
 use rltk::Point;
 use specs::prelude::*;
+use std::collections::HashMap;
 
 use crate::components::*;
+use crate::flow_map::FlowMaps;
 use crate::map::Map;
 use crate::particle_system::ParticleBuilder;
+use crate::raws::{self, Reaction};
+use crate::rng::AiRng;
 use crate::RunState;
 
+/// Monsters with this share of their hit points or less run from their enemies
+const FLEE_HP_PERCENT: i32 = 25;
+/// How many turns a monster looks around where it lost sight of its enemy
+const SEARCH_TURNS: i32 = 10;
+/// The longest an idle monster stands still before it wanders off
+const MAX_IDLE_TURNS: i32 = 8;
+/// How far a monster wanders from where it stands
+const WANDER_RANGE: i32 = 8;
+
 pub struct MonsterAI {}
 
+/// Someone on the level who takes a side, as they stood when the monsters started to act
+struct Actor {
+    entity: Entity,
+    position: Point,
+    faction: String,
+}
+
+/// What a monster wants with the ones it sees, the flow maps are shared by everyone who wants the
+/// same from the same targets
+#[derive(PartialEq, Eq, Hash, Copy, Clone)]
+enum Intent {
+    /// Get to the ones it attacks
+    Hunt,
+    /// Get away from the ones it attacks or fears
+    Escape,
+}
+
+/// The monster's faction and whoever provoked it, which together decide how it reacts to others
+type Allegiance = (String, Option<Entity>);
+
 impl<'a> System<'a> for MonsterAI {
     type SystemData = (
         WriteExpect<'a, Map>,
-        ReadExpect<'a, Point>,
-        ReadExpect<'a, Entity>,
         ReadExpect<'a, RunState>,
+        WriteExpect<'a, AiRng>,
         Entities<'a>,
         WriteStorage<'a, Viewshed>,
         ReadStorage<'a, Monster>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, WantsToMelee>,
-        WriteStorage<'a, Confusion>,
+        ReadStorage<'a, StatusEffects>,
         WriteExpect<'a, ParticleBuilder>,
         WriteStorage<'a, EntityMoved>,
+        WriteStorage<'a, MyTurn>,
+        WriteStorage<'a, Ai>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Faction>,
+        ReadStorage<'a, Provoked>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
             mut map,
-            player_pos,
-            player_entity,
             run_state,
+            mut rng,
             entities,
             mut viewshed,
             monster,
             mut position,
             mut wants_to_melee,
-            mut confused,
+            statuses,
             mut particle_builder,
             mut entity_moved,
+            mut my_turn,
+            mut ai,
+            combat_stats,
+            factions,
+            provoked,
         ) = data;
 
-        if *run_state != RunState::MonsterTurn {
+        if *run_state != RunState::MonsterTurn || my_turn.is_empty() {
             return;
         }
 
-        for (entity, mut viewshed, _monster, mut pos) in
-            (&entities, &mut viewshed, &monster, &mut position).join()
+        let raws = raws::raws();
+        let mut actors: Vec<Actor> = (&entities, &position, &factions, &combat_stats)
+            .join()
+            .filter(|(_entity, _pos, _faction, stats)| stats.hp > 0)
+            .map(|(entity, pos, faction, _stats)| Actor {
+                entity,
+                position: Point::new(pos.x, pos.y),
+                faction: faction.name.clone(),
+            })
+            .collect();
+        let movers: Vec<Point> = actors.iter().map(|actor| actor.position).collect();
+        let mut flow_maps: HashMap<(Allegiance, Intent), FlowMaps> = HashMap::new();
+
+        for (entity, viewshed, _monster, pos, _my_turn, ai) in (
+            &entities,
+            &mut viewshed,
+            &monster,
+            &mut position,
+            &my_turn,
+            &mut ai,
+        )
+            .join()
         {
-            let mut can_act = true;
-            let is_confused = confused.get_mut(entity);
+            if let Some(statuses) = statuses.get(entity) {
+                if statuses.has(StatusKind::Paralysis) {
+                    continue;
+                }
 
-            if let Some(i_am_confused) = is_confused {
-                i_am_confused.turns -= 1;
+                if statuses.has(StatusKind::Confusion) {
+                    particle_builder.request(
+                        pos.x,
+                        pos.y,
+                        rltk::RGB::named(rltk::MAGENTA),
+                        rltk::RGB::named(rltk::BLACK),
+                        rltk::to_cp437('?'),
+                        200.0,
+                    );
 
-                if i_am_confused.turns < 1 {
-                    confused.remove(entity);
+                    continue;
                 }
+            }
 
-                can_act = false;
+            let here = Point::new(pos.x, pos.y);
+            let allegiance: Allegiance = (
+                factions
+                    .get(entity)
+                    .map(|faction| faction.name.clone())
+                    .unwrap_or_default(),
+                provoked.get(entity).map(|provoked| provoked.by),
+            );
+            let reaction = |actor: &Actor| {
+                if Some(actor.entity) == allegiance.1 {
+                    Reaction::Attack
+                } else {
+                    raws.reaction(&allegiance.0, &actor.faction)
+                }
+            };
+            let distance = |point: Point| rltk::DistanceAlg::Pythagoras.distance2d(here, point);
+            let nearest_seen = |wanted: &[Reaction]| {
+                actors
+                    .iter()
+                    .filter(|actor| actor.entity != entity)
+                    .filter(|actor| wanted.contains(&reaction(actor)))
+                    .filter(|actor| viewshed.visible_tiles.contains(&actor.position))
+                    .min_by(|a, b| distance(a.position).total_cmp(&distance(b.position)))
+                    .map(|actor| (actor.entity, actor.position))
+            };
+            let enemy = nearest_seen(&[Reaction::Attack]);
+            let threat = nearest_seen(&[Reaction::Attack, Reaction::Flee]);
+            let feared = nearest_seen(&[Reaction::Flee]).is_some();
+            let badly_hurt = combat_stats
+                .get(entity)
+                .map(|stats| stats.hp * 100 <= stats.max_hp * FLEE_HP_PERCENT)
+                .unwrap_or(false);
 
-                particle_builder.request(
-                    pos.x,
-                    pos.y,
-                    rltk::RGB::named(rltk::MAGENTA),
-                    rltk::RGB::named(rltk::BLACK),
-                    rltk::to_cp437('?'),
-                    200.0,
-                );
-            }
+            ai.state = next_state(
+                ai.state,
+                enemy.map(|(_entity, position)| position),
+                feared || (enemy.is_some() && badly_hurt),
+                &mut rng,
+            );
 
-            if !can_act {
-                continue;
-            }
+            let build_flow_maps = |intent: Intent| {
+                let wanted: &[Reaction] = match intent {
+                    Intent::Hunt => &[Reaction::Attack],
+                    Intent::Escape => &[Reaction::Attack, Reaction::Flee],
+                };
+                let targets: Vec<Point> = actors
+                    .iter()
+                    .filter(|actor| actor.entity != entity)
+                    .filter(|actor| wanted.contains(&reaction(actor)))
+                    .map(|actor| actor.position)
+                    .collect();
 
-            if !viewshed.visible_tiles.contains(&*player_pos) {
-                continue;
-            }
+                FlowMaps::new(&map, &targets, &movers)
+            };
+            let adjacent = |target: Option<(Entity, Point)>| {
+                target.filter(|(_entity, position)| distance(*position) < 1.5)
+            };
+            let step = match ai.state {
+                AiState::Idle { .. } => None,
+                AiState::Chase { .. } => match adjacent(enemy) {
+                    Some((target, _)) => {
+                        attack(&mut wants_to_melee, entity, target);
+                        None
+                    }
+                    None => flow_maps
+                        .entry((allegiance.clone(), Intent::Hunt))
+                        .or_insert_with(|| build_flow_maps(Intent::Hunt))
+                        .towards
+                        .next_step(&map, here),
+                },
+                AiState::Flee => {
+                    let away = flow_maps
+                        .entry((allegiance.clone(), Intent::Escape))
+                        .or_insert_with(|| build_flow_maps(Intent::Escape))
+                        .flee_step(&map, here);
 
-            let distance =
-                rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *player_pos);
+                    // A cornered monster fights back
+                    if let (None, Some((target, _))) = (away, adjacent(threat)) {
+                        attack(&mut wants_to_melee, entity, target);
+                    }
 
-            if distance < 1.5 {
-                wants_to_melee
-                    .insert(
-                        entity,
-                        WantsToMelee {
-                            target: *player_entity,
-                        },
-                    )
-                    .expect("Unable to insert attack");
+                    away
+                }
+                AiState::Search { last_seen, .. } if here == last_seen => {
+                    random_step(&map, &mut rng, here)
+                }
+                AiState::Search { last_seen, .. } => step_towards(&map, here, last_seen),
+                AiState::Wander { destination } => step_towards(&map, here, destination),
+            };
 
-                continue;
+            match ai.state {
+                AiState::Idle { turns } if turns < 1 => {
+                    ai.state = match wander_destination(&map, &mut rng, here) {
+                        Some(destination) => AiState::Wander { destination },
+                        None => idle(&mut rng),
+                    };
+                }
+                // Arrived, or there is no way there
+                AiState::Wander { .. } if step.is_none() => ai.state = idle(&mut rng),
+                _ => {}
             }
 
-            // Path to the player
-            let path = rltk::a_star_search(
-                map.xy_idx(pos.x, pos.y) as i32,
-                map.xy_idx(player_pos.x, player_pos.y) as i32,
-                &*map,
-            );
-
-            // TODO(DP): monsters are stuck near corridor exits (do not move diagonally)
-            if path.success && path.steps.len() > 1 {
+            if let Some(next_step) = step {
                 let mut idx = map.xy_idx(pos.x, pos.y);
 
                 map.blocked[idx] = false;
 
-                let next_step = path.steps[1] as i32;
-
-                pos.x = next_step % map.width;
-                pos.y = next_step / map.width;
+                pos.x = next_step.x;
+                pos.y = next_step.y;
 
                 entity_moved
                     .insert(entity, EntityMoved {})
@@ -120,7 +246,116 @@ impl<'a> System<'a> for MonsterAI {
                 map.blocked[idx] = true;
 
                 viewshed.dirty = true;
+
+                if let Some(actor) = actors.iter_mut().find(|actor| actor.entity == entity) {
+                    actor.position = next_step;
+                }
             }
         }
+
+        my_turn.clear();
+    }
+}
+
+/// Where a monster's mind goes from `state`, before it acts on it. `enemy` is where the nearest one
+/// it attacks stands, if it sees one
+fn next_state(state: AiState, enemy: Option<Point>, fleeing: bool, rng: &mut AiRng) -> AiState {
+    if fleeing {
+        return AiState::Flee;
+    }
+
+    if let Some(last_seen) = enemy {
+        return AiState::Chase { last_seen };
+    }
+
+    match state {
+        AiState::Idle { turns } => AiState::Idle { turns: turns - 1 },
+        AiState::Wander { .. } => state,
+        AiState::Chase { last_seen } => AiState::Search {
+            last_seen,
+            turns: SEARCH_TURNS,
+        },
+        AiState::Search { turns, .. } if turns <= 1 => idle(rng),
+        AiState::Search { last_seen, turns } => AiState::Search {
+            last_seen,
+            turns: turns - 1,
+        },
+        // Out of sight is out of mind, it can rest until it is found again
+        AiState::Flee => idle(rng),
+    }
+}
+
+fn idle(rng: &mut AiRng) -> AiState {
+    AiState::Idle {
+        turns: rng.range(1, MAX_IDLE_TURNS + 1),
+    }
+}
+
+fn attack(wants_to_melee: &mut WriteStorage<WantsToMelee>, entity: Entity, target: Entity) {
+    wants_to_melee
+        .insert(entity, WantsToMelee { target })
+        .expect("Unable to insert attack");
+}
+
+/// The first step of the path to `target`, if there is one and nothing stands on it
+fn step_towards(map: &Map, here: Point, target: Point) -> Option<Point> {
+    let path = rltk::a_star_search(
+        map.xy_idx(here.x, here.y) as i32,
+        map.xy_idx(target.x, target.y) as i32,
+        map,
+    );
+
+    if !path.success || path.steps.len() < 2 {
+        return None;
+    }
+
+    let next_step = path.steps[1];
+
+    if map.blocked[next_step] {
+        return None;
+    }
+
+    Some(Point::new(
+        next_step as i32 % map.width,
+        next_step as i32 / map.width,
+    ))
+}
+
+fn random_step(map: &Map, rng: &mut AiRng, here: Point) -> Option<Point> {
+    let neighbours: Vec<Point> = free_neighbours(map, here).collect();
+
+    if neighbours.is_empty() {
+        return None;
     }
+
+    Some(neighbours[rng.range(0, neighbours.len())])
+}
+
+fn free_neighbours(map: &Map, here: Point) -> impl Iterator<Item = Point> + '_ {
+    (-1..=1)
+        .flat_map(move |dy| (-1..=1).map(move |dx| Point::new(here.x + dx, here.y + dy)))
+        .filter(move |point| *point != here)
+        .filter(move |point| point.x > 0 && point.x < map.width - 1)
+        .filter(move |point| point.y > 0 && point.y < map.height - 1)
+        .filter(move |point| !map.blocked[map.xy_idx(point.x, point.y)])
+}
+
+/// A free tile within `WANDER_RANGE`, after a few tries
+fn wander_destination(map: &Map, rng: &mut AiRng, here: Point) -> Option<Point> {
+    (0..10).find_map(|_| {
+        let destination = Point::new(
+            here.x + rng.range(-WANDER_RANGE, WANDER_RANGE + 1),
+            here.y + rng.range(-WANDER_RANGE, WANDER_RANGE + 1),
+        );
+        let inside = destination.x > 0
+            && destination.x < map.width - 1
+            && destination.y > 0
+            && destination.y < map.height - 1;
+
+        if inside && destination != here && !map.blocked[map.xy_idx(destination.x, destination.y)] {
+            Some(destination)
+        } else {
+            None
+        }
+    })
 }
@@ -1,17 +1,17 @@
-use rltk::{Rltk, RGB};
+use rltk::RGB;
 use specs::prelude::*;
 
 use crate::components::*;
 use crate::render_order::RenderOrder;
 
-pub fn cull_dead_particles(ecs: &mut World, ctx: &Rltk) {
+pub fn cull_dead_particles(ecs: &mut World, frame_time_ms: f32) {
     let mut dead_particles: Vec<Entity> = Vec::new();
     {
         // Age out particles
         let mut particles = ecs.write_storage::<ParticleLifetime>();
         let entities = ecs.entities();
-        for (entity, mut particle) in (&entities, &mut particles).join() {
-            particle.lifetime_ms -= ctx.frame_time_ms;
+        for (entity, particle) in (&entities, &mut particles).join() {
+            particle.lifetime_ms -= frame_time_ms;
             if particle.lifetime_ms < 0.0 {
                 dead_particles.push(entity);
             }
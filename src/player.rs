@@ -4,6 +4,7 @@ use specs::prelude::*;
 use crate::components::*;
 use crate::game_log::GameLog;
 use crate::map::{Map, TileType};
+use crate::status_effect_system;
 use crate::{RunState, State};
 
 pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
@@ -81,52 +82,121 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     }
 }
 
-pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
-    use VirtualKeyCode::*;
+/// A single action taken by the player, independent of the device it came from
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum PlayerCommand {
+    Move { delta_x: i32, delta_y: i32 },
+    SkipTurn,
+    PickUp,
+    ShowInventory,
+    ShowDropItem,
+    ShowRemoveItem,
+    ShowCharacter,
+    SaveGame,
+    Descend,
+    Ascend,
+    // Menu answers
+    SelectItem { index: usize },
+    Target { x: i32, y: i32 },
+    Cancel,
+}
 
-    let optional_player_movement = match ctx.key {
-        Some(key) => match key {
-            Left | H | A => Some((-1, 0)),
-            Right | L | D => Some((1, 0)),
-            Up | K | W => Some((0, -1)),
-            Down | J | S => Some((0, 1)),
-            // Diagonals
-            E | Y => Some((1, -1)),
-            Q | U => Some((-1, -1)),
-            X | N => Some((1, 1)),
-            Z | B => Some((-1, 1)),
-            //
-            Space => return skip_turn(&mut gs.ecs),
-            //
-            G => {
-                get_item(&mut gs.ecs);
-                return RunState::PlayerTurn;
-            }
-            I => return RunState::ShowInventory,
-            F => return RunState::ShowDropItem,
-            R => return RunState::ShowRemoveItem,
-            //
-            Escape => return RunState::SaveGame,
-            // Level changes
-            Period => {
-                if try_next_level(&mut gs.ecs) {
-                    return RunState::NextLevel;
-                }
+pub fn player_input(ctx: &Rltk) -> Option<PlayerCommand> {
+    use PlayerCommand::*;
+    use VirtualKeyCode::*;
 
-                None
-            }
-            //
-            _ => None,
+    let command = match ctx.key? {
+        Left | H | A => Move {
+            delta_x: -1,
+            delta_y: 0,
+        },
+        Right | L | D => Move {
+            delta_x: 1,
+            delta_y: 0,
+        },
+        Up | K | W => Move {
+            delta_x: 0,
+            delta_y: -1,
+        },
+        Down | J | S => Move {
+            delta_x: 0,
+            delta_y: 1,
+        },
+        // Diagonals
+        E | Y => Move {
+            delta_x: 1,
+            delta_y: -1,
         },
-        None => None,
+        Q | U => Move {
+            delta_x: -1,
+            delta_y: -1,
+        },
+        X | N => Move {
+            delta_x: 1,
+            delta_y: 1,
+        },
+        Z | B => Move {
+            delta_x: -1,
+            delta_y: 1,
+        },
+        //
+        Space => SkipTurn,
+        //
+        G => PickUp,
+        I => ShowInventory,
+        F => ShowDropItem,
+        R => ShowRemoveItem,
+        C => ShowCharacter,
+        //
+        Escape => SaveGame,
+        // Level changes
+        Period => Descend,
+        Comma => Ascend,
+        //
+        _ => return None,
     };
 
-    if let Some((delta_x, delta_y)) = optional_player_movement {
-        try_move_player(delta_x, delta_y, &mut gs.ecs);
+    Some(command)
+}
+
+/// Performs a command given while the game is awaiting input
+pub fn perform_command(gs: &mut State, command: PlayerCommand) -> RunState {
+    match command {
+        PlayerCommand::Move { delta_x, delta_y } => {
+            let (delta_x, delta_y) = status_effect_system::stumble(&gs.ecs, delta_x, delta_y);
 
-        RunState::PlayerTurn
-    } else {
-        RunState::AwaitingInput
+            try_move_player(delta_x, delta_y, &mut gs.ecs);
+
+            RunState::PlayerTurn
+        }
+        PlayerCommand::SkipTurn => skip_turn(&mut gs.ecs),
+        PlayerCommand::PickUp => {
+            get_item(&mut gs.ecs);
+
+            RunState::PlayerTurn
+        }
+        PlayerCommand::ShowInventory => RunState::ShowInventory,
+        PlayerCommand::ShowDropItem => RunState::ShowDropItem,
+        PlayerCommand::ShowRemoveItem => RunState::ShowRemoveItem,
+        PlayerCommand::ShowCharacter => RunState::ShowCharacter,
+        PlayerCommand::SaveGame => RunState::SaveGame,
+        PlayerCommand::Descend => {
+            if try_next_level(&mut gs.ecs) {
+                RunState::NextLevel
+            } else {
+                RunState::AwaitingInput
+            }
+        }
+        PlayerCommand::Ascend => {
+            if try_previous_level(&mut gs.ecs) {
+                RunState::PreviousLevel
+            } else {
+                RunState::AwaitingInput
+            }
+        }
+        PlayerCommand::SelectItem { .. } | PlayerCommand::Target { .. } | PlayerCommand::Cancel => {
+            RunState::AwaitingInput
+        }
     }
 }
 
@@ -182,6 +252,23 @@ pub fn try_next_level(ecs: &mut World) -> bool {
     true
 }
 
+pub fn try_previous_level(ecs: &mut World) -> bool {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+
+    if map.tiles[player_idx] != TileType::UpStairs {
+        let mut game_log = ecs.fetch_mut::<GameLog>();
+
+        game_log
+            .entries
+            .push("There is no way up from here.".to_string());
+        return false;
+    }
+
+    true
+}
+
 fn skip_turn(ecs: &mut World) -> RunState {
     let viewshed_components = ecs.read_storage::<Viewshed>();
     let player_entity = ecs.fetch::<Entity>();
@@ -191,11 +278,12 @@ fn skip_turn(ecs: &mut World) -> RunState {
     let hunger_clocks = ecs.read_storage::<HungerClock>();
     let mut can_heal = true;
 
-    if let Some(HungerClock { state, .. }) = hunger_clocks.get(*player_entity) {
-        match state {
-            HungerState::Hungry | HungerState::Starving => can_heal = false,
-            _ => {}
-        }
+    if let Some(HungerClock {
+        state: HungerState::Hungry | HungerState::Starving,
+        ..
+    }) = hunger_clocks.get(*player_entity)
+    {
+        can_heal = false;
     }
 
     if !can_heal {
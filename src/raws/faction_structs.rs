@@ -0,0 +1,18 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// How the members of a faction react to the members of others, by faction name. `Default` is
+/// used for factions that are not listed.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct FactionInfo {
+    pub name: String,
+    pub responses: HashMap<String, Reaction>,
+}
+
+#[derive(Deserialize, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Reaction {
+    Attack,
+    Ignore,
+    Flee,
+}
@@ -0,0 +1,61 @@
+use serde::Deserialize;
+
+use crate::components::DamageType;
+use crate::raws::Renderable;
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Item {
+    pub name: String,
+    pub renderable: Option<Renderable>,
+    pub consumable: Option<Consumable>,
+    pub weapon: Option<Weapon>,
+    pub shield: Option<Shield>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Consumable {
+    pub effects: ConsumableEffects,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields, default)]
+pub struct ConsumableEffects {
+    pub provides_healing: Option<i32>,
+    pub ranged: Option<i32>,
+    pub damage: Option<i32>,
+    /// `Physical` when left out
+    pub damage_type: DamageType,
+    pub area_of_effect: Option<i32>,
+    /// Status effects, by how many turns they last
+    pub confusion: Option<i32>,
+    pub poison: Option<i32>,
+    pub regeneration: Option<i32>,
+    pub haste: Option<i32>,
+    pub slow: Option<i32>,
+    pub paralysis: Option<i32>,
+    pub magic_mapping: bool,
+    pub food: bool,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Weapon {
+    /// Dice like `1d8` or `2d4+1`
+    pub damage: String,
+    /// Added to attack and damage rolls
+    #[serde(default)]
+    pub power_bonus: i32,
+    /// `Physical` when left out
+    #[serde(default)]
+    pub damage_type: DamageType,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Shield {
+    pub defense_bonus: i32,
+    #[serde(default)]
+    pub speed_penalty: i32,
+}
@@ -0,0 +1,42 @@
+use serde::Deserialize;
+
+use crate::components::DamageType;
+use crate::raws::Renderable;
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Mob {
+    pub name: String,
+    pub renderable: Option<Renderable>,
+    #[serde(default)]
+    pub blocks_tile: bool,
+    pub stats: MobStats,
+    pub vision_range: i32,
+    /// Energy gained every tick, `NORMAL_SPEED` when left out
+    pub speed: Option<i32>,
+    /// The name of an entry of the faction table
+    pub faction: String,
+    /// Damage types that do half damage
+    #[serde(default)]
+    pub resistances: Vec<DamageType>,
+    /// Damage types that do no damage
+    #[serde(default)]
+    pub immunities: Vec<DamageType>,
+    /// Damage types that do double damage
+    #[serde(default)]
+    pub vulnerabilities: Vec<DamageType>,
+    /// The experience whoever kills it earns
+    #[serde(default)]
+    pub xp: i32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct MobStats {
+    pub max_hp: i32,
+    pub hp: i32,
+    pub defense: i32,
+    pub power: i32,
+    /// The dice of its natural attack, like `1d6`
+    pub damage: String,
+}
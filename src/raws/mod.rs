@@ -0,0 +1,87 @@
+mod faction_structs;
+mod item_structs;
+mod mob_structs;
+mod prop_structs;
+mod rawmaster;
+mod spawn_table_structs;
+
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::map_builders;
+use crate::raws::faction_structs::FactionInfo;
+use crate::raws::item_structs::Item;
+use crate::raws::mob_structs::Mob;
+use crate::raws::prop_structs::Prop;
+use crate::raws::spawn_table_structs::SpawnTableEntry;
+use crate::render_order::RenderOrder;
+
+pub use crate::raws::faction_structs::Reaction;
+pub use crate::raws::rawmaster::RawMaster;
+
+/// Read at startup when it exists, so the entities can be tuned without rebuilding the game
+pub const RAW_FILE: &str = "./raws/spawns.json";
+
+/// Used when there is no `RAW_FILE` to read, e.g. on the web
+const EMBEDDED_RAWS: &str = include_str!("../../raws/spawns.json");
+
+/// The faction the player belongs to, it has to be in the faction table
+pub const PLAYER_FACTION: &str = "Player";
+
+static RAWS: OnceLock<RawMaster> = OnceLock::new();
+
+/// Everything that can be spawned, as read from `raws/spawns.json`
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Raws {
+    pub items: Vec<Item>,
+    pub mobs: Vec<Mob>,
+    pub props: Vec<Prop>,
+    pub spawn_table: Vec<SpawnTableEntry>,
+    pub faction_table: Vec<FactionInfo>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Renderable {
+    pub glyph: String,
+    pub fg: String,
+    pub bg: String,
+    pub order: RenderOrder,
+}
+
+/// Loads and checks the raws on first use, later calls return the same ones
+pub fn load_raws() -> Result<&'static RawMaster, String> {
+    if let Some(raws) = RAWS.get() {
+        return Ok(raws);
+    }
+
+    let raws = if Path::new(RAW_FILE).exists() {
+        let raw_data = fs::read_to_string(RAW_FILE)
+            .map_err(|error| format!("Unable to read {}: {}", RAW_FILE, error))?;
+
+        RawMaster::load(&raw_data).map_err(|error| format!("{}: {}", RAW_FILE, error))?
+    } else {
+        RawMaster::load(EMBEDDED_RAWS)?
+    };
+
+    for name in map_builders::NAMED_SPAWNS {
+        if !raws.contains(name) {
+            return Err(format!(
+                "The map builders spawn '{}', which is not defined in the raws",
+                name
+            ));
+        }
+    }
+
+    Ok(RAWS.get_or_init(|| raws))
+}
+
+/// The loaded raws. `main` loads them up front, so that a broken file is reported before the
+/// game starts instead of here.
+pub fn raws() -> &'static RawMaster {
+    load_raws().unwrap_or_else(|error| panic!("{}", error))
+}
@@ -0,0 +1,34 @@
+use serde::Deserialize;
+
+use crate::components::DamageType;
+use crate::raws::Renderable;
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Prop {
+    pub name: String,
+    pub renderable: Option<Renderable>,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default)]
+    pub blocks_tile: bool,
+    #[serde(default)]
+    pub blocks_visibility: bool,
+    pub door_open: Option<bool>,
+    pub entry_trigger: Option<EntryTrigger>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct EntryTrigger {
+    pub effects: EntryTriggerEffects,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields, default)]
+pub struct EntryTriggerEffects {
+    pub damage: Option<i32>,
+    /// `Physical` when left out
+    pub damage_type: DamageType,
+    pub single_activation: bool,
+}
@@ -0,0 +1,463 @@
+use std::collections::HashMap;
+
+use rltk::RGB;
+use specs::prelude::*;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+
+use crate::components::*;
+use crate::initiative_system::NORMAL_SPEED;
+use crate::random_table::RandomTable;
+use crate::raws::{Raws, Reaction, Renderable as RawRenderable, PLAYER_FACTION};
+
+/// What a faction member does about anyone whose faction is not in its responses
+const DEFAULT_RESPONSE: &str = "Default";
+
+/// Where the definition of a named entity lives in `Raws`
+#[derive(Copy, Clone)]
+enum RawIndex {
+    Item(usize),
+    Mob(usize),
+    Prop(usize),
+}
+
+pub struct RawMaster {
+    raws: Raws,
+    index: HashMap<String, RawIndex>,
+    factions: HashMap<String, HashMap<String, Reaction>>,
+}
+
+impl RawMaster {
+    /// Parses the raws and checks that every name is unique and every spawn table entry and
+    /// faction exists
+    pub fn load(raw_data: &str) -> Result<RawMaster, String> {
+        let raws: Raws = serde_json::from_str(raw_data)
+            .map_err(|error| format!("Unable to parse the raws: {}", error))?;
+
+        let mut index = HashMap::new();
+        let names = raws
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (&item.name, RawIndex::Item(i)))
+            .chain(
+                raws.mobs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, mob)| (&mob.name, RawIndex::Mob(i))),
+            )
+            .chain(
+                raws.props
+                    .iter()
+                    .enumerate()
+                    .map(|(i, prop)| (&prop.name, RawIndex::Prop(i))),
+            );
+
+        for (name, raw_index) in names {
+            if index.insert(name.clone(), raw_index).is_some() {
+                return Err(format!("'{}' is defined more than once in the raws", name));
+            }
+        }
+
+        let renderables = raws
+            .items
+            .iter()
+            .map(|item| (&item.name, &item.renderable))
+            .chain(raws.mobs.iter().map(|mob| (&mob.name, &mob.renderable)))
+            .chain(raws.props.iter().map(|prop| (&prop.name, &prop.renderable)));
+
+        for (name, renderable) in renderables {
+            if let Some(renderable) = renderable {
+                check_renderable(name, renderable)?;
+            }
+        }
+
+        let dice = raws
+            .items
+            .iter()
+            .filter_map(|item| Some((&item.name, &item.weapon.as_ref()?.damage)))
+            .chain(raws.mobs.iter().map(|mob| (&mob.name, &mob.stats.damage)));
+
+        for (name, dice) in dice {
+            check_dice(name, dice)?;
+        }
+
+        for entry in raws.spawn_table.iter() {
+            if !index.contains_key(&entry.name) {
+                return Err(format!(
+                    "The spawn table refers to '{}', which is not defined in the raws",
+                    entry.name
+                ));
+            }
+        }
+
+        let factions = faction_index(&raws)?;
+
+        Ok(RawMaster {
+            raws,
+            index,
+            factions,
+        })
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    /// The glyph the named entity is drawn with, if it has one
+    pub fn glyph(&self, name: &str) -> Option<char> {
+        let renderable = match self.index.get(name)? {
+            RawIndex::Item(i) => &self.raws.items[*i].renderable,
+            RawIndex::Mob(i) => &self.raws.mobs[*i].renderable,
+            RawIndex::Prop(i) => &self.raws.props[*i].renderable,
+        };
+
+        renderable.as_ref()?.glyph.chars().next()
+    }
+
+    /// How a member of `my_faction` reacts to a member of `their_faction`. Factions that are not
+    /// in the table ignore everyone.
+    pub fn reaction(&self, my_faction: &str, their_faction: &str) -> Reaction {
+        let Some(responses) = self.factions.get(my_faction) else {
+            return Reaction::Ignore;
+        };
+
+        responses
+            .get(their_faction)
+            .or_else(|| responses.get(DEFAULT_RESPONSE))
+            .copied()
+            .unwrap_or(Reaction::Ignore)
+    }
+
+    pub fn spawn_table_for_depth(&self, depth: i32) -> RandomTable {
+        self.raws
+            .spawn_table
+            .iter()
+            .fold(RandomTable::new(), |table, entry| {
+                let weight = if entry.add_map_depth_to_weight {
+                    entry.weight + depth
+                } else {
+                    entry.weight
+                };
+
+                table.add(&entry.name, weight)
+            })
+    }
+
+    /// Builds the named entity at (x, y), returns `None` if the raws have no such entity
+    pub fn spawn_named_entity(
+        &self,
+        ecs: &mut World,
+        name: &str,
+        x: i32,
+        y: i32,
+    ) -> Option<Entity> {
+        let entity = match self.index.get(name)? {
+            RawIndex::Item(i) => self.spawn_item(ecs, *i, x, y),
+            RawIndex::Mob(i) => self.spawn_mob(ecs, *i, x, y),
+            RawIndex::Prop(i) => self.spawn_prop(ecs, *i, x, y),
+        };
+
+        Some(entity)
+    }
+
+    fn spawn_item(&self, ecs: &mut World, i: usize, x: i32, y: i32) -> Entity {
+        let item_template = &self.raws.items[i];
+        let mut eb = ecs
+            .create_entity()
+            .marked::<SimpleMarker<SerializeMe>>()
+            .with(Position { x, y })
+            .with(Name {
+                name: item_template.name.clone(),
+            })
+            .with(Item {});
+
+        if let Some(renderable) = &item_template.renderable {
+            eb = eb.with(get_renderable_component(renderable));
+        }
+
+        if let Some(consumable) = &item_template.consumable {
+            let effects = &consumable.effects;
+
+            eb = eb.with(Consumable {});
+
+            if let Some(heal_amount) = effects.provides_healing {
+                eb = eb.with(ProvidesHealing { heal_amount });
+            }
+            if let Some(range) = effects.ranged {
+                eb = eb.with(Ranged { range });
+            }
+            if let Some(damage) = effects.damage {
+                eb = eb.with(InflictsDamage {
+                    damage,
+                    damage_type: effects.damage_type,
+                });
+            }
+            if let Some(radius) = effects.area_of_effect {
+                eb = eb.with(AreaOfEffect { radius });
+            }
+
+            let statuses: Vec<StatusEffect> = [
+                (StatusKind::Confusion, effects.confusion),
+                (StatusKind::Poison, effects.poison),
+                (StatusKind::Regeneration, effects.regeneration),
+                (StatusKind::Haste, effects.haste),
+                (StatusKind::Slow, effects.slow),
+                (StatusKind::Paralysis, effects.paralysis),
+            ]
+            .into_iter()
+            .filter_map(|(kind, turns)| {
+                Some(StatusEffect {
+                    kind,
+                    turns: turns?,
+                })
+            })
+            .collect();
+
+            if !statuses.is_empty() {
+                eb = eb.with(InflictsStatus { effects: statuses });
+            }
+            if effects.magic_mapping {
+                eb = eb.with(MagicMapper {});
+            }
+            if effects.food {
+                eb = eb.with(ProvidesFood {});
+            }
+        }
+
+        if let Some(weapon) = &item_template.weapon {
+            eb = eb
+                .with(Equippable {
+                    slot: EquipmentSlot::Melee,
+                })
+                .with(MeleeWeapon {
+                    damage: get_dice(&weapon.damage),
+                    damage_type: weapon.damage_type,
+                });
+
+            if weapon.power_bonus != 0 {
+                eb = eb.with(MeleePowerBonus {
+                    power: weapon.power_bonus,
+                });
+            }
+        }
+
+        if let Some(shield) = &item_template.shield {
+            eb = eb
+                .with(Equippable {
+                    slot: EquipmentSlot::Shield,
+                })
+                .with(DefenseBonus {
+                    defense: shield.defense_bonus,
+                });
+
+            if shield.speed_penalty != 0 {
+                eb = eb.with(SpeedPenalty {
+                    penalty: shield.speed_penalty,
+                });
+            }
+        }
+
+        eb.build()
+    }
+
+    fn spawn_mob(&self, ecs: &mut World, i: usize, x: i32, y: i32) -> Entity {
+        let mob_template = &self.raws.mobs[i];
+        let mut eb = ecs
+            .create_entity()
+            .marked::<SimpleMarker<SerializeMe>>()
+            .with(Position { x, y })
+            .with(Name {
+                name: mob_template.name.clone(),
+            })
+            .with(Monster {})
+            .with(CombatStats {
+                max_hp: mob_template.stats.max_hp,
+                hp: mob_template.stats.hp,
+                defense: mob_template.stats.defense,
+                power: mob_template.stats.power,
+            })
+            .with(Viewshed {
+                visible_tiles: Vec::new(),
+                range: mob_template.vision_range,
+                dirty: true,
+            })
+            .with(Initiative {
+                speed: mob_template.speed.unwrap_or(NORMAL_SPEED),
+                energy: 0,
+            })
+            .with(Ai {
+                state: AiState::Idle { turns: 0 },
+            })
+            .with(Faction {
+                name: mob_template.faction.clone(),
+            })
+            .with(MeleeWeapon {
+                damage: get_dice(&mob_template.stats.damage),
+                damage_type: DamageType::Physical,
+            });
+
+        if let Some(renderable) = &mob_template.renderable {
+            eb = eb.with(get_renderable_component(renderable));
+        }
+
+        if mob_template.blocks_tile {
+            eb = eb.with(BlocksTile {});
+        }
+        if !mob_template.resistances.is_empty() {
+            eb = eb.with(Resistances {
+                damage_types: mob_template.resistances.clone(),
+            });
+        }
+        if !mob_template.immunities.is_empty() {
+            eb = eb.with(Immunities {
+                damage_types: mob_template.immunities.clone(),
+            });
+        }
+        if !mob_template.vulnerabilities.is_empty() {
+            eb = eb.with(Vulnerabilities {
+                damage_types: mob_template.vulnerabilities.clone(),
+            });
+        }
+        if mob_template.xp > 0 {
+            eb = eb.with(XpValue {
+                xp: mob_template.xp,
+            });
+        }
+
+        eb.build()
+    }
+
+    fn spawn_prop(&self, ecs: &mut World, i: usize, x: i32, y: i32) -> Entity {
+        let prop_template = &self.raws.props[i];
+        let mut eb = ecs
+            .create_entity()
+            .marked::<SimpleMarker<SerializeMe>>()
+            .with(Position { x, y })
+            .with(Name {
+                name: prop_template.name.clone(),
+            });
+
+        if let Some(renderable) = &prop_template.renderable {
+            eb = eb.with(get_renderable_component(renderable));
+        }
+
+        if prop_template.hidden {
+            eb = eb.with(Hidden {});
+        }
+        if prop_template.blocks_tile {
+            eb = eb.with(BlocksTile {});
+        }
+        if prop_template.blocks_visibility {
+            eb = eb.with(BlocksVisibility {});
+        }
+        if let Some(open) = prop_template.door_open {
+            eb = eb.with(Door { open });
+        }
+
+        if let Some(entry_trigger) = &prop_template.entry_trigger {
+            let effects = &entry_trigger.effects;
+
+            eb = eb.with(EntryTrigger {});
+
+            if let Some(damage) = effects.damage {
+                eb = eb.with(InflictsDamage {
+                    damage,
+                    damage_type: effects.damage_type,
+                });
+            }
+            if effects.single_activation {
+                eb = eb.with(SingleActivation {});
+            }
+        }
+
+        eb.build()
+    }
+}
+
+/// The faction table by name, after checking that every faction it and the mobs mention exists
+fn faction_index(raws: &Raws) -> Result<HashMap<String, HashMap<String, Reaction>>, String> {
+    let mut factions = HashMap::new();
+
+    for faction in raws.faction_table.iter() {
+        if factions
+            .insert(faction.name.clone(), faction.responses.clone())
+            .is_some()
+        {
+            return Err(format!(
+                "The faction '{}' is defined more than once in the raws",
+                faction.name
+            ));
+        }
+    }
+
+    if !factions.contains_key(PLAYER_FACTION) {
+        return Err(format!(
+            "The faction table has no '{}' faction",
+            PLAYER_FACTION
+        ));
+    }
+
+    for faction in raws.faction_table.iter() {
+        for other in faction.responses.keys() {
+            if other != DEFAULT_RESPONSE && !factions.contains_key(other) {
+                return Err(format!(
+                    "The faction '{}' reacts to '{}', which is not in the faction table",
+                    faction.name, other
+                ));
+            }
+        }
+    }
+
+    for mob in raws.mobs.iter() {
+        if !factions.contains_key(&mob.faction) {
+            return Err(format!(
+                "'{}' belongs to the faction '{}', which is not in the faction table",
+                mob.name, mob.faction
+            ));
+        }
+    }
+
+    Ok(factions)
+}
+
+fn check_renderable(name: &str, renderable: &RawRenderable) -> Result<(), String> {
+    if renderable.glyph.chars().count() != 1 {
+        return Err(format!(
+            "The glyph of '{}' has to be a single character, got '{}'",
+            name, renderable.glyph
+        ));
+    }
+
+    for color in [&renderable.fg, &renderable.bg] {
+        if RGB::from_hex(color).is_err() {
+            return Err(format!("The color '{}' of '{}' is not valid", color, name));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_dice(name: &str, dice: &str) -> Result<(), String> {
+    match rltk::parse_dice_string(dice) {
+        Ok(parsed) if parsed.n_dice > 0 && parsed.die_type > 0 => Ok(()),
+        _ => Err(format!(
+            "The damage of '{}' has to be dice like 1d6 or 2d4+1, got '{}'",
+            name, dice
+        )),
+    }
+}
+
+// `load` has already checked the dice
+fn get_dice(dice: &str) -> rltk::DiceType {
+    rltk::parse_dice_string(dice).unwrap()
+}
+
+// `load` has already checked the glyphs and colors
+fn get_renderable_component(renderable: &RawRenderable) -> Renderable {
+    Renderable {
+        glyph: rltk::to_cp437(renderable.glyph.chars().next().unwrap()),
+        fg: RGB::from_hex(&renderable.fg).unwrap(),
+        bg: RGB::from_hex(&renderable.bg).unwrap(),
+        render_order: renderable.order,
+    }
+}
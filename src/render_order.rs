@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Ord, PartialOrd, Eq, PartialEq, Deserialize, Serialize, Copy, Clone)]
+#[derive(Ord, PartialOrd, Eq, PartialEq, Deserialize, Serialize, Copy, Clone, Debug)]
 pub enum RenderOrder {
     Particle,
     Player,
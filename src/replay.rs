@@ -0,0 +1,189 @@
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::headless::HeadlessGame;
+use crate::player::PlayerCommand;
+use crate::save_slots::SaveSlots;
+use crate::save_storage::MemoryStorage;
+
+/// What the game records itself to when no `--record <path>` is given
+pub const DEFAULT_REPLAY_FILE: &str = "last_game.replay";
+
+/// A replay file uses the headless script syntax, with `seed <number>` lines marking where a
+/// new game was started:
+///
+/// ```text
+/// seed 1234
+/// move 1 0
+/// inventory
+/// select a
+/// ```
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ReplayEntry {
+    NewGame { seed: u64 },
+    Command(PlayerCommand),
+}
+
+/// Writes every command the game accepts to a replay file, one line at a time, so that the file
+/// is complete up to the last command even if the game crashes right after it.
+pub struct Recorder {
+    file: File,
+    /// Set while the game plays on from something the replay has not seen, until the next new game
+    paused: bool,
+}
+
+impl Recorder {
+    pub fn create(path: &str, seed: u64) -> Result<Recorder, String> {
+        let unable = |error: std::io::Error| format!("Unable to create replay {}: {}", path, error);
+
+        if let Some(dir) = Path::new(path).parent() {
+            fs::create_dir_all(dir).map_err(unable)?;
+        }
+
+        let file = File::create(path).map_err(unable)?;
+        let mut recorder = Recorder {
+            file,
+            paused: false,
+        };
+
+        recorder.new_game(seed);
+
+        Ok(recorder)
+    }
+
+    pub fn record(&mut self, command: PlayerCommand) {
+        if !self.paused {
+            self.write_line(&command.to_string());
+        }
+    }
+
+    pub fn new_game(&mut self, seed: u64) {
+        self.paused = false;
+        self.write_line(&format!("seed {}", seed));
+    }
+
+    /// Stops recording commands until the next new game, leaving `reason` in the replay
+    pub fn pause(&mut self, reason: &str) {
+        self.note(reason);
+        self.paused = true;
+    }
+
+    /// Leaves a comment in the replay, the replay itself skips it
+    pub fn note(&mut self, text: &str) {
+        self.write_line(&format!("# {}", text));
+    }
+
+    fn write_line(&mut self, line: &str) {
+        // A replay that cannot be written should never stop the game itself
+        if let Err(error) = writeln!(self.file, "{}", line) {
+            rltk::console::log(format!("Unable to write to the replay: {}", error));
+        }
+    }
+}
+
+pub fn parse_replay(replay: &str) -> Result<Vec<ReplayEntry>, String> {
+    let mut entries = Vec::new();
+
+    for (i, line) in replay.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let entry = match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            ["seed", seed] => ReplayEntry::NewGame {
+                seed: seed
+                    .parse::<u64>()
+                    .map_err(|_| format!("Line {}: Expected a seed, got '{}'", i + 1, seed))?,
+            },
+            _ => ReplayEntry::Command(
+                line.parse::<PlayerCommand>()
+                    .map_err(|error| format!("Line {}: {}", i + 1, error))?,
+            ),
+        };
+
+        entries.push(entry);
+    }
+
+    match entries.first() {
+        Some(ReplayEntry::NewGame { .. }) => Ok(entries),
+        _ => Err("A replay has to start with a 'seed <number>' line".to_string()),
+    }
+}
+
+/// Feeds a replay back through the game, returns the game as it was after the last entry. What
+/// the replay saves is kept in memory only.
+pub fn run_replay(entries: &[ReplayEntry]) -> HeadlessGame {
+    run_replay_with_slots(entries, SaveSlots::new(Rc::new(MemoryStorage::new())))
+}
+
+/// Same as `run_replay`, with the saves of the replay going to `save_slots`
+pub fn run_replay_with_slots(entries: &[ReplayEntry], save_slots: SaveSlots) -> HeadlessGame {
+    let mut game: Option<HeadlessGame> = None;
+
+    for entry in entries {
+        match *entry {
+            ReplayEntry::NewGame { seed } => match game.as_mut() {
+                Some(game) => game.new_game(seed),
+                None => {
+                    let mut new_game = HeadlessGame::with_seed(seed);
+
+                    new_game.state.use_save_slots(save_slots.clone());
+                    game = Some(new_game);
+                }
+            },
+            ReplayEntry::Command(command) => {
+                if let Some(game) = game.as_mut() {
+                    game.apply(command);
+                }
+            }
+        }
+    }
+
+    game.expect("A replay starts with a new game")
+}
+
+/// Returns the replay path given with `--replay <file>`, if any
+pub fn replay_argument(args: &[String]) -> Option<String> {
+    let position = args.iter().position(|arg| arg == "--replay")?;
+
+    args.get(position + 1).cloned()
+}
+
+/// Where the game records itself when no `--record <path>` is given: next to the saves in the
+/// user's data directory, or the working directory on platforms without one
+pub fn default_replay_path() -> String {
+    match dirs::data_dir() {
+        Some(dir) => dir
+            .join("roguelike-tutorial")
+            .join(DEFAULT_REPLAY_FILE)
+            .to_string_lossy()
+            .into_owned(),
+        None => DEFAULT_REPLAY_FILE.to_string(),
+    }
+}
+
+/// Returns the path given with `--record <file>`, if any
+pub fn record_argument(args: &[String]) -> Option<String> {
+    let position = args.iter().position(|arg| arg == "--record")?;
+
+    args.get(position + 1).cloned()
+}
+
+pub fn run_replay_file(path: &str) -> Result<String, String> {
+    let replay = fs::read_to_string(path)
+        .map_err(|error| format!("Unable to read replay {}: {}", path, error))?;
+    let entries = parse_replay(&replay)?;
+
+    let game = run_replay(&entries);
+
+    Ok(format!(
+        "Replay entries: {}\n{}",
+        entries.len(),
+        game.summary()
+    ))
+}
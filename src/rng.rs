@@ -0,0 +1,139 @@
+use std::ops::{Deref, DerefMut};
+
+use rltk::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+/// The seed the current run was started with
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct GameSeed(pub u64);
+
+/// Every subsystem draws from its own stream, so rolls made by one never shift the others
+#[derive(Copy, Clone)]
+enum Stream {
+    MapGeneration = 1,
+    Spawning = 2,
+    Combat = 3,
+    Perception = 4,
+    Ai = 5,
+    Status = 6,
+}
+
+/// Declares a resource for every stream drawn from during play, each one dereferences to its
+/// generator, and `RngStreams` that saves where they all stand under the given field names
+macro_rules! play_streams {
+    ($( $(#[$doc:meta])* $field:ident: $name:ident = Stream::$stream:ident ),* $(,)?) => {
+        $(
+        $(#[$doc])*
+        pub struct $name(pub RandomNumberGenerator);
+
+        impl $name {
+            pub fn new(seed: u64) -> $name {
+                $name(stream(seed, Stream::$stream, 0))
+            }
+        }
+
+        impl Deref for $name {
+            type Target = RandomNumberGenerator;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl DerefMut for $name {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
+            }
+        }
+        )*
+
+        /// Where the streams drawn from during play stand, saved with the game so that a loaded
+        /// game rolls what the saved one would have. The map and spawn streams start over for
+        /// every level anyway.
+        #[derive(Clone, Serialize, Deserialize)]
+        pub struct RngStreams {
+            $( $field: RandomNumberGenerator, )*
+        }
+
+        impl RngStreams {
+            /// The streams of a run with `seed` that has not rolled anything yet
+            pub fn new(seed: u64) -> RngStreams {
+                RngStreams {
+                    $( $field: $name::new(seed).0, )*
+                }
+            }
+
+            pub fn capture(ecs: &World) -> RngStreams {
+                RngStreams {
+                    $( $field: ecs.fetch::<$name>().0.clone(), )*
+                }
+            }
+
+            /// Replaces the streams in the world with these
+            pub fn insert_into(self, ecs: &mut World) {
+                $( ecs.insert($name(self.$field)); )*
+            }
+        }
+    };
+}
+
+play_streams!(
+    /// Rolls made while fighting: hits, damage, and whatever else a fight needs
+    combat: CombatRng = Stream::Combat,
+    /// Rolls made to notice hidden things
+    perception: PerceptionRng = Stream::Perception,
+    /// Rolls made by monsters deciding where to go
+    ai: AiRng = Stream::Ai,
+    /// Rolls made for what status effects do, like where a confused player stumbles
+    status: StatusRng = Stream::Status,
+);
+
+/// Picks a seed for a run that was not given one
+pub fn random_seed() -> u64 {
+    RandomNumberGenerator::new().next_u64()
+}
+
+/// The generator for the layout of the map at `depth`
+pub fn map_rng(seed: u64, depth: i32) -> RandomNumberGenerator {
+    stream(seed, Stream::MapGeneration, depth)
+}
+
+/// The generator for what is spawned on the map at `depth`
+pub fn spawn_rng(seed: u64, depth: i32) -> RandomNumberGenerator {
+    stream(seed, Stream::Spawning, depth)
+}
+
+/// The same seed, stream and depth always give the same generator, whatever happened before
+fn stream(seed: u64, stream: Stream, depth: i32) -> RandomNumberGenerator {
+    let stream_seed = mix(seed ^ mix(stream as u64));
+
+    RandomNumberGenerator::seeded(mix(stream_seed ^ mix(depth as u64)))
+}
+
+/// The SplitMix64 finalizer, so that close seeds (or depths) do not give similar generators
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+    z ^ (z >> 31)
+}
+
+/// Returns the seed given with `--seed <number>`, if any
+pub fn seed_argument(args: &[String]) -> Result<Option<u64>, String> {
+    let position = match args.iter().position(|arg| arg == "--seed") {
+        Some(position) => position,
+        None => return Ok(None),
+    };
+
+    let value = args
+        .get(position + 1)
+        .ok_or_else(|| "Expected a number after --seed".to_string())?;
+
+    value
+        .parse::<u64>()
+        .map(Some)
+        .map_err(|_| format!("Expected a number after --seed, got '{}'", value))
+}
@@ -0,0 +1,353 @@
+use specs::prelude::*;
+use std::rc::Rc;
+
+use crate::save_storage::{self, SaveStorage};
+use crate::saveload_system::{self, SaveEncoding, SlotMetadata};
+
+const SLOT_PREFIX: &str = "slot_";
+const AUTOSAVE: &str = "autosave";
+const PREVIOUS_AUTOSAVE: &str = "autosave_previous";
+const ENCODINGS: [SaveEncoding; 2] = [SaveEncoding::Binary, SaveEncoding::Json];
+
+/// Where the game saved before there were slots, relative to the working directory
+pub const LEGACY_SAVE_FILE: &str = "savegame.json";
+/// Added to a legacy save once it is imported, so that it is only imported once
+const IMPORTED_SUFFIX: &str = ".imported";
+
+/// How many turns pass between autosaves unless `--autosave-turns` says otherwise
+pub const DEFAULT_AUTOSAVE_TURNS: u64 = 100;
+
+/// When the game saves by itself, see `State::set_autosave`
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub struct AutosaveSettings {
+    pub on_next_level: bool,
+    pub every_turns: Option<u64>,
+}
+
+impl AutosaveSettings {
+    /// Autosaves on every new level and every `every_turns` turns, 0 turns the interval off
+    pub fn every(every_turns: u64) -> AutosaveSettings {
+        AutosaveSettings {
+            on_next_level: true,
+            every_turns: Some(every_turns).filter(|turns| *turns > 0),
+        }
+    }
+}
+
+/// Returns the interval given with `--autosave-turns <number>`, if any
+pub fn autosave_turns_argument(args: &[String]) -> Result<Option<u64>, String> {
+    let position = match args.iter().position(|arg| arg == "--autosave-turns") {
+        Some(position) => position,
+        None => return Ok(None),
+    };
+
+    let value = args
+        .get(position + 1)
+        .ok_or_else(|| "Expected a number after --autosave-turns".to_string())?;
+
+    value
+        .parse::<u64>()
+        .map(Some)
+        .map_err(|_| format!("Expected a number after --autosave-turns, got '{}'", value))
+}
+
+/// The saves the slot pickers show: the autosave, the one it replaced, and the numbered slots
+#[derive(PartialEq, Eq, Ord, PartialOrd, Copy, Clone, Debug)]
+pub enum SlotId {
+    Autosave,
+    PreviousAutosave,
+    Numbered(u32),
+}
+
+impl SlotId {
+    fn stem(self) -> String {
+        match self {
+            SlotId::Autosave => AUTOSAVE.to_string(),
+            SlotId::PreviousAutosave => PREVIOUS_AUTOSAVE.to_string(),
+            SlotId::Numbered(number) => format!("{}{}", SLOT_PREFIX, number),
+        }
+    }
+
+    fn from_stem(stem: &str) -> Option<SlotId> {
+        match stem {
+            AUTOSAVE => Some(SlotId::Autosave),
+            PREVIOUS_AUTOSAVE => Some(SlotId::PreviousAutosave),
+            _ => stem
+                .strip_prefix(SLOT_PREFIX)?
+                .parse()
+                .ok()
+                .map(SlotId::Numbered),
+        }
+    }
+}
+
+impl std::fmt::Display for SlotId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SlotId::Autosave => write!(f, "Autosave"),
+            SlotId::PreviousAutosave => write!(f, "Previous autosave"),
+            SlotId::Numbered(number) => write!(f, "Slot {}", number),
+        }
+    }
+}
+
+/// A save in the storage. Saves that cannot be read are still listed, so they can be deleted
+/// from the slot picker
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct SaveSlot {
+    pub id: SlotId,
+    pub metadata: Result<SlotMetadata, String>,
+}
+
+impl SaveSlot {
+    pub fn title(&self) -> String {
+        self.id.to_string()
+    }
+
+    /// One line for the slot picker: name, depth, turns, time and seed
+    pub fn describe(&self) -> String {
+        match &self.metadata {
+            Ok(metadata) => format!(
+                "{} - depth {}, turn {}, {}, seed {}",
+                metadata.character_name,
+                metadata.depth,
+                metadata.turns,
+                format_timestamp(metadata.timestamp),
+                metadata.seed
+            ),
+            Err(error) => error.clone(),
+        }
+    }
+}
+
+/// The saves in a storage: `autosave.sav`, `autosave_previous.sav` and `slot_<number>.sav` (or
+/// `.json` for JSON saves)
+#[derive(Clone)]
+pub struct SaveSlots {
+    storage: Rc<dyn SaveStorage>,
+    encoding: SaveEncoding,
+}
+
+impl SaveSlots {
+    pub fn new(storage: Rc<dyn SaveStorage>) -> SaveSlots {
+        SaveSlots {
+            storage,
+            encoding: SaveEncoding::default(),
+        }
+    }
+
+    /// The slots in the storage the game uses on this platform, see `platform_storage`
+    pub fn for_platform() -> SaveSlots {
+        SaveSlots::new(save_storage::platform_storage())
+    }
+
+    /// Saves are written with `encoding` from now on, slots of the other one can still be loaded
+    pub fn with_encoding(mut self, encoding: SaveEncoding) -> SaveSlots {
+        self.encoding = encoding;
+        self
+    }
+
+    /// The encoding saves get when none is picked for them
+    pub fn encoding(&self) -> SaveEncoding {
+        self.encoding
+    }
+
+    pub fn storage(&self) -> &dyn SaveStorage {
+        self.storage.as_ref()
+    }
+
+    /// The name of the slot in the storage, or the name a save to it would get when it is empty
+    pub fn name(&self, id: SlotId) -> String {
+        ENCODINGS
+            .iter()
+            .map(|encoding| encoded_name(id, *encoding))
+            .find(|name| self.storage.contains(name))
+            .unwrap_or_else(|| encoded_name(id, self.encoding))
+    }
+
+    /// Every slot with its metadata, the autosaves first
+    pub fn list(&self) -> Vec<SaveSlot> {
+        self.ids()
+            .into_iter()
+            .map(|id| SaveSlot {
+                id,
+                metadata: self
+                    .storage
+                    .read(&self.name(id))
+                    .and_then(|data| saveload_system::parse_save(&data))
+                    .map(|save| save.header.metadata),
+            })
+            .collect()
+    }
+
+    /// Same as `!list().is_empty()` without reading the saves
+    pub fn has_saves(&self) -> bool {
+        !self.ids().is_empty()
+    }
+
+    /// Saves the game to a numbered slot after the last one and returns its number
+    pub fn save_new(&self, ecs: &mut World) -> Result<u32, String> {
+        self.save_new_as(ecs, self.encoding)
+    }
+
+    /// Same as `save_new`, with `encoding` instead of the encoding of the slots
+    pub fn save_new_as(&self, ecs: &mut World, encoding: SaveEncoding) -> Result<u32, String> {
+        let number = self.next_number();
+
+        self.save_as(ecs, SlotId::Numbered(number), encoding)?;
+
+        Ok(number)
+    }
+
+    /// Moves the save from before slots at `path` to a numbered slot after the last one, as it
+    /// is; it is upgraded when loaded. The file is renamed with `.imported` added, so that it is
+    /// imported once. Returns the number of the slot, or `None` when there is no such file.
+    pub fn import_legacy_save(&self, path: &str) -> Result<Option<u32>, String> {
+        let unable = |error: std::io::Error| format!("Unable to import {}: {}", path, error);
+
+        if !std::path::Path::new(path).is_file() {
+            return Ok(None);
+        }
+
+        let data = std::fs::read(path).map_err(unable)?;
+        let number = self.next_number();
+
+        self.storage.write(
+            &encoded_name(SlotId::Numbered(number), SaveEncoding::Json),
+            &data,
+        )?;
+        std::fs::rename(path, format!("{}{}", path, IMPORTED_SUFFIX)).map_err(unable)?;
+
+        Ok(Some(number))
+    }
+
+    /// Saves the game to the slot, replacing what was there
+    pub fn save(&self, ecs: &mut World, id: SlotId) -> Result<(), String> {
+        self.save_as(ecs, id, self.encoding)
+    }
+
+    /// Same as `save`, with `encoding` instead of the encoding of the slots
+    pub fn save_as(
+        &self,
+        ecs: &mut World,
+        id: SlotId,
+        encoding: SaveEncoding,
+    ) -> Result<(), String> {
+        let name = encoded_name(id, encoding);
+
+        saveload_system::save_game(ecs, self.storage(), &name, encoding)?;
+
+        // An overwritten slot may have been saved with the other encoding
+        let _ = self.storage.delete(&encoded_name(id, encoding.other()));
+
+        Ok(())
+    }
+
+    /// Saves to the autosave, the autosave before it is kept as the previous autosave in case
+    /// this one does not make it
+    pub fn autosave(&self, ecs: &mut World) -> Result<(), String> {
+        let current = self.name(SlotId::Autosave);
+
+        if self.storage.contains(&current) {
+            for encoding in ENCODINGS {
+                let _ = self
+                    .storage
+                    .delete(&encoded_name(SlotId::PreviousAutosave, encoding));
+            }
+
+            let extension = current.rsplit('.').next().unwrap_or_default();
+
+            self.storage.rename(
+                &current,
+                &format!("{}.{}", SlotId::PreviousAutosave.stem(), extension),
+            )?;
+        }
+
+        self.save(ecs, SlotId::Autosave)
+    }
+
+    pub fn load(&self, ecs: &mut World, id: SlotId) -> Result<(), String> {
+        saveload_system::load_game(ecs, self.storage(), &self.name(id))
+    }
+
+    pub fn delete(&self, id: SlotId) -> Result<(), String> {
+        self.storage
+            .delete(&self.name(id))
+            .map_err(|error| format!("Unable to delete {}: {}", id, error))
+    }
+
+    fn next_number(&self) -> u32 {
+        self.ids()
+            .iter()
+            .filter_map(|id| match id {
+                SlotId::Numbered(number) => Some(number + 1),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(1)
+    }
+
+    fn ids(&self) -> Vec<SlotId> {
+        let mut ids: Vec<SlotId> = self
+            .storage
+            .names()
+            .iter()
+            .filter_map(|name| {
+                let (stem, extension) = name.split_once('.')?;
+
+                if !ENCODINGS
+                    .iter()
+                    .any(|encoding| extension == encoding.extension())
+                {
+                    return None;
+                }
+
+                SlotId::from_stem(stem)
+            })
+            .collect();
+
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+fn encoded_name(id: SlotId, encoding: SaveEncoding) -> String {
+    format!("{}.{}", id.stem(), encoding.extension())
+}
+
+/// `YYYY-MM-DD HH:MM` in UTC, seconds since the Unix epoch
+pub fn format_timestamp(timestamp: u64) -> String {
+    // Only saves from before slots have no time
+    if timestamp == 0 {
+        return "unknown time".to_string();
+    }
+
+    let days = (timestamp / 86_400) as i64;
+    let minutes = timestamp % 86_400 / 60;
+
+    // Days to a civil date, from Howard Hinnant's `civil_from_days`
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        minutes / 60,
+        minutes % 60
+    )
+}
@@ -0,0 +1,214 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// Where saves are kept, by name. `saveload_system` reads and writes saves only through this.
+pub trait SaveStorage {
+    fn read(&self, name: &str) -> Result<Vec<u8>, String>;
+    fn write(&self, name: &str, data: &[u8]) -> Result<(), String>;
+    fn delete(&self, name: &str) -> Result<(), String>;
+    /// The names of every stored save, in no particular order
+    fn names(&self) -> Vec<String>;
+
+    /// Gives a save another name, replacing whatever had that name
+    fn rename(&self, from: &str, to: &str) -> Result<(), String> {
+        self.write(to, &self.read(from)?)?;
+        self.delete(from)
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.names().iter().any(|stored| stored == name)
+    }
+}
+
+/// Saves as files in one directory, created on the first write. A save is written to a temporary
+/// file first and renamed over the old one once complete, so a crash leaves one or the other.
+#[derive(Clone, Debug)]
+pub struct FileStorage {
+    dir: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> FileStorage {
+        FileStorage { dir: dir.into() }
+    }
+
+    /// The `saves` directory in the user's data directory, or the working directory on platforms
+    /// without one
+    pub fn in_user_data_dir() -> FileStorage {
+        match dirs::data_dir() {
+            Some(dir) => FileStorage::new(dir.join("roguelike-tutorial").join("saves")),
+            None => FileStorage::new("."),
+        }
+    }
+}
+
+impl SaveStorage for FileStorage {
+    fn read(&self, name: &str) -> Result<Vec<u8>, String> {
+        fs::read(self.dir.join(name)).map_err(|error| format!("Unable to read {}: {}", name, error))
+    }
+
+    fn write(&self, name: &str, data: &[u8]) -> Result<(), String> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|error| format!("Unable to create {}: {}", self.dir.display(), error))?;
+
+        let unable = |error: std::io::Error| format!("Unable to write {}: {}", name, error);
+        let temporary = self.dir.join(format!("{}.tmp", name));
+        let mut file = File::create(&temporary).map_err(unable)?;
+
+        file.write_all(data).map_err(unable)?;
+        file.sync_all().map_err(unable)?;
+
+        fs::rename(&temporary, self.dir.join(name)).map_err(unable)
+    }
+
+    fn delete(&self, name: &str) -> Result<(), String> {
+        fs::remove_file(self.dir.join(name))
+            .map_err(|error| format!("Unable to delete {}: {}", name, error))
+    }
+
+    fn names(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        entries
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .collect()
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.dir.join(name).is_file()
+    }
+
+    fn rename(&self, from: &str, to: &str) -> Result<(), String> {
+        fs::rename(self.dir.join(from), self.dir.join(to))
+            .map_err(|error| format!("Unable to rename {} to {}: {}", from, to, error))
+    }
+}
+
+/// Saves kept in memory for as long as one of the clones lives, for tests
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+    saves: Rc<RefCell<BTreeMap<String, Vec<u8>>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+}
+
+impl SaveStorage for MemoryStorage {
+    fn read(&self, name: &str) -> Result<Vec<u8>, String> {
+        self.saves
+            .borrow()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("There is no save named {}", name))
+    }
+
+    fn write(&self, name: &str, data: &[u8]) -> Result<(), String> {
+        self.saves
+            .borrow_mut()
+            .insert(name.to_string(), data.to_vec());
+
+        Ok(())
+    }
+
+    fn delete(&self, name: &str) -> Result<(), String> {
+        self.saves
+            .borrow_mut()
+            .remove(name)
+            .map(|_| ())
+            .ok_or_else(|| format!("There is no save named {}", name))
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.saves.borrow().keys().cloned().collect()
+    }
+}
+
+/// Saves in the browser's localStorage, base64 encoded under `prefix` + name
+#[cfg(target_arch = "wasm32")]
+#[derive(Clone, Debug)]
+pub struct LocalStorage {
+    prefix: String,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorage {
+    pub fn new(prefix: &str) -> LocalStorage {
+        LocalStorage {
+            prefix: prefix.to_string(),
+        }
+    }
+
+    fn storage(&self) -> Result<web_sys::Storage, String> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| "The browser has no localStorage".to_string())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl SaveStorage for LocalStorage {
+    fn read(&self, name: &str) -> Result<Vec<u8>, String> {
+        use base64::Engine;
+
+        let encoded = self
+            .storage()?
+            .get_item(&format!("{}{}", self.prefix, name))
+            .ok()
+            .flatten()
+            .ok_or_else(|| format!("There is no save named {}", name))?;
+
+        base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|error| format!("Unable to read {}: {}", name, error))
+    }
+
+    fn write(&self, name: &str, data: &[u8]) -> Result<(), String> {
+        use base64::Engine;
+
+        let encoded = base64::engine::general_purpose::STANDARD.encode(data);
+
+        // The browser refuses to store more than a few megabytes per site
+        self.storage()?
+            .set_item(&format!("{}{}", self.prefix, name), &encoded)
+            .map_err(|_| format!("Unable to write {}, the browser storage may be full", name))
+    }
+
+    fn delete(&self, name: &str) -> Result<(), String> {
+        self.storage()?
+            .remove_item(&format!("{}{}", self.prefix, name))
+            .map_err(|_| format!("Unable to delete {}", name))
+    }
+
+    fn names(&self) -> Vec<String> {
+        let Ok(storage) = self.storage() else {
+            return Vec::new();
+        };
+        let length = storage.length().unwrap_or(0);
+
+        (0..length)
+            .filter_map(|i| storage.key(i).ok().flatten())
+            .filter_map(|key| key.strip_prefix(&self.prefix).map(str::to_string))
+            .collect()
+    }
+}
+
+/// Where the game keeps its saves: files in the user's data directory, or localStorage in the
+/// browser
+pub fn platform_storage() -> Rc<dyn SaveStorage> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let storage = Rc::new(FileStorage::in_user_data_dir());
+
+    #[cfg(target_arch = "wasm32")]
+    let storage = Rc::new(LocalStorage::new("roguelike-tutorial/saves/"));
+
+    storage
+}
@@ -1,123 +1,300 @@
-use specs::saveload::SimpleMarkerAllocator;
+use serde::{Deserialize, Serialize};
 use specs::{
-    error::NoError,
     prelude::*,
-    saveload::{DeserializeComponents, MarkedBuilder, SerializeComponents, SimpleMarker},
+    saveload::{MarkedBuilder, SimpleMarker},
 };
-use std::fs;
-use std::fs::File;
+use std::io::{Read, Write};
 use std::path::Path;
 
+use crate::component_registry::{self, SavedComponents};
 use crate::components::*;
-use crate::map::Map;
-
-const SAVE_FILE: &str = "./savegame.json";
-
-macro_rules! serialize_individually {
-    ($ecs:expr, $ser:expr, $data:expr, $( $type:ty),*) => {
-        $(
-        SerializeComponents::<NoError, SimpleMarker<SerializeMe>>::serialize(
-            &( $ecs.read_storage::<$type>(), ),
-            &$data.0,
-            &$data.1,
-            &mut $ser,
-        )
-        .unwrap();
-        )*
-    };
+use crate::dungeon::MasterDungeonMap;
+use crate::map::{Map, TileType};
+use crate::rng::{GameSeed, RngStreams};
+use crate::save_storage::{FileStorage, SaveStorage};
+use crate::tile_encoding;
+use crate::TurnCount;
+
+/// Bumped whenever what a save holds changes, together with a migration from the version
+/// before it in `MIGRATIONS`
+pub const SAVE_FORMAT_VERSION: u32 = 13;
+
+/// Upgrades a save from one format version to the next, `MIGRATIONS[0]` takes version 1 to 2
+type Migration = fn(&mut SaveFile) -> Result<(), String>;
+
+const MIGRATIONS: [Migration; (SAVE_FORMAT_VERSION - 1) as usize] = [
+    name_components,
+    add_slot_metadata,
+    pack_map_tiles,
+    add_initiative,
+    add_ai_state,
+    add_factions,
+    add_damage_dice,
+    add_status_effects,
+    add_damage_types,
+    add_experience,
+    add_attributes,
+    add_rng_streams,
+];
+
+/// The components saved by format version 1, in the order they were written
+const FORMAT_1_COMPONENTS: [&str; 38] = [
+    "Position",
+    "Renderable",
+    "Player",
+    "Viewshed",
+    "Monster",
+    "Name",
+    "BlocksTile",
+    "CombatStats",
+    "SufferDamage",
+    "WantsToMelee",
+    "Item",
+    "Consumable",
+    "Ranged",
+    "InflictsDamage",
+    "AreaOfEffect",
+    "Confusion",
+    "ProvidesHealing",
+    "InBackpack",
+    "WantsToPickupItem",
+    "WantsToUseItem",
+    "WantsToDropItem",
+    "SerializationHelper",
+    "Equippable",
+    "Equipped",
+    "MeleePowerBonus",
+    "DefenseBonus",
+    "WantsToRemoveItem",
+    "ParticleLifetime",
+    "HungerClock",
+    "ProvidesFood",
+    "MagicMapper",
+    "Hidden",
+    "EntryTrigger",
+    "EntityMoved",
+    "SingleActivation",
+    "OtherLevelPosition",
+    "BlocksVisibility",
+    "Door",
+];
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct SaveHeader {
+    pub format_version: u32,
+    /// The version of the game that wrote the save, only used in messages
+    pub game_version: String,
+    #[serde(default)]
+    pub metadata: SlotMetadata,
 }
 
-macro_rules! deserialize_individually {
-    ($ecs:expr, $de:expr, $data:expr, $( $type:ty),*) => {
-        $(
-        DeserializeComponents::<NoError, _>::deserialize(
-            &mut ( &mut $ecs.write_storage::<$type>(), ),
-            $data.0, // entities
-            &mut $data.1, // marker
-            &mut $data.2, // allocater
-            &mut $de,
-        )
-        .unwrap();
-        )*
+/// What the save slot picker shows about a save
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Clone, Debug)]
+pub struct SlotMetadata {
+    pub character_name: String,
+    pub depth: i32,
+    pub turns: u64,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub seed: u64,
+}
+
+/// What a binary save starts with, JSON saves start with `{`
+const BINARY_MAGIC: &[u8] = b"RLSAVE";
+
+/// How a save is written, loading tells them apart by the first bytes
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum SaveEncoding {
+    /// MessagePack compressed with deflate, behind `BINARY_MAGIC`
+    #[default]
+    Binary,
+    /// Plain JSON, to read or edit a save by hand
+    Json,
+}
+
+impl SaveEncoding {
+    pub fn extension(self) -> &'static str {
+        match self {
+            SaveEncoding::Binary => "sav",
+            SaveEncoding::Json => "json",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SaveEncoding::Binary => "Binary",
+            SaveEncoding::Json => "JSON",
+        }
+    }
+
+    pub fn other(self) -> SaveEncoding {
+        match self {
+            SaveEncoding::Binary => SaveEncoding::Json,
+            SaveEncoding::Json => SaveEncoding::Binary,
+        }
+    }
+}
+
+/// Returns the encoding given with `--save-format <binary|json>`, if any
+pub fn save_encoding_argument(args: &[String]) -> Result<Option<SaveEncoding>, String> {
+    let position = match args.iter().position(|arg| arg == "--save-format") {
+        Some(position) => position,
+        None => return Ok(None),
     };
+
+    match args.get(position + 1).map(String::as_str) {
+        Some("binary") => Ok(Some(SaveEncoding::Binary)),
+        Some("json") => Ok(Some(SaveEncoding::Json)),
+        Some(value) => Err(format!(
+            "Expected binary or json after --save-format, got '{}'",
+            value
+        )),
+        None => Err("Expected binary or json after --save-format".to_string()),
+    }
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-pub fn save_game(ecs: &mut World) {
+/// A save: the header, then every component storage by its type name
+#[derive(Serialize, Deserialize)]
+pub struct SaveFile {
+    pub header: SaveHeader,
+    pub components: SavedComponents,
+}
+
+/// Writes the game to the storage under `name`, replacing what was there
+pub fn save_game(
+    ecs: &mut World,
+    storage: &dyn SaveStorage,
+    name: &str,
+    encoding: SaveEncoding,
+) -> Result<(), String> {
+    let data = encode_save(&snapshot(ecs), encoding)?;
+
+    storage.write(name, &data)
+}
+
+/// `save_game` to a file
+pub fn save_game_to(ecs: &mut World, path: &str, encoding: SaveEncoding) -> Result<(), String> {
+    let (storage, name) = file_storage(path)?;
+
+    save_game(ecs, &storage, &name, encoding)
+}
+
+/// The directory of a file as a storage, and the name of the file in it
+fn file_storage(path: &str) -> Result<(FileStorage, String), String> {
+    let path = Path::new(path);
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("{} is not a file name", path.display()))?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    Ok((FileStorage::new(dir), name.to_string()))
+}
+
+/// Everything a save holds about the game
+fn snapshot(ecs: &mut World) -> SaveFile {
     // Create helper
     let map_copy = ecs.get_mut::<Map>().unwrap().clone();
+    let dungeon = (*ecs.fetch::<MasterDungeonMap>()).clone();
+    let seed = ecs.fetch::<GameSeed>().0;
+    let turns = ecs.fetch::<TurnCount>().0;
+    let rng_streams = RngStreams::capture(ecs);
+    let metadata = SlotMetadata {
+        character_name: player_name(ecs),
+        depth: map_copy.depth,
+        turns,
+        timestamp: now(),
+        seed,
+    };
     let save_helper = ecs
         .create_entity()
-        .with(SerializationHelper { map: map_copy })
+        .with(SerializationHelper {
+            map: map_copy,
+            dungeon,
+            seed,
+            turns,
+            rng_streams,
+        })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
-    {
-        // Actually serialize
-        let data = (
-            ecs.entities(),
-            ecs.read_storage::<SimpleMarker<SerializeMe>>(),
-        );
-
-        let save_file = File::create(SAVE_FILE).unwrap();
-        let mut serializer = serde_json::Serializer::new(save_file);
-
-        serialize_individually!(
-            ecs,
-            serializer,
-            data,
-            Position,
-            Renderable,
-            Player,
-            Viewshed,
-            Monster,
-            Name,
-            BlocksTile,
-            CombatStats,
-            SufferDamage,
-            WantsToMelee,
-            Item,
-            Consumable,
-            Ranged,
-            InflictsDamage,
-            AreaOfEffect,
-            Confusion,
-            ProvidesHealing,
-            InBackpack,
-            WantsToPickupItem,
-            WantsToUseItem,
-            WantsToDropItem,
-            SerializationHelper,
-            Equippable,
-            Equipped,
-            MeleePowerBonus,
-            DefenseBonus,
-            WantsToRemoveItem,
-            ParticleLifetime,
-            HungerClock,
-            ProvidesFood,
-            MagicMapper,
-            Hidden,
-            EntryTrigger,
-            EntityMoved,
-            SingleActivation,
-            BlocksVisibility,
-            Door
-        );
-    }
+    // Actually serialize
+    let mut components = SavedComponents::new();
+
+    component_registry::serialize_components(ecs, &mut components);
 
     // Clean up
     ecs.delete_entity(save_helper).expect("Crash on cleanup");
+
+    SaveFile {
+        header: SaveHeader {
+            format_version: SAVE_FORMAT_VERSION,
+            game_version: env!("CARGO_PKG_VERSION").to_string(),
+            metadata,
+        },
+        components,
+    }
+}
+
+pub fn encode_save(save: &SaveFile, encoding: SaveEncoding) -> Result<Vec<u8>, String> {
+    let unable = |error: &dyn std::fmt::Display| format!("Unable to write the save: {}", error);
+
+    match encoding {
+        SaveEncoding::Json => serde_json::to_vec(save).map_err(|error| unable(&error)),
+        SaveEncoding::Binary => {
+            // Bincode cannot read back the `serde_json::Value`s the storages are kept in,
+            // MessagePack describes itself the way JSON does
+            let packed = rmp_serde::to_vec_named(save).map_err(|error| unable(&error))?;
+            let mut encoder = flate2::write::DeflateEncoder::new(
+                BINARY_MAGIC.to_vec(),
+                flate2::Compression::default(),
+            );
+
+            encoder.write_all(&packed).map_err(|error| unable(&error))?;
+            encoder.finish().map_err(|error| unable(&error))
+        }
+    }
+}
+
+fn player_name(ecs: &World) -> String {
+    let names = ecs.read_storage::<Name>();
+
+    names
+        .get(*ecs.fetch::<Entity>())
+        .map(|name| name.name.clone())
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
 }
 
 #[cfg(target_arch = "wasm32")]
-pub fn save_game(_ecs: &mut World) {}
+fn now() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+/// Replaces the world with the game saved in the storage under `name`
+pub fn load_game(ecs: &mut World, storage: &dyn SaveStorage, name: &str) -> Result<(), String> {
+    load_game_data(ecs, &storage.read(name)?)
+}
+
+/// `load_game` from a file
+pub fn load_game_from(ecs: &mut World, path: &str) -> Result<(), String> {
+    let (storage, name) = file_storage(path)?;
 
-pub fn does_save_exist() -> bool {
-    Path::new(SAVE_FILE).exists()
+    load_game(ecs, &storage, &name)
 }
 
-pub fn load_game(ecs: &mut World) {
+/// Replaces the world with the saved game. The world is only touched once the save has been read
+/// and upgraded, a save that turns out broken after that leaves it half loaded.
+fn load_game_data(ecs: &mut World, data: &[u8]) -> Result<(), String> {
+    let mut components = parse_save(data)?.components;
+
     {
         // Delete everything
         let mut to_delete = Vec::new();
@@ -129,68 +306,26 @@ pub fn load_game(ecs: &mut World) {
         }
     }
 
-    let data = fs::read_to_string("./savegame.json").unwrap();
-    let mut de = serde_json::Deserializer::from_str(&data);
-
-    {
-        let mut d = (
-            &mut ecs.entities(),
-            &mut ecs.write_storage::<SimpleMarker<SerializeMe>>(),
-            &mut ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>(),
-        );
-
-        deserialize_individually!(
-            ecs,
-            de,
-            d,
-            Position,
-            Renderable,
-            Player,
-            Viewshed,
-            Monster,
-            Name,
-            BlocksTile,
-            CombatStats,
-            SufferDamage,
-            WantsToMelee,
-            Item,
-            Consumable,
-            Ranged,
-            InflictsDamage,
-            AreaOfEffect,
-            Confusion,
-            ProvidesHealing,
-            InBackpack,
-            WantsToPickupItem,
-            WantsToUseItem,
-            WantsToDropItem,
-            SerializationHelper,
-            Equippable,
-            Equipped,
-            MeleePowerBonus,
-            DefenseBonus,
-            WantsToRemoveItem,
-            ParticleLifetime,
-            HungerClock,
-            ProvidesFood,
-            MagicMapper,
-            Hidden,
-            EntryTrigger,
-            EntityMoved,
-            SingleActivation
-        );
-    }
+    component_registry::deserialize_components(ecs, &mut components)?;
 
     let mut deleteme: Option<Entity> = None;
+    let mut rng_streams: Option<RngStreams> = None;
     {
         let entities = ecs.entities();
         let helper = ecs.read_storage::<SerializationHelper>();
         let player = ecs.read_storage::<Player>();
         let position = ecs.read_storage::<Position>();
         for (e, h) in (&entities, &helper).join() {
+            h.map.check_size()?;
+            h.dungeon.check_levels()?;
+
             let mut world_map = ecs.write_resource::<Map>();
             *world_map = h.map.clone();
             world_map.tile_content = vec![Vec::new(); world_map.map_length];
+            *ecs.write_resource::<MasterDungeonMap>() = h.dungeon.clone();
+            *ecs.write_resource::<GameSeed>() = GameSeed(h.seed);
+            *ecs.write_resource::<TurnCount>() = TurnCount(h.turns);
+            rng_streams = Some(h.rng_streams.clone());
             deleteme = Some(e);
         }
         for (e, _p, pos) in (&entities, &player, &position).join() {
@@ -201,12 +336,622 @@ pub fn load_game(ecs: &mut World) {
         }
     }
 
-    ecs.delete_entity(deleteme.unwrap())
-        .expect("Unable to delete helper");
+    let helper = deleteme.ok_or_else(|| "The save has no map".to_string())?;
+
+    ecs.delete_entity(helper).expect("Unable to delete helper");
+
+    if let Some(rng_streams) = rng_streams {
+        rng_streams.insert_into(ecs);
+    }
+
+    Ok(())
+}
+
+/// Reads a save of any encoding and format version up to `SAVE_FORMAT_VERSION`, upgraded to the
+/// current one
+pub fn parse_save(data: &[u8]) -> Result<SaveFile, String> {
+    let mut save = match data.strip_prefix(BINARY_MAGIC) {
+        Some(compressed) => decode_binary(compressed)?,
+        None => decode_json(data)?,
+    };
+
+    let version = save.header.format_version;
+
+    if version > SAVE_FORMAT_VERSION {
+        return Err(format!(
+            "The save was made by a newer version of the game ({})",
+            save.header.game_version
+        ));
+    }
+    if version == 0 {
+        return Err("The save has an unknown format version 0".to_string());
+    }
+
+    for migration in MIGRATIONS.iter().skip(version as usize - 1) {
+        migration(&mut save)?;
+    }
+
+    save.header.format_version = SAVE_FORMAT_VERSION;
+
+    Ok(save)
+}
+
+/// The encoding a save was written with
+pub fn encoding_of(data: &[u8]) -> SaveEncoding {
+    if data.starts_with(BINARY_MAGIC) {
+        SaveEncoding::Binary
+    } else {
+        SaveEncoding::Json
+    }
+}
+
+fn decode_binary(compressed: &[u8]) -> Result<SaveFile, String> {
+    let mut packed = Vec::new();
+
+    flate2::read::DeflateDecoder::new(compressed)
+        .read_to_end(&mut packed)
+        .map_err(|error| format!("The save is not readable: {}", error))?;
+
+    rmp_serde::from_slice(&packed).map_err(|error| format!("The save is not readable: {}", error))
+}
+
+fn decode_json(data: &[u8]) -> Result<SaveFile, String> {
+    let data = std::str::from_utf8(data)
+        .map_err(|error| format!("The save is not readable: {}", error))?;
+
+    match serde_json::from_str::<SaveFile>(data) {
+        Ok(save) => Ok(save),
+        Err(error) => {
+            // Format version 1 had no header, just one storage after another
+            read_format_1(data).ok_or_else(|| format!("The save is not readable: {}", error))
+        }
+    }
+}
+
+/// Reads a headerless save, the storages stay unnamed (numbered) until `name_components`
+fn read_format_1(data: &str) -> Option<SaveFile> {
+    let storages = serde_json::Deserializer::from_str(data)
+        .into_iter::<serde_json::Value>()
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+
+    if storages.is_empty() || !storages.iter().all(|storage| storage.is_array()) {
+        return None;
+    }
+
+    Some(SaveFile {
+        header: SaveHeader {
+            format_version: 1,
+            game_version: "unknown".to_string(),
+            metadata: SlotMetadata::default(),
+        },
+        components: storages
+            .into_iter()
+            .enumerate()
+            .map(|(i, storage)| (i.to_string(), storage))
+            .collect(),
+    })
+}
+
+/// Version 1 to 2: names the storages, fills in what saves from before seeds and the master
+/// dungeon lack
+fn name_components(save: &mut SaveFile) -> Result<(), String> {
+    let names: Vec<&str> = match save.components.len() {
+        38 => FORMAT_1_COMPONENTS.to_vec(),
+        // Before the master dungeon there were no other levels to be on
+        37 => FORMAT_1_COMPONENTS
+            .iter()
+            .copied()
+            .filter(|name| *name != "OtherLevelPosition")
+            .collect(),
+        count => {
+            return Err(format!(
+                "The save has {} component storages, which no known version wrote",
+                count
+            ))
+        }
+    };
+
+    let mut components = serde_json::Map::new();
+
+    for (i, name) in names.iter().enumerate() {
+        let storage = save
+            .components
+            .remove(&i.to_string())
+            .ok_or_else(|| format!("The save is missing storage {}", i))?;
+
+        components.insert(name.to_string(), storage);
+    }
+
+    let helpers = components
+        .get_mut("SerializationHelper")
+        .and_then(|storage| storage.as_array_mut())
+        .into_iter()
+        .flatten()
+        .filter_map(|entity| entity.pointer_mut("/components/0"))
+        .filter_map(|helper| helper.as_object_mut());
+
+    for helper in helpers {
+        helper
+            .entry("dungeon")
+            .or_insert_with(|| serde_json::json!({ "levels": {} }));
+        helper.entry("seed").or_insert_with(|| serde_json::json!(0));
+    }
+
+    save.components = components;
+
+    Ok(())
+}
+
+/// Version 2 to 3: counts turns and keeps what the save slot picker shows in the header
+fn add_slot_metadata(save: &mut SaveFile) -> Result<(), String> {
+    let player_markers: Vec<&serde_json::Value> = saved_entities(&save.components, "Player")
+        .map(|(marker, _)| marker)
+        .collect();
+    let character_name = saved_entities(&save.components, "Name")
+        .find(|(marker, _)| player_markers.contains(marker))
+        .and_then(|(_, name)| name["name"].as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    let helper = save
+        .components
+        .get_mut("SerializationHelper")
+        .and_then(|storage| storage.as_array_mut())
+        .into_iter()
+        .flatten()
+        .filter_map(|entity| entity.pointer_mut("/components/0"))
+        .find_map(|helper| helper.as_object_mut())
+        .ok_or_else(|| "The save has no map".to_string())?;
+
+    helper.insert("turns".to_string(), serde_json::json!(0));
+
+    save.header.metadata = SlotMetadata {
+        character_name,
+        depth: helper["map"]["depth"].as_i64().unwrap_or_default() as i32,
+        turns: 0,
+        timestamp: 0,
+        seed: helper["seed"].as_u64().unwrap_or_default(),
+    };
+
+    Ok(())
+}
+
+/// Version 3 to 4: run-length encodes the tiles and bit-packs the per-tile flags of every map
+fn pack_map_tiles(save: &mut SaveFile) -> Result<(), String> {
+    let helper = save
+        .components
+        .get_mut("SerializationHelper")
+        .and_then(|storage| storage.as_array_mut())
+        .into_iter()
+        .flatten()
+        .filter_map(|entity| entity.pointer_mut("/components/0"))
+        .find_map(|helper| helper.as_object_mut())
+        .ok_or_else(|| "The save has no map".to_string())?;
+
+    if let Some(map) = helper.get_mut("map") {
+        pack_map(map)?;
+    }
+
+    let levels = helper
+        .get_mut("dungeon")
+        .and_then(|dungeon| dungeon.get_mut("levels"))
+        .and_then(|levels| levels.as_object_mut())
+        .into_iter()
+        .flat_map(|levels| levels.values_mut());
+
+    for level in levels {
+        if let Some(map) = level.get_mut("map") {
+            pack_map(map)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Gives the player and every monster the speed they had when the scheduler came in, saves from
+/// before it had none
+fn add_initiative(save: &mut SaveFile) -> Result<(), String> {
+    const NORMAL_SPEED: i32 = 100;
+    const MOB_SPEEDS: [(&str, i32); 2] = [("Goblin", 125), ("Orc", 80)];
+
+    let speed_of = |marker: &serde_json::Value| {
+        saved_entities(&save.components, "Name")
+            .find(|(named, _)| *named == marker)
+            .and_then(|(_, name)| frozen(&MOB_SPEEDS, name.get("name")?.as_str()?))
+            .unwrap_or(NORMAL_SPEED)
+    };
+    let storage = saved_entities(&save.components, "Player")
+        .map(|(marker, _)| (marker, NORMAL_SPEED))
+        .chain(
+            saved_entities(&save.components, "Monster")
+                .map(|(marker, _)| (marker, speed_of(marker))),
+        )
+        .map(|(marker, speed)| {
+            serde_json::json!({
+                "marker": marker,
+                "components": [{ "speed": speed, "energy": 0 }],
+            })
+        })
+        .collect();
+
+    save.components
+        .insert("Initiative".to_string(), serde_json::Value::Array(storage));
+
+    Ok(())
+}
+
+/// Saves from before the AI states had monsters that stood still until they saw the player
+fn add_ai_state(save: &mut SaveFile) -> Result<(), String> {
+    let storage = saved_entities(&save.components, "Monster")
+        .map(|(marker, _)| {
+            serde_json::json!({
+                "marker": marker,
+                "components": [{ "state": { "Idle": { "turns": 0 } } }],
+            })
+        })
+        .collect();
+
+    save.components
+        .insert("Ai".to_string(), serde_json::Value::Array(storage));
+
+    Ok(())
+}
+
+/// Saves from before the factions had every monster attack the player and nobody else
+fn add_factions(save: &mut SaveFile) -> Result<(), String> {
+    const PLAYER_FACTION: &str = "Player";
+    const MOB_FACTIONS: [(&str, &str); 4] = [
+        ("Goblin", "Goblins"),
+        ("Orc", "Orcs"),
+        ("Deer", "Animals"),
+        ("Guard", "Guards"),
+    ];
+
+    let names: Vec<(&serde_json::Value, &str)> = saved_entities(&save.components, "Name")
+        .filter_map(|(marker, name)| Some((marker, name.get("name")?.as_str()?)))
+        .collect();
+    let name_of = |marker: &serde_json::Value| {
+        names
+            .iter()
+            .find(|(named, _)| *named == marker)
+            .map(|(_, name)| *name)
+    };
+    let player = saved_entities(&save.components, "Player")
+        .map(|(marker, _)| (marker.clone(), PLAYER_FACTION.to_string()));
+    let monsters = saved_entities(&save.components, "Monster")
+        .map(|(marker, _)| {
+            let name = name_of(marker).unwrap_or_default();
+            let faction = frozen(&MOB_FACTIONS, name).ok_or_else(|| {
+                format!(
+                    "The save has a monster '{}' that the game does not know",
+                    name
+                )
+            })?;
+
+            Ok((marker.clone(), faction.to_string()))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let storage = player
+        .chain(monsters)
+        .map(|(marker, faction)| {
+            serde_json::json!({
+                "marker": marker,
+                "components": [{ "name": faction }],
+            })
+        })
+        .collect();
+
+    save.components
+        .insert("Faction".to_string(), serde_json::Value::Array(storage));
+
+    Ok(())
+}
+
+/// Saves from before the attack rolls had weapons that only added power, and monsters without
+/// claws and teeth of their own
+fn add_damage_dice(save: &mut SaveFile) -> Result<(), String> {
+    /// The number of dice, the sides of each and the bonus
+    const MELEE_DAMAGE: [(&str, (i32, i32, i32)); 6] = [
+        ("Goblin", (1, 4, 0)),
+        ("Orc", (1, 6, 0)),
+        ("Deer", (1, 3, 0)),
+        ("Guard", (1, 8, 0)),
+        ("Dagger", (1, 4, 0)),
+        ("Longsword", (1, 8, 0)),
+    ];
+
+    let storage = saved_entities(&save.components, "Name")
+        .filter_map(|(marker, name)| {
+            let (n_dice, die_type, bonus) = frozen(&MELEE_DAMAGE, name.get("name")?.as_str()?)?;
+            let damage = rltk::DiceType::new(n_dice, die_type, bonus);
+
+            Some(serde_json::json!({
+                "marker": marker,
+                "components": [{ "damage": damage }],
+            }))
+        })
+        .collect();
+
+    save.components
+        .insert("MeleeWeapon".to_string(), serde_json::Value::Array(storage));
+
+    Ok(())
+}
+
+/// Saves from before the status effects had a storage for each of confusion, haste and slow,
+/// both on the items that cause them and on whoever is under them
+fn add_status_effects(save: &mut SaveFile) -> Result<(), String> {
+    let items: Vec<serde_json::Value> = saved_entities(&save.components, "Item")
+        .map(|(marker, _)| marker.clone())
+        .collect();
+    let mut effects: Vec<(serde_json::Value, Vec<serde_json::Value>)> = Vec::new();
+
+    for kind in ["Confusion", "Haste", "Slow"] {
+        for (marker, component) in saved_entities(&save.components, kind) {
+            let effect = serde_json::json!({ "kind": kind, "turns": component["turns"] });
+
+            match effects.iter_mut().find(|(known, _)| known == marker) {
+                Some((_, known_effects)) => known_effects.push(effect),
+                None => effects.push((marker.clone(), vec![effect])),
+            }
+        }
+
+        save.components.remove(kind);
+    }
+
+    let (inflicted, suffered): (Vec<_>, Vec<_>) = effects
+        .into_iter()
+        .map(|(marker, effects)| {
+            serde_json::json!({
+                "marker": marker,
+                "components": [{ "effects": effects }],
+            })
+        })
+        .partition(|entity| items.contains(&entity["marker"]));
+
+    save.components.insert(
+        "InflictsStatus".to_string(),
+        serde_json::Value::Array(inflicted),
+    );
+    save.components.insert(
+        "StatusEffects".to_string(),
+        serde_json::Value::Array(suffered),
+    );
+
+    Ok(())
+}
+
+/// Saves from before damage had types, where everything hit as hard as a blow and nobody resisted
+/// anything
+fn add_damage_types(save: &mut SaveFile) -> Result<(), String> {
+    /// Everything else that dealt damage dealt physical damage
+    const DAMAGE_TYPES: [(&str, DamageType); 2] = [
+        ("Fireball Scroll", DamageType::Fire),
+        ("Frost Scroll", DamageType::Cold),
+    ];
+    /// The damage types a mob resisted, was immune to and was vulnerable to
+    type Affinities = (
+        &'static [DamageType],
+        &'static [DamageType],
+        &'static [DamageType],
+    );
+    const DAMAGE_AFFINITIES: [(&str, Affinities); 3] = [
+        ("Goblin", (&[], &[], &[DamageType::Fire])),
+        ("Orc", (&[DamageType::Fire], &[], &[])),
+        ("Guard", (&[], &[DamageType::Poison], &[])),
+    ];
+
+    let names: Vec<(serde_json::Value, String)> = saved_entities(&save.components, "Name")
+        .filter_map(|(marker, name)| Some((marker.clone(), name.get("name")?.as_str()?.into())))
+        .collect();
+    let damage_type_of = |marker: &serde_json::Value| {
+        names
+            .iter()
+            .find(|(named, _)| named == marker)
+            .and_then(|(_, name)| frozen(&DAMAGE_TYPES, name))
+            .unwrap_or_default()
+    };
+
+    for storage in ["InflictsDamage", "MeleeWeapon"] {
+        let Some(entities) = save
+            .components
+            .get_mut(storage)
+            .and_then(|storage| storage.as_array_mut())
+        else {
+            continue;
+        };
+
+        for entity in entities.iter_mut() {
+            let damage_type = damage_type_of(&entity["marker"]);
+
+            if let Some(component) = entity.pointer_mut("/components/0") {
+                if component.is_object() {
+                    component["damage_type"] = serde_json::json!(damage_type);
+                }
+            }
+        }
+    }
+
+    if let Some(entities) = save
+        .components
+        .get_mut("SufferDamage")
+        .and_then(|storage| storage.as_array_mut())
+    {
+        for entity in entities.iter_mut() {
+            if let Some(amount) = entity.pointer_mut("/components/0/amount") {
+                let typed: Vec<serde_json::Value> = amount
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|amount| serde_json::json!([amount, DamageType::Physical]))
+                    .collect();
+
+                *amount = serde_json::Value::Array(typed);
+            }
+        }
+    }
+
+    let mut affinities = [Vec::new(), Vec::new(), Vec::new()];
+
+    for (marker, name) in names.iter() {
+        let Some((resistances, immunities, vulnerabilities)) = frozen(&DAMAGE_AFFINITIES, name)
+        else {
+            continue;
+        };
+
+        for (storage, damage_types) in
+            affinities
+                .iter_mut()
+                .zip([resistances, immunities, vulnerabilities])
+        {
+            if !damage_types.is_empty() {
+                storage.push(serde_json::json!({
+                    "marker": marker,
+                    "components": [{ "damage_types": damage_types }],
+                }));
+            }
+        }
+    }
+
+    for (name, storage) in ["Resistances", "Immunities", "Vulnerabilities"]
+        .into_iter()
+        .zip(affinities)
+    {
+        save.components
+            .insert(name.to_string(), serde_json::Value::Array(storage));
+    }
+
+    Ok(())
 }
 
-pub fn delete_save() {
-    if Path::new(SAVE_FILE).exists() {
-        fs::remove_file(SAVE_FILE).expect("Unable to delete file");
+/// Saves from before experience, where the player stayed as they started whatever they killed
+fn add_experience(save: &mut SaveFile) -> Result<(), String> {
+    const XP_VALUES: [(&str, i32); 4] = [("Goblin", 10), ("Orc", 20), ("Deer", 5), ("Guard", 30)];
+
+    let experience = saved_entities(&save.components, "Player")
+        .map(|(marker, _)| {
+            serde_json::json!({
+                "marker": marker,
+                "components": [{ "level": 1, "xp": 0 }],
+            })
+        })
+        .collect();
+    let xp_values = saved_entities(&save.components, "Name")
+        .filter_map(|(marker, name)| {
+            let xp = frozen(&XP_VALUES, name.get("name")?.as_str()?)?;
+
+            (xp > 0).then(|| {
+                serde_json::json!({
+                    "marker": marker,
+                    "components": [{ "xp": xp }],
+                })
+            })
+        })
+        .collect();
+
+    save.components.insert(
+        "Experience".to_string(),
+        serde_json::Value::Array(experience),
+    );
+    save.components
+        .insert("XpValue".to_string(), serde_json::Value::Array(xp_values));
+
+    Ok(())
+}
+
+/// Saves from before attributes and skills, where the player was average at everything and had
+/// learned nothing
+fn add_attributes(save: &mut SaveFile) -> Result<(), String> {
+    let players: Vec<serde_json::Value> = saved_entities(&save.components, "Player")
+        .map(|(marker, _)| marker.clone())
+        .collect();
+    let storage = |component: serde_json::Value| {
+        players
+            .iter()
+            .map(|marker| {
+                serde_json::json!({
+                    "marker": marker,
+                    "components": [component],
+                })
+            })
+            .collect()
+    };
+    let attributes = storage(serde_json::json!(Attributes::default()));
+    let skills = storage(serde_json::json!(Skills::default()));
+
+    save.components.insert(
+        "Attributes".to_string(),
+        serde_json::Value::Array(attributes),
+    );
+    save.components
+        .insert("Skills".to_string(), serde_json::Value::Array(skills));
+
+    Ok(())
+}
+
+/// Saves from before the random streams were saved, where a loaded game rolled on from wherever
+/// the game it was loaded into stood. They start over from the seed instead.
+fn add_rng_streams(save: &mut SaveFile) -> Result<(), String> {
+    let helpers = save
+        .components
+        .get_mut("SerializationHelper")
+        .and_then(|storage| storage.as_array_mut())
+        .into_iter()
+        .flatten()
+        .filter_map(|entity| entity.pointer_mut("/components/0"))
+        .filter_map(|helper| helper.as_object_mut());
+
+    for helper in helpers {
+        let seed = helper
+            .get("seed")
+            .and_then(|seed| seed.as_u64())
+            .unwrap_or_default();
+        let rng_streams = serde_json::to_value(RngStreams::new(seed))
+            .map_err(|error| format!("Unable to add the random streams: {}", error))?;
+
+        helper.insert("rng_streams".to_string(), rng_streams);
     }
+
+    Ok(())
+}
+
+fn pack_map(map: &mut serde_json::Value) -> Result<(), String> {
+    let unreadable =
+        |error: serde_json::Error| format!("The save has an unreadable map: {}", error);
+
+    for field in ["revealed_tiles", "visible_tiles", "blocked"] {
+        let bits: Vec<bool> = serde_json::from_value(map[field].take()).map_err(unreadable)?;
+
+        map[field] = serde_json::json!([bits.len(), tile_encoding::pack_bits(&bits)]);
+    }
+
+    let tiles: Vec<TileType> = serde_json::from_value(map["tiles"].take()).map_err(unreadable)?;
+
+    map["tiles"] = serde_json::to_value(tile_encoding::run_lengths(&tiles)).map_err(unreadable)?;
+
+    Ok(())
+}
+
+/// The marker and component of every entity that has one in the named storage
+fn saved_entities<'a>(
+    components: &'a SavedComponents,
+    name: &str,
+) -> impl Iterator<Item = (&'a serde_json::Value, &'a serde_json::Value)> {
+    components
+        .get(name)
+        .and_then(|storage| storage.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|entity| Some((entity.get("marker")?, entity.pointer("/components/0")?)))
+        .filter(|(_, component)| !component.is_null())
+}
+
+/// Looks the name up in a table a migration keeps of the raws as they were when it was written,
+/// so that the same save upgrades the same way however the raws change later
+fn frozen<T: Copy>(table: &[(&str, T)], name: &str) -> Option<T> {
+    table
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|(_, value)| *value)
 }
@@ -0,0 +1,434 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::fs;
+use std::str::FromStr;
+
+use specs::prelude::*;
+
+use crate::components::{CombatStats, InBackpack, Item, Position};
+use crate::dungeon::MasterDungeonMap;
+use crate::map::Map;
+use crate::render_order::RenderOrder;
+use crate::saveload_system::{self, SaveFile};
+use crate::{mapgen, raws, State};
+
+/// Fields of saved components that hold an entity, as `(component, field)`
+const ENTITY_REFERENCES: [(&str, &str); 9] = [
+    ("WantsToMelee", "target"),
+    ("InBackpack", "owner"),
+    ("WantsToPickupItem", "collected_by"),
+    ("WantsToPickupItem", "item"),
+    ("WantsToUseItem", "item"),
+    ("WantsToDropItem", "item"),
+    ("Equipped", "owner"),
+    ("WantsToRemoveItem", "item"),
+    ("Provoked", "by"),
+];
+
+pub const USAGE: &str = "Usage: savetool <save> entities
+       savetool <save> map [--depth <number>]
+       savetool <save> validate
+       savetool <save> edit [--out <file>] [--script <file>] [<edit>...]
+Edits: \"hp <number>\", \"give <item>\", \"depth <number>\"";
+
+/// What `savetool` does with the save, read from its command line
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct SavetoolOptions {
+    pub path: String,
+    pub command: SavetoolCommand,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum SavetoolCommand {
+    /// Lists every entity with its components
+    Entities,
+    /// Prints the current level, or a visited one
+    Map { depth: Option<i32> },
+    /// Checks that the references between entities hold
+    Validate,
+    /// Applies the edits from the command line, then the ones in `script`, and writes the save
+    /// to `out` (or back to where it was read from)
+    Edit {
+        edits: Vec<SaveEdit>,
+        script: Option<String>,
+        out: Option<String>,
+    },
+}
+
+/// A change to a save, written the way the `edit` command takes it
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum SaveEdit {
+    /// Sets the hit points of the player, raising the maximum if they are above it
+    Hp(i32),
+    /// Puts an item from the raws into the player's backpack
+    Give(String),
+    /// Takes the player to the level at this depth, visited or not
+    Depth(i32),
+}
+
+impl FromStr for SaveEdit {
+    type Err = String;
+
+    fn from_str(edit: &str) -> Result<Self, Self::Err> {
+        let (name, value) = edit.trim().split_once(' ').unwrap_or((edit.trim(), ""));
+        let value = value.trim();
+        let number = || {
+            value
+                .parse::<i32>()
+                .map_err(|_| format!("Expected a number after {}, got '{}'", name, value))
+        };
+
+        match name {
+            "hp" if number()? >= 1 => Ok(SaveEdit::Hp(number()?)),
+            "hp" => Err("The hit points have to be positive".to_string()),
+            "give" if !value.is_empty() => Ok(SaveEdit::Give(value.to_string())),
+            "give" => Err("Expected an item after give".to_string()),
+            "depth" if number()? >= 1 => Ok(SaveEdit::Depth(number()?)),
+            "depth" => Err("The depth has to be positive".to_string()),
+            _ => Err(format!("Unknown edit '{}'", edit)),
+        }
+    }
+}
+
+pub fn parse_arguments(args: &[String]) -> Result<SavetoolOptions, String> {
+    let mut args = args.iter().skip(1);
+    let path = args.next().ok_or_else(|| USAGE.to_string())?.clone();
+    let command = args.next().ok_or_else(|| USAGE.to_string())?;
+
+    let command = match command.as_str() {
+        "entities" => SavetoolCommand::Entities,
+        "validate" => SavetoolCommand::Validate,
+        "map" => {
+            let depth =
+                match args.next().map(String::as_str) {
+                    None => None,
+                    Some("--depth") => {
+                        let value = args
+                            .next()
+                            .ok_or_else(|| "Expected a number after --depth".to_string())?;
+
+                        Some(value.parse::<i32>().map_err(|_| {
+                            format!("Expected a number after --depth, got '{}'", value)
+                        })?)
+                    }
+                    Some(arg) => return Err(format!("Unknown argument '{}'\n{}", arg, USAGE)),
+                };
+
+            SavetoolCommand::Map { depth }
+        }
+        "edit" => {
+            let mut edits = Vec::new();
+            let mut script = None;
+            let mut out = None;
+
+            while let Some(arg) = args.next() {
+                let mut value = || {
+                    args.next()
+                        .cloned()
+                        .ok_or_else(|| format!("Expected a file after {}", arg))
+                };
+
+                match arg.as_str() {
+                    "--out" => out = Some(value()?),
+                    "--script" => script = Some(value()?),
+                    _ => edits.push(arg.parse()?),
+                }
+            }
+
+            SavetoolCommand::Edit { edits, script, out }
+        }
+        _ => return Err(format!("Unknown command '{}'\n{}", command, USAGE)),
+    };
+
+    Ok(SavetoolOptions { path, command })
+}
+
+/// Reads edits one per line, `#` starts a comment
+pub fn parse_edit_script(script: &str) -> Result<Vec<SaveEdit>, String> {
+    script
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| {
+            line.parse::<SaveEdit>()
+                .map_err(|error| format!("Line {}: {}", i + 1, error))
+        })
+        .collect()
+}
+
+/// Runs the command, the output is what `savetool` prints. A save that does not validate is an
+/// error with the list of problems.
+pub fn run(options: &SavetoolOptions) -> Result<String, String> {
+    let data = fs::read(&options.path)
+        .map_err(|error| format!("Unable to read {}: {}", options.path, error))?;
+    let save = saveload_system::parse_save(&data)?;
+
+    match &options.command {
+        SavetoolCommand::Entities => Ok(list_entities(&save)),
+        SavetoolCommand::Map { depth } => render_level(&save, *depth),
+        SavetoolCommand::Validate => {
+            let problems = validate(&save);
+
+            if problems.is_empty() {
+                Ok("The save is valid\n".to_string())
+            } else {
+                Err(problems.join("\n"))
+            }
+        }
+        SavetoolCommand::Edit { edits, script, out } => {
+            let mut edits = edits.clone();
+
+            if let Some(script) = script {
+                let script = fs::read_to_string(script)
+                    .map_err(|error| format!("Unable to read script {}: {}", script, error))?;
+
+                edits.extend(parse_edit_script(&script)?);
+            }
+
+            let out = out.as_deref().unwrap_or(&options.path);
+
+            edit(&options.path, out, &edits)?;
+
+            Ok(format!(
+                "Edits applied: {}, written to {}\n",
+                edits.len(),
+                out
+            ))
+        }
+    }
+}
+
+/// Every entity with a component, by its saved id
+fn entities(save: &SaveFile) -> BTreeMap<u64, Vec<(&str, &Value)>> {
+    let mut entities: BTreeMap<u64, Vec<(&str, &Value)>> = BTreeMap::new();
+
+    for (name, storage) in save.components.iter() {
+        for entity in storage.as_array().into_iter().flatten() {
+            let marker = entity.pointer("/marker/0").and_then(Value::as_u64);
+            let component = entity.pointer("/components/0").filter(|c| !c.is_null());
+
+            if let (Some(marker), Some(component)) = (marker, component) {
+                entities
+                    .entry(marker)
+                    .or_default()
+                    .push((name.as_str(), component));
+            }
+        }
+    }
+
+    entities
+}
+
+fn component<'a>(components: &[(&str, &'a Value)], name: &str) -> Option<&'a Value> {
+    components
+        .iter()
+        .find(|(component_name, _)| *component_name == name)
+        .map(|(_, component)| *component)
+}
+
+pub fn list_entities(save: &SaveFile) -> String {
+    let mut output = String::new();
+
+    for (id, components) in entities(save) {
+        let name = component(&components, "Name")
+            .and_then(|name| name["name"].as_str())
+            .unwrap_or("");
+
+        // Writing to a `String` cannot fail
+        writeln!(output, "Entity {} {}", id, name).unwrap();
+
+        for (component_name, value) in components {
+            match value {
+                // The map and the master dungeon are printed by `map`
+                _ if component_name == "SerializationHelper" => {
+                    writeln!(output, "  {}", component_name).unwrap()
+                }
+                Value::Object(fields) if fields.is_empty() => {
+                    writeln!(output, "  {}", component_name).unwrap()
+                }
+                _ => writeln!(output, "  {} {}", component_name, value).unwrap(),
+            }
+        }
+    }
+
+    output
+}
+
+/// The problems with references between the saved entities, empty for a valid save
+pub fn validate(save: &SaveFile) -> Vec<String> {
+    let entities = entities(save);
+    let ids: BTreeSet<u64> = entities.keys().copied().collect();
+    let mut problems = Vec::new();
+
+    let players = entities
+        .values()
+        .filter(|components| component(components, "Player").is_some())
+        .count();
+
+    if players != 1 {
+        problems.push(format!("There are {} players instead of 1", players));
+    }
+
+    let helpers = entities
+        .values()
+        .filter(|components| component(components, "SerializationHelper").is_some())
+        .count();
+
+    if helpers != 1 {
+        problems.push(format!("There are {} maps instead of 1", helpers));
+    }
+
+    for (id, components) in entities.iter() {
+        for (component_name, field) in ENTITY_REFERENCES {
+            let Some(value) = component(components, component_name) else {
+                continue;
+            };
+
+            match value[field].get(0).and_then(Value::as_u64) {
+                Some(target) if ids.contains(&target) => {}
+                Some(target) => problems.push(format!(
+                    "Entity {}: {}.{} points at entity {}, which is not in the save",
+                    id, component_name, field, target
+                )),
+                None => problems.push(format!(
+                    "Entity {}: {}.{} is not an entity",
+                    id, component_name, field
+                )),
+            }
+        }
+
+        let carried = component(components, "InBackpack").is_some()
+            || component(components, "Equipped").is_some();
+
+        if carried && component(components, "Position").is_some() {
+            problems.push(format!("Entity {} is carried but also lies on the map", id));
+        }
+    }
+
+    problems
+}
+
+/// Draws the level at `depth` (the current one if `None`) with the saved entities on it
+pub fn render_level(save: &SaveFile, depth: Option<i32>) -> Result<String, String> {
+    let entities = entities(save);
+    let helper = entities
+        .values()
+        .find_map(|components| component(components, "SerializationHelper"))
+        .ok_or_else(|| "The save has no map".to_string())?;
+    let unreadable =
+        |error: serde_json::Error| format!("The save has an unreadable map: {}", error);
+    let current: Map = serde_json::from_value(helper["map"].clone()).map_err(unreadable)?;
+
+    let (map, on_level) = match depth {
+        None => (current, "Position"),
+        Some(depth) if depth == current.depth => (current, "Position"),
+        Some(depth) => {
+            let dungeon: MasterDungeonMap =
+                serde_json::from_value(helper["dungeon"].clone()).map_err(unreadable)?;
+            let (map, _) = dungeon
+                .get_level(depth)
+                .ok_or_else(|| format!("The player has not been on depth {}", depth))?;
+
+            (map, "OtherLevelPosition")
+        }
+    };
+
+    let mut glyphs: Vec<(RenderOrder, usize, char)> = entities
+        .values()
+        .filter_map(|components| {
+            let position = component(components, on_level)?;
+            let renderable = component(components, "Renderable")?;
+
+            if on_level == "OtherLevelPosition" && position["depth"] != map.depth {
+                return None;
+            }
+
+            let x = position["x"].as_i64()? as i32;
+            let y = position["y"].as_i64()? as i32;
+            let glyph = renderable["glyph"].as_u64()? as u8;
+            let order = RenderOrder::deserialize(&renderable["render_order"]).ok()?;
+
+            Some((order, map.xy_idx(x, y), rltk::to_char(glyph)))
+        })
+        .collect();
+
+    // Whatever is drawn last is on top, like `camera::render_camera`
+    glyphs.sort_by_key(|(order, _, _)| std::cmp::Reverse(*order));
+
+    let mut output = mapgen::render_map(&map, None, &[]);
+    let row_length = map.width as usize + 1;
+
+    for (_, idx, glyph) in glyphs {
+        let row = idx / map.width as usize;
+        let column = idx % map.width as usize;
+        let offset = row * row_length + column;
+
+        if offset < output.len() && output.is_char_boundary(offset) {
+            output.replace_range(offset..offset + 1, &glyph.to_string());
+        }
+    }
+
+    Ok(format!("Depth: {}\n{}", map.depth, output))
+}
+
+/// Loads the save into a game, applies the edits and saves it to `out` with the same encoding
+pub fn edit(path: &str, out: &str, edits: &[SaveEdit]) -> Result<(), String> {
+    let encoding = saveload_system::encoding_of(
+        &fs::read(path).map_err(|error| format!("Unable to read {}: {}", path, error))?,
+    );
+    let mut state = State::new();
+
+    saveload_system::load_game_from(&mut state.ecs, path)?;
+
+    for edit in edits {
+        apply_edit(&mut state, edit)?;
+    }
+
+    saveload_system::save_game_to(&mut state.ecs, out, encoding)
+}
+
+fn apply_edit(state: &mut State, edit: &SaveEdit) -> Result<(), String> {
+    let player = *state.ecs.fetch::<Entity>();
+
+    match edit {
+        SaveEdit::Hp(hp) => {
+            let mut stats = state.ecs.write_storage::<CombatStats>();
+            let stats = stats
+                .get_mut(player)
+                .ok_or_else(|| "The player has no hit points".to_string())?;
+
+            if *hp < 1 {
+                return Err("The hit points have to be positive".to_string());
+            }
+
+            stats.hp = *hp;
+            stats.max_hp = stats.max_hp.max(*hp);
+        }
+        SaveEdit::Give(name) => {
+            let item = raws::raws()
+                .spawn_named_entity(&mut state.ecs, name, 0, 0)
+                .ok_or_else(|| format!("There is no '{}' in the raws", name))?;
+
+            if state.ecs.read_storage::<Item>().get(item).is_none() {
+                state
+                    .ecs
+                    .delete_entity(item)
+                    .expect("Unable to delete entity");
+
+                return Err(format!("'{}' is not an item", name));
+            }
+
+            state.ecs.write_storage::<Position>().remove(item);
+            state
+                .ecs
+                .write_storage::<InBackpack>()
+                .insert(item, InBackpack { owner: player })
+                .expect("Unable to insert backpack entry");
+        }
+        SaveEdit::Depth(depth) => state.travel_to_depth(*depth),
+    }
+
+    Ok(())
+}
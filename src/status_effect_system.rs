@@ -0,0 +1,145 @@
+use specs::prelude::*;
+
+use crate::components::*;
+use crate::game_log::GameLog;
+use crate::rng::StatusRng;
+
+/// The damage every dose of poison does each turn
+pub const POISON_DAMAGE: i32 = 1;
+/// The hit points regeneration gives back each turn
+pub const REGENERATION_HEAL: i32 = 1;
+/// The chance in percent that a confused player moves somewhere else than they meant to
+const STUMBLE_PERCENT: i32 = 50;
+
+/// Puts the effect on the target, stacking with or refreshing the ones already there
+pub fn apply_status(
+    statuses: &mut WriteStorage<StatusEffects>,
+    target: Entity,
+    effect: StatusEffect,
+) {
+    if let Some(target_statuses) = statuses.get_mut(target) {
+        target_statuses.add(effect);
+    } else {
+        statuses
+            .insert(
+                target,
+                StatusEffects {
+                    effects: vec![effect],
+                },
+            )
+            .expect("Unable to insert status");
+    }
+}
+
+pub fn has_status(ecs: &World, entity: Entity, kind: StatusKind) -> bool {
+    ecs.read_storage::<StatusEffects>()
+        .get(entity)
+        .is_some_and(|statuses| statuses.has(kind))
+}
+
+/// Where a move of the player really goes, which is not always where they meant when confused
+pub fn stumble(ecs: &World, delta_x: i32, delta_y: i32) -> (i32, i32) {
+    let player_entity = *ecs.fetch::<Entity>();
+
+    if !has_status(ecs, player_entity, StatusKind::Confusion) {
+        return (delta_x, delta_y);
+    }
+
+    let mut rng = ecs.fetch_mut::<StatusRng>();
+
+    if rng.roll_dice(1, 100) > STUMBLE_PERCENT {
+        return (delta_x, delta_y);
+    }
+
+    ecs.fetch_mut::<GameLog>()
+        .entries
+        .push("You stumble around in confusion.".to_string());
+
+    loop {
+        let (x, y) = (rng.range(-1, 2), rng.range(-1, 2));
+
+        if x != 0 || y != 0 {
+            return (x, y);
+        }
+    }
+}
+
+/// One tick of the clock for every status effect on the level: poison hurts, regeneration heals,
+/// and every effect gets a turn closer to wearing off
+pub struct StatusEffectSystem {}
+
+impl<'a> System<'a> for StatusEffectSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, StatusEffects>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, SufferDamage>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            mut game_log,
+            positions,
+            mut statuses,
+            mut combat_stats,
+            mut suffer_damage,
+        ) = data;
+
+        let mut wore_off = Vec::new();
+
+        // Only the current level ticks, like the initiative
+        for (entity, _position, entity_statuses) in (&entities, &positions, &mut statuses).join() {
+            for effect in entity_statuses.effects.iter_mut() {
+                match effect.kind {
+                    StatusKind::Poison => {
+                        SufferDamage::new_damage(
+                            &mut suffer_damage,
+                            entity,
+                            POISON_DAMAGE,
+                            DamageType::Poison,
+                        );
+                    }
+                    StatusKind::Regeneration => {
+                        if let Some(stats) = combat_stats.get_mut(entity).filter(|s| s.hp > 0) {
+                            stats.hp = i32::min(stats.hp + REGENERATION_HEAL, stats.max_hp);
+                        }
+                    }
+                    _ => {}
+                }
+
+                effect.turns -= 1;
+            }
+
+            let before: Vec<StatusKind> = entity_statuses
+                .effects
+                .iter()
+                .map(|effect| effect.kind)
+                .collect();
+
+            entity_statuses.effects.retain(|effect| effect.turns > 0);
+
+            for kind in before {
+                if !entity_statuses.has(kind) && !wore_off.contains(&(entity, kind)) {
+                    wore_off.push((entity, kind));
+                }
+            }
+        }
+
+        for (entity, kind) in wore_off {
+            if statuses.get(entity).is_some_and(|s| s.effects.is_empty()) {
+                statuses.remove(entity);
+            }
+
+            if entity == *player_entity {
+                game_log
+                    .entries
+                    .push(format!("You are no longer {}.", kind.name().to_lowercase()));
+            }
+        }
+    }
+}
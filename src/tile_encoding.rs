@@ -0,0 +1,98 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::map::TileType;
+
+/// More tiles than any map has, a save that claims more is broken and is not unpacked
+pub const MAX_TILES: usize = 1 << 22;
+
+fn too_many_tiles<E: serde::de::Error>(len: usize) -> E {
+    E::custom(format!(
+        "{} tiles are more than a map can have ({})",
+        len, MAX_TILES
+    ))
+}
+
+/// A `Vec<bool>` of the map as its length and 8 tiles to a byte, for `#[serde(with = ...)]`
+pub mod bit_packed {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(bits: &[bool], serializer: S) -> Result<S::Ok, S::Error> {
+        (bits.len(), pack_bits(bits)).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<bool>, D::Error> {
+        let (len, bytes) = <(usize, Vec<u8>)>::deserialize(deserializer)?;
+
+        if len > MAX_TILES {
+            return Err(too_many_tiles(len));
+        }
+
+        if bytes.len() * 8 < len {
+            return Err(serde::de::Error::custom(format!(
+                "{} bytes cannot hold {} tiles",
+                bytes.len(),
+                len
+            )));
+        }
+
+        Ok(unpack_bits(&bytes, len))
+    }
+}
+
+/// A `Vec<TileType>` as runs of the same tile, `[tile, count]`
+pub mod run_length {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(tiles: &[TileType], serializer: S) -> Result<S::Ok, S::Error> {
+        run_lengths(tiles).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<TileType>, D::Error> {
+        let runs = <Vec<(TileType, usize)>>::deserialize(deserializer)?;
+        let len = runs
+            .iter()
+            .try_fold(0usize, |len, (_, count)| len.checked_add(*count))
+            .unwrap_or(usize::MAX);
+
+        if len > MAX_TILES {
+            return Err(too_many_tiles(len));
+        }
+
+        Ok(runs
+            .into_iter()
+            .flat_map(|(tile, count)| std::iter::repeat_n(tile, count))
+            .collect())
+    }
+}
+
+pub fn pack_bits(bits: &[bool]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0, |byte, (i, bit)| byte | (u8::from(*bit) << i))
+        })
+        .collect()
+}
+
+pub fn unpack_bits(bytes: &[u8], len: usize) -> Vec<bool> {
+    (0..len)
+        .map(|i| bytes[i / 8] & (1 << (i % 8)) != 0)
+        .collect()
+}
+
+pub fn run_lengths(tiles: &[TileType]) -> Vec<(TileType, usize)> {
+    let mut runs: Vec<(TileType, usize)> = Vec::new();
+
+    for tile in tiles {
+        match runs.last_mut() {
+            Some((last, count)) if last == tile => *count += 1,
+            _ => runs.push((*tile, 1)),
+        }
+    }
+
+    runs
+}
@@ -73,7 +73,12 @@ impl<'a> System<'a> for TriggerSystem {
                         200.0,
                     );
 
-                    SufferDamage::new_damage(&mut inflict_damage, moved_entity, damage.damage);
+                    SufferDamage::new_damage(
+                        &mut inflict_damage,
+                        moved_entity,
+                        damage.damage,
+                        damage.damage_type,
+                    );
                 }
 
                 // If it is single activation, it needs to be removed
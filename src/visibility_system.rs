@@ -4,6 +4,7 @@ use specs::prelude::*;
 use crate::components::*;
 use crate::game_log::GameLog;
 use crate::map::Map;
+use crate::rng::PerceptionRng;
 
 pub struct VisibilitySystem {}
 
@@ -15,7 +16,7 @@ impl<'a> System<'a> for VisibilitySystem {
         WriteStorage<'a, Position>,
         ReadStorage<'a, Player>,
         WriteStorage<'a, Hidden>,
-        WriteExpect<'a, rltk::RandomNumberGenerator>,
+        WriteExpect<'a, PerceptionRng>,
         WriteExpect<'a, GameLog>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, BlocksVisibility>,
@@ -0,0 +1,134 @@
+mod common;
+
+use rltk::Point;
+use roguelike_tutorial::components::*;
+use roguelike_tutorial::headless::HeadlessGame;
+use roguelike_tutorial::map::{Map, TileType};
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::saveload_system::{self, SaveEncoding};
+use specs::prelude::*;
+
+fn ai_state(game: &HeadlessGame, entity: Entity) -> AiState {
+    let ai = game.state.ecs.read_storage::<Ai>();
+
+    ai.get(entity).unwrap().state
+}
+
+fn set_ai_state(game: &mut HeadlessGame, entity: Entity, state: AiState) {
+    let mut ai = game.state.ecs.write_storage::<Ai>();
+
+    ai.get_mut(entity).unwrap().state = state;
+}
+
+/// Puts a wall along the column `x` of the open floor around the player
+fn wall_off_column(game: &mut HeadlessGame, x: i32) {
+    let pos = common::player_position(game);
+
+    {
+        let mut map = game.state.ecs.write_resource::<Map>();
+
+        for y in pos.y - 3..=pos.y + 3 {
+            let idx = map.xy_idx(x, y);
+
+            map.tiles[idx] = TileType::Wall;
+        }
+    }
+
+    common::refresh(game);
+}
+
+fn distance(a: Point, b: Point) -> f32 {
+    rltk::DistanceAlg::Pythagoras.distance2d(a, b)
+}
+
+#[test]
+fn a_monster_that_sees_the_player_chases_them() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let goblin = common::spawn(&mut game, "Goblin", pos.x + 3, pos.y);
+
+    assert!(matches!(ai_state(&game, goblin), AiState::Idle { .. }));
+
+    game.apply(PlayerCommand::SkipTurn);
+
+    assert_eq!(ai_state(&game, goblin), AiState::Chase { last_seen: pos });
+    assert!(distance(common::position_of(&game, goblin), pos) < 3.0);
+}
+
+#[test]
+fn a_monster_that_loses_sight_of_the_player_searches_where_it_last_saw_them() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let goblin = common::spawn(&mut game, "Goblin", pos.x + 3, pos.y);
+
+    wall_off_column(&mut game, pos.x + 2);
+    set_ai_state(&mut game, goblin, AiState::Chase { last_seen: pos });
+    game.apply(PlayerCommand::SkipTurn);
+
+    assert!(matches!(
+        ai_state(&game, goblin),
+        AiState::Search { last_seen, .. } if last_seen == pos
+    ));
+}
+
+#[test]
+fn a_badly_hurt_monster_flees() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let goblin = common::spawn(&mut game, "Goblin", pos.x + 1, pos.y);
+
+    {
+        let mut stats = game.state.ecs.write_storage::<CombatStats>();
+
+        stats.get_mut(goblin).unwrap().hp = 1;
+    }
+
+    game.apply(PlayerCommand::SkipTurn);
+
+    assert_eq!(ai_state(&game, goblin), AiState::Flee);
+    assert!(distance(common::position_of(&game, goblin), pos) > 1.5);
+    assert_eq!(common::attacks(&game, "Goblin", "Player"), 0);
+}
+
+#[test]
+fn an_idle_monster_wanders_off() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let goblin = common::spawn(&mut game, "Goblin", pos.x + 3, pos.y);
+
+    wall_off_column(&mut game, pos.x + 2);
+    set_ai_state(&mut game, goblin, AiState::Idle { turns: 0 });
+
+    let mut wandered = false;
+
+    for _ in 0..5 {
+        game.apply(PlayerCommand::SkipTurn);
+        wandered |= matches!(ai_state(&game, goblin), AiState::Wander { .. });
+    }
+
+    assert!(wandered);
+}
+
+#[test]
+fn the_ai_state_is_saved() {
+    let path = &common::temp_path("ai_state.sav");
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let goblin = common::spawn(&mut game, "Goblin", pos.x + 3, pos.y);
+    let state = AiState::Search {
+        last_seen: Point::new(pos.x - 1, pos.y),
+        turns: 4,
+    };
+
+    set_ai_state(&mut game, goblin, state);
+    saveload_system::save_game_to(&mut game.state.ecs, path, SaveEncoding::Binary).unwrap();
+    game.state.start_new_game(1);
+    saveload_system::load_game_from(&mut game.state.ecs, path).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    let ai = game.state.ecs.read_storage::<Ai>();
+    let monsters = game.state.ecs.read_storage::<Monster>();
+    let states: Vec<AiState> = (&ai, &monsters).join().map(|(ai, _)| ai.state).collect();
+
+    assert_eq!(states, vec![state]);
+}
@@ -0,0 +1,234 @@
+mod common;
+
+use roguelike_tutorial::attributes;
+use roguelike_tutorial::components::*;
+use roguelike_tutorial::headless::HeadlessGame;
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::saveload_system::{self, SaveEncoding};
+use roguelike_tutorial::{RunState, TurnCount};
+use specs::prelude::*;
+
+fn set_attributes(game: &mut HeadlessGame, attributes: Attributes) {
+    let player = common::player_entity(game);
+
+    game.state
+        .ecs
+        .write_storage::<Attributes>()
+        .insert(player, attributes)
+        .unwrap();
+}
+
+fn skills_of(game: &HeadlessGame) -> Skills {
+    let player = common::player_entity(game);
+
+    game.state
+        .ecs
+        .read_storage::<Skills>()
+        .get(player)
+        .cloned()
+        .unwrap()
+}
+
+#[test]
+fn every_two_points_from_average_are_worth_one() {
+    assert_eq!(attributes::bonus(10), 0);
+    assert_eq!(attributes::bonus(11), 0);
+    assert_eq!(attributes::bonus(12), 1);
+    assert_eq!(attributes::bonus(9), -1);
+    assert_eq!(attributes::bonus(7), -2);
+
+    let hardy = Attributes {
+        fitness: 14,
+        intelligence: 6,
+        ..Attributes::default()
+    };
+
+    assert_eq!(attributes::max_hp(&hardy), 30 + 10);
+    assert_eq!(attributes::vision_range(&hardy), 8 - 2);
+    assert_eq!(attributes::hunger_duration(Some(&hardy)), 200 + 40);
+    assert_eq!(attributes::hunger_duration(None), 200);
+}
+
+#[test]
+fn the_player_starts_average_and_unskilled() {
+    let game = common::isolated_game();
+    let player = common::player_entity(&game);
+
+    assert_eq!(
+        game.state.ecs.read_storage::<Attributes>().get(player),
+        Some(&Attributes::default())
+    );
+    assert_eq!(skills_of(&game), Skills::default());
+    assert_eq!(common::player_stats(&game).max_hp, 30);
+    assert_eq!(
+        game.state
+            .ecs
+            .read_storage::<Viewshed>()
+            .get(player)
+            .unwrap()
+            .range,
+        8
+    );
+}
+
+#[test]
+fn fitness_keeps_hunger_away_for_longer() {
+    let mut game = common::isolated_game();
+    let player = common::player_entity(&game);
+
+    set_attributes(
+        &mut game,
+        Attributes {
+            fitness: 14,
+            ..Attributes::default()
+        },
+    );
+    game.state
+        .ecs
+        .write_storage::<HungerClock>()
+        .insert(
+            player,
+            HungerClock {
+                state: HungerState::Normal,
+                duration: 1,
+            },
+        )
+        .unwrap();
+    game.apply(PlayerCommand::SkipTurn);
+
+    let clocks = game.state.ecs.read_storage::<HungerClock>();
+    let clock = clocks.get(player).unwrap();
+
+    assert!(clock.state == HungerState::Hungry);
+    assert_eq!(clock.duration, 240);
+}
+
+#[test]
+fn intelligence_and_magic_strengthen_damaging_scrolls() {
+    let mut game = common::isolated_game();
+    let player = common::player_entity(&game);
+    let pos = common::player_position(&game);
+    let orc = common::spawn(&mut game, "Orc", pos.x + 3, pos.y);
+
+    common::set_hp(&mut game, orc, 100);
+    set_attributes(
+        &mut game,
+        Attributes {
+            intelligence: 14,
+            ..Attributes::default()
+        },
+    );
+    game.state
+        .ecs
+        .write_storage::<Skills>()
+        .get_mut(player)
+        .unwrap()
+        .magic
+        .level = 1;
+    common::use_item_on(&mut game, "Magic Missile Scroll", orc);
+
+    assert_eq!(common::hp_of(&game, orc), 100 - (8 + 2 + 1));
+    assert_eq!(skills_of(&game).magic.practice, 1);
+}
+
+#[test]
+fn fighting_raises_melee_and_defense() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let guard = common::spawn(&mut game, "Guard", pos.x + 1, pos.y);
+
+    common::set_hp(&mut game, guard, 1000);
+
+    for _ in 0..10 {
+        common::set_player_hp(&mut game, 1000);
+        game.apply(PlayerCommand::Move {
+            delta_x: 1,
+            delta_y: 0,
+        });
+    }
+
+    assert_eq!(common::attacks(&game, "Player", "Guard"), 10);
+    assert_eq!(skills_of(&game).melee.level, 1);
+    assert!(common::log_contains(&game, "Your melee skill rises to 1."));
+
+    // The guard fights back once attacked
+    for _ in 0..40 {
+        if common::attacks(&game, "Guard", "Player") >= 10 {
+            break;
+        }
+
+        common::set_player_hp(&mut game, 1000);
+        game.apply(PlayerCommand::SkipTurn);
+    }
+
+    assert!(common::attacks(&game, "Guard", "Player") >= 10);
+    assert_eq!(skills_of(&game).defense.level, 1);
+}
+
+#[test]
+fn the_character_screen_shows_everything_and_takes_no_time() {
+    let mut game = common::isolated_game();
+    let turns = game.state.ecs.fetch::<TurnCount>().0;
+
+    set_attributes(
+        &mut game,
+        Attributes {
+            might: 13,
+            ..Attributes::default()
+        },
+    );
+
+    assert_eq!(
+        game.apply(PlayerCommand::ShowCharacter),
+        RunState::ShowCharacter
+    );
+
+    let summary = game.summary();
+
+    assert!(summary.contains("Level 1 (XP 0 / 20)"));
+    assert!(summary.contains("Might         13 (+1)"));
+    assert!(summary.contains("Intelligence  10 (+0)"));
+    assert!(summary.contains("Magic          0 (0 / 10)"));
+    assert_eq!(game.apply(PlayerCommand::Cancel), RunState::AwaitingInput);
+    assert!(!game.summary().contains("Character:"));
+    assert_eq!(game.state.ecs.fetch::<TurnCount>().0, turns);
+}
+
+#[test]
+fn attributes_and_skills_are_saved() {
+    let path = &common::temp_path("attributes.sav");
+    let mut game = common::isolated_game();
+    let player = common::player_entity(&game);
+    let attributes = Attributes {
+        might: 14,
+        fitness: 9,
+        quickness: 12,
+        intelligence: 11,
+    };
+
+    set_attributes(&mut game, attributes.clone());
+    game.state
+        .ecs
+        .write_storage::<Skills>()
+        .get_mut(player)
+        .unwrap()
+        .defense = Skill {
+        level: 2,
+        practice: 7,
+    };
+
+    let skills = skills_of(&game);
+
+    saveload_system::save_game_to(&mut game.state.ecs, path, SaveEncoding::Binary).unwrap();
+    game.state.start_new_game(1);
+    saveload_system::load_game_from(&mut game.state.ecs, path).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    let player = common::player_entity(&game);
+
+    assert_eq!(
+        game.state.ecs.read_storage::<Attributes>().get(player),
+        Some(&attributes)
+    );
+    assert_eq!(skills_of(&game), skills);
+}
@@ -0,0 +1,121 @@
+use roguelike_tutorial::map::TileType;
+use roguelike_tutorial::map_builders::chain_config::{self, BuilderChains};
+
+const MINIMAL_CHAINS: &str = r#"{
+    "chains": [
+        {
+            "name": "Caves",
+            "weights": [ { "min_depth": 1, "max_depth": null, "weight": 1 } ],
+            "starter": "CellularAutomata",
+            "builders": [
+                { "AreaStartingPosition": { "x": "Center", "y": "Center" } },
+                "CullUnreachable",
+                "DistantExit"
+            ]
+        }
+    ]
+}"#;
+
+#[test]
+fn the_shipped_builder_chains_load() {
+    let chains = chain_config::load_builder_chains().unwrap();
+
+    for depth in 1..=10 {
+        assert!(chains.chains.iter().any(|chain| chain.weight(depth) > 0));
+    }
+}
+
+#[test]
+fn every_shipped_chain_builds_a_playable_level() {
+    let chains = chain_config::load_builder_chains().unwrap();
+
+    for (i, chain) in chains.chains.iter().enumerate() {
+        let mut rng = rltk::RandomNumberGenerator::seeded(i as u64);
+        let spawn_rng = rltk::RandomNumberGenerator::seeded(i as u64);
+        let mut builder = chains.build(chain, 1, &mut rng, spawn_rng, 80, 50);
+
+        builder.build_map(&mut rng);
+
+        let build_data = &builder.build_data;
+
+        assert!(build_data.starting_position.is_some(), "{}", chain.name);
+        assert!(
+            build_data.map.tiles.contains(&TileType::DownStairs),
+            "{}",
+            chain.name
+        );
+    }
+}
+
+#[test]
+fn chains_are_picked_by_depth() {
+    let chains = MINIMAL_CHAINS.replace(
+        r#"{ "min_depth": 1, "max_depth": null, "weight": 1 }"#,
+        r#"{ "min_depth": 1, "max_depth": 2, "weight": 1 }"#,
+    );
+    let chains = chains.replace(
+        r#""chains": ["#,
+        r#""chains": [
+        {
+            "name": "Deep caves",
+            "weights": [ { "min_depth": 3, "max_depth": null, "weight": 1 } ],
+            "starter": "CellularAutomata",
+            "builders": [
+                { "AreaStartingPosition": { "x": "Center", "y": "Center" } },
+                "DistantExit"
+            ]
+        },"#,
+    );
+    let chains = BuilderChains::load(&chains).unwrap();
+    let mut rng = rltk::RandomNumberGenerator::seeded(1);
+
+    for _ in 0..10 {
+        assert_eq!(chains.pick(2, &mut rng).name, "Caves");
+        assert_eq!(chains.pick(3, &mut rng).name, "Deep caves");
+    }
+}
+
+#[test]
+fn a_depth_without_chains_is_a_load_error() {
+    let chains = MINIMAL_CHAINS.replace(
+        r#""min_depth": 1, "max_depth": null"#,
+        r#""min_depth": 1, "max_depth": 4"#,
+    );
+
+    let error = BuilderChains::load(&chains).err().unwrap();
+
+    assert!(error.contains("depth 5"), "{}", error);
+}
+
+#[test]
+fn builders_in_the_wrong_order_are_load_errors() {
+    let without_start = MINIMAL_CHAINS.replace(
+        r#"{ "AreaStartingPosition": { "x": "Center", "y": "Center" } },"#,
+        "",
+    );
+    let rooms_without_rooms = MINIMAL_CHAINS.replace(r#""CullUnreachable","#, r#""RoomDrawer","#);
+    let without_exit = MINIMAL_CHAINS.replace(r#""DistantExit""#, r#""VoronoiSpawning""#);
+
+    for chains in [without_start, rooms_without_rooms, without_exit] {
+        let error = BuilderChains::load(&chains).err().unwrap();
+
+        assert!(error.contains("Caves"), "{}", error);
+    }
+}
+
+#[test]
+fn a_builder_that_might_not_run_does_not_count() {
+    let chains = MINIMAL_CHAINS.replace(
+        r#""DistantExit""#,
+        r#"{ "Chance": { "one_in": 2, "builders": ["DistantExit"] } }"#,
+    );
+
+    assert!(BuilderChains::load(&chains).is_err());
+}
+
+#[test]
+fn a_typo_in_a_builder_is_a_load_error() {
+    let chains = MINIMAL_CHAINS.replace("CullUnreachable", "CullUnreachables");
+
+    assert!(BuilderChains::load(&chains).is_err());
+}
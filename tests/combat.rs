@@ -0,0 +1,113 @@
+mod common;
+
+use roguelike_tutorial::map::{Map, TileType};
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::RunState;
+
+#[test]
+fn attacking_a_monster_damages_it_and_it_strikes_back() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let orc = common::spawn(&mut game, "Orc", pos.x + 1, pos.y);
+
+    // Tough enough that it never gets hurt badly enough to run
+    common::set_hp(&mut game, orc, 100);
+
+    // Either side can miss, so keep swinging until both have drawn blood
+    for _ in 0..40 {
+        game.apply(PlayerCommand::Move {
+            delta_x: 1,
+            delta_y: 0,
+        });
+
+        if common::hp_of(&game, orc) < 100 && common::player_stats(&game).hp < 30 {
+            break;
+        }
+    }
+
+    assert!(common::hp_of(&game, orc) < 100);
+    assert!(common::player_stats(&game).hp < 30);
+    assert_eq!(common::player_position(&game), pos);
+}
+
+#[test]
+fn attacks_can_miss_against_armour() {
+    for seed in 1..=5 {
+        let mut game = common::isolated_game_with_seed(seed);
+        let pos = common::player_position(&game);
+        let guard = common::spawn(&mut game, "Guard", pos.x + 1, pos.y);
+
+        common::set_hp(&mut game, guard, 100);
+
+        for _ in 0..40 {
+            common::set_player_hp(&mut game, 30);
+            game.apply(PlayerCommand::Move {
+                delta_x: 1,
+                delta_y: 0,
+            });
+        }
+
+        assert!(
+            common::log_contains(&game, "Player misses Guard")
+                || common::log_contains(&game, "Player fumbles the attack on Guard"),
+            "seed {}",
+            seed
+        );
+    }
+}
+
+#[test]
+fn killing_a_monster_removes_it() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let goblin = common::spawn(&mut game, "Goblin", pos.x, pos.y + 1);
+
+    // A badly hurt goblin runs, so corner it
+    {
+        let mut map = game.state.ecs.fetch_mut::<Map>();
+
+        for (x, y) in [(-1, 1), (1, 1), (-1, 2), (0, 2), (1, 2)] {
+            let idx = map.xy_idx(pos.x + x, pos.y + y);
+
+            map.tiles[idx] = TileType::Wall;
+        }
+    }
+    common::refresh(&mut game);
+
+    for _ in 0..40 {
+        if !common::is_alive(&game, goblin) {
+            break;
+        }
+
+        common::set_player_hp(&mut game, 30);
+        game.apply(PlayerCommand::Move {
+            delta_x: 0,
+            delta_y: 1,
+        });
+    }
+
+    assert!(!common::is_alive(&game, goblin));
+    assert!(common::log_contains(&game, "Goblin is dead"));
+}
+
+#[test]
+fn the_player_dying_ends_the_game() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+
+    common::spawn(&mut game, "Orc", pos.x - 1, pos.y);
+    common::set_player_hp(&mut game, 1);
+
+    let mut run_state = RunState::AwaitingInput;
+
+    for _ in 0..20 {
+        run_state = game.apply(PlayerCommand::SkipTurn);
+
+        if run_state == RunState::GameOver {
+            break;
+        }
+    }
+
+    assert_eq!(run_state, RunState::GameOver);
+    assert!(game.is_finished());
+}
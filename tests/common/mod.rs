@@ -0,0 +1,201 @@
+#![allow(dead_code)]
+
+use rltk::Point;
+use specs::prelude::*;
+
+use roguelike_tutorial::components::*;
+use roguelike_tutorial::headless::HeadlessGame;
+use roguelike_tutorial::map::{Map, TileType};
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::spawner;
+
+/// The seed `isolated_game` starts from, so that every run of a test rolls the same dice
+pub const TEST_SEED: u64 = 1;
+
+/// Starts a game with everything but the player removed and the player standing in the middle
+/// of the map on open floor, so that tests do not depend on the randomly generated level.
+pub fn isolated_game() -> HeadlessGame {
+    isolated_game_with_seed(TEST_SEED)
+}
+
+/// Same as `isolated_game`, for tests that try a few seeds
+pub fn isolated_game_with_seed(seed: u64) -> HeadlessGame {
+    let mut game = HeadlessGame::with_seed(seed);
+    let player = player_entity(&game);
+
+    {
+        let entities = game.state.ecs.entities();
+        let backpack = game.state.ecs.read_storage::<InBackpack>();
+
+        for entity in entities.join() {
+            if entity != player && backpack.get(entity).is_none() {
+                entities.delete(entity).expect("Unable to delete entity");
+            }
+        }
+    }
+
+    game.state.ecs.maintain();
+
+    let player_pos = {
+        let map = game.state.ecs.fetch::<Map>();
+
+        Point::new(map.width / 2, map.height / 2)
+    };
+    {
+        let mut positions = game.state.ecs.write_storage::<Position>();
+        let position = positions.get_mut(player).unwrap();
+
+        position.x = player_pos.x;
+        position.y = player_pos.y;
+    }
+    *game.state.ecs.write_resource::<Point>() = player_pos;
+    {
+        let mut map = game.state.ecs.write_resource::<Map>();
+
+        for y in player_pos.y - 3..=player_pos.y + 3 {
+            for x in player_pos.x - 3..=player_pos.x + 3 {
+                if x > 0 && x < map.width - 1 && y > 0 && y < map.height - 1 {
+                    let idx = map.xy_idx(x, y);
+
+                    map.tiles[idx] = TileType::Floor;
+                }
+            }
+        }
+    }
+
+    refresh(&mut game);
+
+    game
+}
+
+/// Re-indexes the map and recomputes every viewshed
+pub fn refresh(game: &mut HeadlessGame) {
+    {
+        let mut viewsheds = game.state.ecs.write_storage::<Viewshed>();
+
+        for viewshed in (&mut viewsheds).join() {
+            viewshed.dirty = true;
+        }
+    }
+
+    game.state.run_systems();
+}
+
+pub fn player_entity(game: &HeadlessGame) -> Entity {
+    *game.state.ecs.fetch::<Entity>()
+}
+
+pub fn player_position(game: &HeadlessGame) -> Point {
+    *game.state.ecs.fetch::<Point>()
+}
+
+pub fn player_stats(game: &HeadlessGame) -> CombatStats {
+    let stats = game.state.ecs.read_storage::<CombatStats>();
+
+    stats.get(player_entity(game)).unwrap().clone()
+}
+
+pub fn set_player_hp(game: &mut HeadlessGame, hp: i32) {
+    let player = player_entity(game);
+    let mut stats = game.state.ecs.write_storage::<CombatStats>();
+
+    stats.get_mut(player).unwrap().hp = hp;
+}
+
+/// Sets both the current and maximum hp, so that a monster is too tough to run or die
+pub fn set_hp(game: &mut HeadlessGame, entity: Entity, hp: i32) {
+    let mut stats = game.state.ecs.write_storage::<CombatStats>();
+    let entity_stats = stats.get_mut(entity).unwrap();
+
+    entity_stats.max_hp = hp;
+    entity_stats.hp = hp;
+}
+
+/// Spawns a named entity through the spawner and returns it
+pub fn spawn(game: &mut HeadlessGame, name: &str, x: i32, y: i32) -> Entity {
+    let idx = game.state.ecs.fetch::<Map>().xy_idx(x, y);
+    let entity = spawner::spawn_entity(&mut game.state.ecs, &(&idx, &name.to_string()))
+        .expect("Entity was not spawned");
+
+    refresh(game);
+
+    entity
+}
+
+/// Spawns the item under the player, who picks it up and uses it from the inventory. The item has
+/// to be the only one carried, and is returned.
+pub fn use_item(game: &mut HeadlessGame, name: &str) -> Entity {
+    let pos = player_position(game);
+    let item = spawn(game, name, pos.x, pos.y);
+
+    game.apply(PlayerCommand::PickUp);
+    game.apply(PlayerCommand::ShowInventory);
+    game.apply(PlayerCommand::SelectItem { index: 0 });
+
+    item
+}
+
+/// Like `use_item`, aimed at wherever `target` has got to by the time it is used
+pub fn use_item_on(game: &mut HeadlessGame, name: &str, target: Entity) -> Entity {
+    let item = use_item(game, name);
+    let target_pos = position_of(game, target);
+
+    game.apply(PlayerCommand::Target {
+        x: target_pos.x,
+        y: target_pos.y,
+    });
+
+    item
+}
+
+/// A path in the temp directory for a file the test cleans up itself, named apart for each process
+pub fn temp_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("{}_{}", std::process::id(), name));
+
+    path.to_str().unwrap().to_string()
+}
+
+pub fn hp_of(game: &HeadlessGame, entity: Entity) -> i32 {
+    let stats = game.state.ecs.read_storage::<CombatStats>();
+
+    stats.get(entity).unwrap().hp
+}
+
+pub fn position_of(game: &HeadlessGame, entity: Entity) -> Point {
+    let positions = game.state.ecs.read_storage::<Position>();
+    let position = positions.get(entity).unwrap();
+
+    Point::new(position.x, position.y)
+}
+
+pub fn is_alive(game: &HeadlessGame, entity: Entity) -> bool {
+    game.state.ecs.entities().is_alive(entity)
+}
+
+pub fn log_contains(game: &HeadlessGame, text: &str) -> bool {
+    let log = game
+        .state
+        .ecs
+        .fetch::<roguelike_tutorial::game_log::GameLog>();
+
+    log.entries.iter().any(|entry| entry.contains(text))
+}
+
+/// How many attacks of `attacker` on `target` the log has, hits and misses alike
+pub fn attacks(game: &HeadlessGame, attacker: &str, target: &str) -> usize {
+    let log = game
+        .state
+        .ecs
+        .fetch::<roguelike_tutorial::game_log::GameLog>();
+    let attack_lines = [
+        format!("{} hits {}", attacker, target),
+        format!("{} critically hits {}", attacker, target),
+        format!("{} misses {}", attacker, target),
+        format!("{} fumbles the attack on {}", attacker, target),
+    ];
+
+    log.entries
+        .iter()
+        .filter(|entry| attack_lines.iter().any(|line| entry.starts_with(line)))
+        .count()
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use roguelike_tutorial::component_registry::{SAVED_COMPONENTS, TRANSIENT_COMPONENTS};
+use roguelike_tutorial::components::{BlocksVisibility, Door, Name};
+use roguelike_tutorial::saveload_system::{self, SaveEncoding};
+use specs::prelude::*;
+
+/// Names of the types deriving `Component` in every file under `dir`
+fn derived_components(dir: &Path, names: &mut Vec<String>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+
+        if path.is_dir() {
+            derived_components(&path, names);
+            continue;
+        }
+
+        let source = fs::read_to_string(&path).unwrap();
+        let mut derives_component = false;
+
+        for line in source.lines().map(str::trim) {
+            if line.starts_with("#[derive(") {
+                derives_component = line.contains("Component");
+            } else if let Some(item) = line
+                .strip_prefix("pub struct ")
+                .or_else(|| line.strip_prefix("struct "))
+            {
+                if derives_component {
+                    let name: String = item
+                        .chars()
+                        .take_while(|c| c.is_alphanumeric() || *c == '_')
+                        .collect();
+
+                    names.push(name);
+                }
+
+                derives_component = false;
+            }
+        }
+    }
+}
+
+#[test]
+fn every_component_is_saved_or_marked_transient() {
+    let mut names = Vec::new();
+
+    derived_components(Path::new("src"), &mut names);
+
+    assert!(names.contains(&"Position".to_string()));
+
+    for name in names {
+        assert!(
+            SAVED_COMPONENTS.contains(&name.as_str())
+                || TRANSIENT_COMPONENTS.contains(&name.as_str()),
+            "{} is neither saved nor marked transient in the component registry",
+            name
+        );
+    }
+}
+
+#[test]
+fn a_component_is_listed_only_once() {
+    let mut seen = HashSet::new();
+
+    for name in SAVED_COMPONENTS.iter().chain(TRANSIENT_COMPONENTS.iter()) {
+        assert!(seen.insert(name), "{} is listed twice", name);
+    }
+}
+
+#[test]
+fn doors_survive_saving_and_loading() {
+    let path = &common::temp_path("component_registry.json");
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+
+    common::spawn(&mut game, "Door", pos.x + 2, pos.y);
+    saveload_system::save_game_to(&mut game.state.ecs, path, SaveEncoding::Binary).unwrap();
+    saveload_system::load_game_from(&mut game.state.ecs, path).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    let names = game.state.ecs.read_storage::<Name>();
+    let doors = game.state.ecs.read_storage::<Door>();
+    let blocks_visibility = game.state.ecs.read_storage::<BlocksVisibility>();
+
+    let (_, door, _) = (&names, &doors, &blocks_visibility)
+        .join()
+        .find(|(name, _, _)| name.name == "Door")
+        .expect("The door was not loaded with its components");
+
+    assert!(!door.open);
+}
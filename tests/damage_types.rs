@@ -0,0 +1,104 @@
+mod common;
+
+use roguelike_tutorial::components::*;
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::status_effect_system;
+use specs::prelude::*;
+
+#[test]
+fn the_orc_resists_the_fire() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let orc = common::spawn(&mut game, "Orc", pos.x + 3, pos.y);
+
+    common::set_hp(&mut game, orc, 100);
+    common::set_player_hp(&mut game, 1000);
+    common::use_item_on(&mut game, "Fireball Scroll", orc);
+
+    assert!(common::log_contains(&game, "The orc resists the fire."));
+    assert_eq!(common::hp_of(&game, orc), 100 - 10);
+}
+
+#[test]
+fn fire_hurts_a_goblin_badly() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let goblin = common::spawn(&mut game, "Goblin", pos.x + 3, pos.y);
+
+    common::set_hp(&mut game, goblin, 100);
+    common::set_player_hp(&mut game, 1000);
+    common::use_item_on(&mut game, "Fireball Scroll", goblin);
+
+    assert!(common::log_contains(
+        &game,
+        "The fire hurts the goblin badly."
+    ));
+    assert_eq!(common::hp_of(&game, goblin), 100 - 40);
+}
+
+#[test]
+fn cold_hurts_an_orc_as_usual() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let orc = common::spawn(&mut game, "Orc", pos.x + 3, pos.y);
+
+    common::set_hp(&mut game, orc, 100);
+    common::use_item_on(&mut game, "Frost Scroll", orc);
+
+    assert_eq!(common::hp_of(&game, orc), 100 - 10);
+}
+
+#[test]
+fn a_guard_is_immune_to_poison() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let guard = common::spawn(&mut game, "Guard", pos.x + 3, pos.y);
+
+    {
+        let mut statuses = game.state.ecs.write_storage::<StatusEffects>();
+
+        status_effect_system::apply_status(
+            &mut statuses,
+            guard,
+            StatusEffect {
+                kind: StatusKind::Poison,
+                turns: 5,
+            },
+        );
+    }
+    game.run(std::iter::repeat_n(PlayerCommand::SkipTurn, 3));
+
+    assert!(common::log_contains(
+        &game,
+        "The guard is immune to the poison."
+    ));
+    assert_eq!(common::hp_of(&game, guard), 24);
+}
+
+#[test]
+fn damage_types_come_from_the_raws() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let fireball = common::spawn(&mut game, "Fireball Scroll", pos.x, pos.y);
+    let missile = common::spawn(&mut game, "Magic Missile Scroll", pos.x, pos.y);
+    let orc = common::spawn(&mut game, "Orc", pos.x + 2, pos.y);
+    let ecs = &game.state.ecs;
+    let inflicts = ecs.read_storage::<InflictsDamage>();
+
+    assert_eq!(
+        inflicts.get(fireball).unwrap().damage_type,
+        DamageType::Fire
+    );
+    assert_eq!(
+        inflicts.get(missile).unwrap().damage_type,
+        DamageType::Physical
+    );
+    assert_eq!(
+        ecs.read_storage::<Resistances>()
+            .get(orc)
+            .unwrap()
+            .damage_types,
+        vec![DamageType::Fire]
+    );
+    assert!(!ecs.read_storage::<Vulnerabilities>().contains(orc));
+}
@@ -0,0 +1,181 @@
+mod common;
+
+use roguelike_tutorial::components::*;
+use roguelike_tutorial::experience;
+use roguelike_tutorial::headless::HeadlessGame;
+use roguelike_tutorial::map::{Map, TileType};
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::saveload_system::{self, SaveEncoding};
+use specs::prelude::*;
+
+fn experience_of(game: &HeadlessGame) -> Experience {
+    let player = common::player_entity(game);
+
+    game.state
+        .ecs
+        .read_storage::<Experience>()
+        .get(player)
+        .cloned()
+        .unwrap()
+}
+
+/// Walls in every tile around the point, so whatever stands there stays put
+fn wall_around(game: &mut HeadlessGame, x: i32, y: i32) {
+    {
+        let mut map = game.state.ecs.fetch_mut::<Map>();
+
+        for (dx, dy) in (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| (dx, dy))) {
+            if (dx, dy) == (0, 0) {
+                continue;
+            }
+
+            let idx = map.xy_idx(x + dx, y + dy);
+
+            map.tiles[idx] = TileType::Wall;
+        }
+    }
+    common::refresh(game);
+}
+
+#[test]
+fn levels_take_more_xp_every_time() {
+    let mut experience = Experience { level: 1, xp: 0 };
+    let mut stats = CombatStats {
+        max_hp: 30,
+        hp: 20,
+        defense: 2,
+        power: 5,
+    };
+
+    assert_eq!(experience::xp_for_level(1), 0);
+    assert_eq!(experience::xp_for_level(2), 20);
+    assert_eq!(experience::xp_for_level(3), 60);
+    assert_eq!(experience::gain_xp(&mut experience, &mut stats, 19), 0);
+    assert_eq!(experience::gain_xp(&mut experience, &mut stats, 50), 2);
+    assert_eq!((experience.level, experience.xp), (3, 69));
+    assert_eq!((stats.max_hp, stats.hp, stats.power), (40, 30, 7));
+}
+
+#[test]
+fn killing_a_monster_in_melee_earns_its_xp() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let goblin = common::spawn(&mut game, "Goblin", pos.x, pos.y + 1);
+
+    // A badly hurt goblin runs, so corner it
+    {
+        let mut map = game.state.ecs.fetch_mut::<Map>();
+
+        for (x, y) in [(-1, 1), (1, 1), (-1, 2), (0, 2), (1, 2)] {
+            let idx = map.xy_idx(pos.x + x, pos.y + y);
+
+            map.tiles[idx] = TileType::Wall;
+        }
+    }
+    common::refresh(&mut game);
+
+    for _ in 0..40 {
+        if !common::is_alive(&game, goblin) {
+            break;
+        }
+
+        common::set_player_hp(&mut game, 30);
+        game.apply(PlayerCommand::Move {
+            delta_x: 0,
+            delta_y: 1,
+        });
+    }
+
+    assert!(!common::is_alive(&game, goblin));
+    assert_eq!(experience_of(&game).xp, 10);
+}
+
+#[test]
+fn an_area_effect_kill_is_credited_to_the_player_and_levels_them_up() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let goblin = common::spawn(&mut game, "Goblin", pos.x + 3, pos.y);
+    let other_goblin = common::spawn(&mut game, "Goblin", pos.x + 3, pos.y + 1);
+
+    common::set_player_hp(&mut game, 1000);
+    common::use_item_on(&mut game, "Fireball Scroll", goblin);
+
+    assert!(!common::is_alive(&game, goblin));
+    assert!(!common::is_alive(&game, other_goblin));
+    assert_eq!(experience_of(&game).xp, 20);
+    assert_eq!(experience_of(&game).level, 2);
+    assert!(common::log_contains(&game, "Welcome to level 2!"));
+    assert_eq!(common::player_stats(&game).max_hp, 30 + 5);
+    assert_eq!(common::player_stats(&game).power, 5 + 1);
+    assert!(game.summary().contains("Level: 2 (XP: 20 / 60)"));
+}
+
+#[test]
+fn a_trap_kill_is_credited_to_whoever_hurt_the_victim_last() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let orc = common::spawn(&mut game, "Orc", pos.x + 3, pos.y);
+
+    // The magic missile leaves it with a hit point, for the trap to take
+    common::set_hp(&mut game, orc, 9);
+    common::use_item_on(&mut game, "Magic Missile Scroll", orc);
+
+    assert_eq!(common::hp_of(&game, orc), 1);
+
+    let orc_pos = common::position_of(&game, orc);
+
+    wall_around(&mut game, orc_pos.x, orc_pos.y);
+    common::spawn(&mut game, "Bear Trap", orc_pos.x, orc_pos.y);
+    game.state
+        .ecs
+        .write_storage::<EntityMoved>()
+        .insert(orc, EntityMoved {})
+        .unwrap();
+    game.apply(PlayerCommand::SkipTurn);
+
+    assert!(!common::is_alive(&game, orc));
+    assert_eq!(experience_of(&game).xp, 20);
+}
+
+#[test]
+fn a_monster_nobody_hurt_earns_nobody_xp() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let (x, y) = (pos.x + 3, pos.y);
+    let orc = common::spawn(&mut game, "Orc", x, y);
+
+    wall_around(&mut game, x, y);
+    common::spawn(&mut game, "Bear Trap", x, y);
+    common::set_hp(&mut game, orc, 1);
+    game.state
+        .ecs
+        .write_storage::<EntityMoved>()
+        .insert(orc, EntityMoved {})
+        .unwrap();
+    game.apply(PlayerCommand::SkipTurn);
+
+    assert!(!common::is_alive(&game, orc));
+    assert_eq!(experience_of(&game).xp, 0);
+}
+
+#[test]
+fn experience_is_saved() {
+    let path = &common::temp_path("experience.sav");
+    let mut game = common::isolated_game();
+    let player = common::player_entity(&game);
+
+    game.state
+        .ecs
+        .write_storage::<Experience>()
+        .insert(player, Experience { level: 3, xp: 75 })
+        .unwrap();
+
+    saveload_system::save_game_to(&mut game.state.ecs, path, SaveEncoding::Binary).unwrap();
+    game.state.start_new_game(1);
+    saveload_system::load_game_from(&mut game.state.ecs, path).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    let experience = experience_of(&game);
+
+    assert_eq!((experience.level, experience.xp), (3, 75));
+}
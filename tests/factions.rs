@@ -0,0 +1,114 @@
+mod common;
+
+use roguelike_tutorial::components::*;
+use roguelike_tutorial::headless::HeadlessGame;
+use roguelike_tutorial::map::{Map, TileType};
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::raws::{self, Reaction};
+use specs::prelude::*;
+
+/// Puts a wall along the column `x` of the open floor around the player
+fn wall_off_column(game: &mut HeadlessGame, x: i32) {
+    let pos = common::player_position(game);
+
+    {
+        let mut map = game.state.ecs.fetch_mut::<Map>();
+
+        for y in pos.y - 3..=pos.y + 3 {
+            let idx = map.xy_idx(x, y);
+
+            map.tiles[idx] = TileType::Wall;
+        }
+    }
+
+    common::refresh(game);
+}
+
+fn wait(game: &mut HeadlessGame, turns: usize) {
+    common::set_player_hp(game, 1000);
+    game.run(std::iter::repeat_n(PlayerCommand::SkipTurn, turns));
+}
+
+#[test]
+fn the_faction_table_decides_reactions() {
+    let raws = raws::raws();
+
+    assert_eq!(raws.reaction("Goblins", "Orcs"), Reaction::Attack);
+    assert_eq!(raws.reaction("Goblins", "Goblins"), Reaction::Ignore);
+    assert_eq!(
+        raws.reaction("Guards", raws::PLAYER_FACTION),
+        Reaction::Ignore
+    );
+    assert_eq!(raws.reaction("Animals", "Orcs"), Reaction::Flee);
+    assert_eq!(raws.reaction("Nobody", "Orcs"), Reaction::Ignore);
+}
+
+#[test]
+fn orcs_and_goblins_fight_each_other() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+
+    wall_off_column(&mut game, pos.x + 2);
+    common::spawn(&mut game, "Orc", pos.x + 3, pos.y);
+    common::spawn(&mut game, "Goblin", pos.x + 3, pos.y + 1);
+    wait(&mut game, 3);
+
+    assert!(
+        common::attacks(&game, "Orc", "Goblin") > 0 || common::attacks(&game, "Goblin", "Orc") > 0
+    );
+    assert_eq!(common::attacks(&game, "Orc", "Player"), 0);
+    assert_eq!(common::attacks(&game, "Goblin", "Player"), 0);
+}
+
+#[test]
+fn a_guard_ignores_the_player_until_provoked() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let guard = common::spawn(&mut game, "Guard", pos.x + 1, pos.y);
+
+    // So that it does not wander off
+    game.state
+        .ecs
+        .write_storage::<Ai>()
+        .get_mut(guard)
+        .unwrap()
+        .state = AiState::Idle { turns: 100 };
+    wait(&mut game, 5);
+
+    assert_eq!(common::attacks(&game, "Guard", "Player"), 0);
+    assert!(!game.state.ecs.read_storage::<Provoked>().contains(guard));
+
+    game.apply(PlayerCommand::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+    wait(&mut game, 3);
+
+    assert!(common::attacks(&game, "Player", "Guard") > 0);
+    assert!(common::attacks(&game, "Guard", "Player") > 0);
+}
+
+#[test]
+fn a_deer_flees_from_a_goblin_and_ignores_the_player() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let deer = common::spawn(&mut game, "Deer", pos.x + 1, pos.y);
+
+    wait(&mut game, 3);
+
+    assert!(!common::log_contains(&game, "Deer hits"));
+    assert_ne!(
+        game.state.ecs.read_storage::<Ai>().get(deer).unwrap().state,
+        AiState::Flee
+    );
+
+    let deer_pos = common::position_of(&game, deer);
+
+    common::spawn(&mut game, "Goblin", deer_pos.x, deer_pos.y + 1);
+    wait(&mut game, 1);
+
+    assert_eq!(
+        game.state.ecs.read_storage::<Ai>().get(deer).unwrap().state,
+        AiState::Flee
+    );
+}
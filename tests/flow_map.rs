@@ -0,0 +1,116 @@
+use rltk::Point;
+use roguelike_tutorial::flow_map::{self, FlowMap, FlowMaps, CARDINAL_COST, UNREACHABLE};
+use roguelike_tutorial::map::{Map, TileType};
+
+/// A 20x10 map with a room from (1, 1) to (18, 8), and walls at `walls`
+fn room(walls: &[(i32, i32)]) -> Map {
+    let mut map = Map::new(1, 20, 10);
+
+    for y in 1..9 {
+        for x in 1..19 {
+            let idx = map.xy_idx(x, y);
+
+            map.tiles[idx] = TileType::Floor;
+        }
+    }
+    for (x, y) in walls {
+        let idx = map.xy_idx(*x, *y);
+
+        map.tiles[idx] = TileType::Wall;
+    }
+
+    map.populate_blocked();
+    map
+}
+
+fn block(map: &mut Map, x: i32, y: i32) {
+    let idx = map.xy_idx(x, y);
+
+    map.blocked[idx] = true;
+}
+
+#[test]
+fn the_flow_map_leads_to_the_target_around_walls() {
+    let walls: Vec<(i32, i32)> = (1..8).map(|y| (10, y)).collect();
+    let map = room(&walls);
+    let passable = flow_map::passable_tiles(&map, std::iter::empty());
+    let flow = FlowMap::towards(&map, &[Point::new(5, 2)], &passable);
+
+    assert_eq!(flow.value(Point::new(5, 2)), 0);
+    assert_eq!(flow.value(Point::new(6, 2)), CARDINAL_COST);
+    assert_eq!(flow.value(Point::new(10, 2)), UNREACHABLE);
+    assert!(flow.value(Point::new(15, 2)) > flow.value(Point::new(5, 8)));
+
+    let mut here = Point::new(15, 2);
+
+    for _ in 0..20 {
+        match flow.next_step(&map, here) {
+            Some(step) => here = step,
+            None => break,
+        }
+    }
+
+    assert_eq!(here, Point::new(5, 2));
+}
+
+#[test]
+fn a_monster_whose_best_step_is_taken_steps_around() {
+    let mut map = room(&[]);
+    let player = Point::new(10, 2);
+    let monsters = [Point::new(10, 3), Point::new(10, 4)];
+
+    for monster in monsters {
+        block(&mut map, monster.x, monster.y);
+    }
+
+    let flows = FlowMaps::new(&map, &[player], &monsters);
+    let step = flows.towards.next_step(&map, monsters[1]).unwrap();
+
+    assert_eq!(step.y, 3);
+    assert_ne!(step.x, 10);
+}
+
+#[test]
+fn monsters_in_a_corridor_do_not_cut_off_the_ones_behind() {
+    let walls: Vec<(i32, i32)> = (1..9)
+        .filter(|y| *y != 5)
+        .flat_map(|y| [(9, y), (10, y), (11, y)])
+        .collect();
+    let mut map = room(&walls);
+    let monsters = [Point::new(10, 5), Point::new(12, 5)];
+
+    block(&mut map, 10, 5);
+    block(&mut map, 12, 5);
+
+    let flows = FlowMaps::new(&map, &[Point::new(3, 5)], &monsters);
+
+    assert_ne!(flows.towards.value(Point::new(15, 5)), UNREACHABLE);
+    assert_eq!(
+        flows.towards.next_step(&map, monsters[1]),
+        Some(Point::new(11, 5))
+    );
+}
+
+#[test]
+fn a_fleeing_monster_gets_further_away_until_it_is_cornered() {
+    let map = room(&[]);
+    let player = Point::new(15, 4);
+    let flows = FlowMaps::new(&map, &[player], &[]);
+    let step = flows.flee_step(&map, Point::new(14, 4)).unwrap();
+
+    assert!(step.x < 14);
+    assert_eq!(flows.flee_step(&map, Point::new(1, 1)), None);
+}
+
+#[test]
+fn a_fleeing_monster_steps_out_of_reach() {
+    let map = room(&[]);
+    let player = Point::new(10, 4);
+    let flows = FlowMaps::new(&map, &[player], &[]);
+
+    for here in [Point::new(11, 4), Point::new(10, 5), Point::new(11, 5)] {
+        let step = flows.flee_step(&map, here).unwrap();
+
+        assert!((step.x - player.x).abs() > 1 || (step.y - player.y).abs() > 1);
+    }
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use roguelike_tutorial::headless::{self, HeadlessGame};
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::save_slots::SaveSlots;
+use roguelike_tutorial::save_storage::MemoryStorage;
+use roguelike_tutorial::{RunState, TurnCount};
+use std::rc::Rc;
+
+#[test]
+fn scripts_skip_blank_lines_and_comments() {
+    let script =
+        "# walk east, then look around\nmove 1 0\n\n  wait\nselect b\nselect 30\ntarget 10 12\n";
+
+    assert_eq!(
+        headless::parse_script(script),
+        Ok(vec![
+            PlayerCommand::Move {
+                delta_x: 1,
+                delta_y: 0
+            },
+            PlayerCommand::SkipTurn,
+            PlayerCommand::SelectItem { index: 1 },
+            PlayerCommand::SelectItem { index: 30 },
+            PlayerCommand::Target { x: 10, y: 12 },
+        ])
+    );
+}
+
+#[test]
+fn script_errors_name_the_line() {
+    assert_eq!(
+        headless::parse_script("wait\n\nfly"),
+        Err("Line 3: Unknown command 'fly'".to_string())
+    );
+    assert_eq!(
+        headless::parse_script("move 1 east"),
+        Err("Line 1: Expected a number, got 'east'".to_string())
+    );
+    assert_eq!(
+        headless::parse_script("select B"),
+        Err("Line 1: Expected a menu letter, got 'B'".to_string())
+    );
+}
+
+#[test]
+fn every_command_is_written_the_way_it_is_read() {
+    let commands = [
+        PlayerCommand::Move {
+            delta_x: -1,
+            delta_y: 1,
+        },
+        PlayerCommand::SkipTurn,
+        PlayerCommand::PickUp,
+        PlayerCommand::ShowInventory,
+        PlayerCommand::ShowDropItem,
+        PlayerCommand::ShowRemoveItem,
+        PlayerCommand::ShowCharacter,
+        PlayerCommand::SaveGame,
+        PlayerCommand::Descend,
+        PlayerCommand::Ascend,
+        PlayerCommand::SelectItem { index: 25 },
+        PlayerCommand::SelectItem { index: 26 },
+        PlayerCommand::Target { x: 3, y: 4 },
+        PlayerCommand::Cancel,
+    ];
+
+    for command in commands {
+        assert_eq!(command.to_string().parse::<PlayerCommand>(), Ok(command));
+    }
+}
+
+#[test]
+fn the_script_follows_the_headless_flag() {
+    let args = |line: &str| line.split(' ').map(str::to_string).collect::<Vec<_>>();
+
+    assert_eq!(
+        headless::script_argument(&args("game --seed 3 --headless walk.txt")),
+        Some("walk.txt".to_string())
+    );
+    assert_eq!(headless::script_argument(&args("game --headless")), None);
+    assert_eq!(headless::script_argument(&args("game")), None);
+}
+
+#[test]
+fn menus_wait_for_input_and_take_no_turn() {
+    let mut game = HeadlessGame::with_seed(21);
+
+    assert_eq!(game.run_state(), RunState::AwaitingInput);
+    assert_eq!(
+        game.apply(PlayerCommand::ShowInventory),
+        RunState::ShowInventory
+    );
+    assert_eq!(game.apply(PlayerCommand::Cancel), RunState::AwaitingInput);
+    assert_eq!(*game.state.ecs.fetch::<TurnCount>(), TurnCount(0));
+    assert_eq!(game.apply(PlayerCommand::SkipTurn), RunState::AwaitingInput);
+    assert_eq!(*game.state.ecs.fetch::<TurnCount>(), TurnCount(1));
+}
+
+#[test]
+fn commands_after_the_game_is_finished_are_not_run() {
+    let mut game = HeadlessGame::with_seed(21);
+
+    game.state
+        .use_save_slots(SaveSlots::new(Rc::new(MemoryStorage::new())));
+
+    let applied = game.run([
+        PlayerCommand::SkipTurn,
+        PlayerCommand::SaveGame,
+        PlayerCommand::SkipTurn,
+    ]);
+
+    assert_eq!(applied, 2);
+    assert!(game.is_finished());
+    assert_eq!(*game.state.ecs.fetch::<TurnCount>(), TurnCount(1));
+    assert_eq!(game.apply(PlayerCommand::SkipTurn), game.run_state());
+    assert_eq!(*game.state.ecs.fetch::<TurnCount>(), TurnCount(1));
+}
+
+#[test]
+fn a_script_file_runs_on_a_new_game() {
+    let path = &common::temp_path("headless.txt");
+
+    std::fs::write(path, "wait\nwait\ninventory\ncancel\n").unwrap();
+
+    let summary = headless::run_script_file(path, 42, None).unwrap();
+
+    std::fs::remove_file(path).unwrap();
+
+    assert!(summary.starts_with("Commands run: 4 of 4\nRun state: AwaitingInput\nSeed: 42\n"));
+    assert!(summary.contains("Depth: 1"));
+    assert!(headless::run_script_file(path, 42, None)
+        .unwrap_err()
+        .starts_with("Unable to read script"));
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use roguelike_tutorial::components::*;
+use roguelike_tutorial::player::PlayerCommand;
+use specs::prelude::*;
+
+fn set_hunger(game: &mut roguelike_tutorial::headless::HeadlessGame, state: HungerState) {
+    let player = common::player_entity(game);
+    let mut clocks = game.state.ecs.write_storage::<HungerClock>();
+
+    *clocks.get_mut(player).unwrap() = HungerClock { state, duration: 1 };
+}
+
+fn hunger_state(game: &roguelike_tutorial::headless::HeadlessGame) -> HungerState {
+    let clocks = game.state.ecs.read_storage::<HungerClock>();
+
+    clocks.get(common::player_entity(game)).unwrap().state
+}
+
+#[test]
+fn hunger_advances_every_turn() {
+    let mut game = common::isolated_game();
+
+    set_hunger(&mut game, HungerState::Normal);
+    game.apply(PlayerCommand::SkipTurn);
+
+    assert!(hunger_state(&game) == HungerState::Hungry);
+    assert!(common::log_contains(&game, "You are hungry."));
+}
+
+#[test]
+fn starving_hurts() {
+    let mut game = common::isolated_game();
+
+    common::set_player_hp(&mut game, 20);
+    set_hunger(&mut game, HungerState::Starving);
+    game.apply(PlayerCommand::SkipTurn);
+
+    assert_eq!(common::player_stats(&game).hp, 19);
+}
+
+#[test]
+fn eating_makes_the_player_well_fed() {
+    let mut game = common::isolated_game();
+
+    set_hunger(&mut game, HungerState::Hungry);
+    common::use_item(&mut game, "Rations");
+
+    assert!(hunger_state(&game) == HungerState::WellFed);
+}
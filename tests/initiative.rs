@@ -0,0 +1,129 @@
+mod common;
+
+use roguelike_tutorial::components::*;
+use roguelike_tutorial::headless::HeadlessGame;
+use roguelike_tutorial::initiative_system::{self, NORMAL_SPEED};
+use roguelike_tutorial::player::PlayerCommand;
+use specs::prelude::*;
+
+/// How often the named monster next to the player hits while the player waits `turns` turns
+fn attacks_while_waiting(name: &str, turns: usize) -> usize {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+
+    common::spawn(&mut game, name, pos.x + 1, pos.y);
+    common::set_player_hp(&mut game, 1000);
+    game.run(std::iter::repeat_n(PlayerCommand::SkipTurn, turns));
+
+    common::attacks(&game, name, "Player")
+}
+
+fn energy(game: &HeadlessGame) -> i32 {
+    let initiatives = game.state.ecs.read_storage::<Initiative>();
+
+    initiatives.get(common::player_entity(game)).unwrap().energy
+}
+
+#[test]
+fn fast_monsters_act_more_often_than_slow_ones() {
+    let goblin_hits = attacks_while_waiting("Goblin", 8);
+    let orc_hits = attacks_while_waiting("Orc", 8);
+
+    assert!(goblin_hits > 8, "{}", goblin_hits);
+    assert!(orc_hits < 8, "{}", orc_hits);
+}
+
+#[test]
+fn haste_gives_the_player_more_turns() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+
+    common::use_item(&mut game, "Haste Potion");
+
+    let player = common::player_entity(&game);
+
+    assert!(common::log_contains(
+        &game,
+        "everything around you slows down"
+    ));
+    assert_eq!(
+        initiative_system::speed_of(&game.state.ecs, player),
+        NORMAL_SPEED * 2
+    );
+
+    common::spawn(&mut game, "Orc", pos.x + 1, pos.y);
+    common::set_player_hp(&mut game, 1000);
+    game.run(std::iter::repeat_n(PlayerCommand::SkipTurn, 8));
+
+    let orc_attacks = common::attacks(&game, "Orc", "Player");
+
+    assert!(orc_attacks <= 4, "{}", orc_attacks);
+
+    game.run(std::iter::repeat_n(PlayerCommand::SkipTurn, 40));
+
+    assert!(common::log_contains(&game, "You are no longer hasted."));
+    assert_eq!(
+        initiative_system::speed_of(&game.state.ecs, player),
+        NORMAL_SPEED
+    );
+}
+
+#[test]
+fn slowed_monsters_lose_turns() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let goblin = common::spawn(&mut game, "Goblin", pos.x + 1, pos.y);
+
+    common::use_item_on(&mut game, "Slow Scroll", goblin);
+
+    assert!(common::log_contains(
+        &game,
+        "You use Slow Scroll on Goblin, slowing them."
+    ));
+    assert_eq!(initiative_system::speed_of(&game.state.ecs, goblin), 62);
+}
+
+#[test]
+fn heavy_armour_slows_the_player_down() {
+    let mut game = common::isolated_game();
+    let player = common::player_entity(&game);
+
+    common::use_item(&mut game, "Tower Shield");
+
+    assert_eq!(initiative_system::speed_of(&game.state.ecs, player), 80);
+
+    game.apply(PlayerCommand::ShowRemoveItem);
+    game.apply(PlayerCommand::SelectItem { index: 0 });
+
+    assert_eq!(
+        initiative_system::speed_of(&game.state.ecs, player),
+        NORMAL_SPEED
+    );
+}
+
+#[test]
+fn picking_up_costs_half_a_turn() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+
+    common::spawn(&mut game, "Dagger", pos.x, pos.y);
+    common::spawn(&mut game, "Shield", pos.x, pos.y);
+
+    assert_eq!(energy(&game), 0);
+
+    // Paying for the first leaves the player waiting for a tick, the second is paid from it
+    game.apply(PlayerCommand::PickUp);
+
+    assert_eq!(
+        energy(&game),
+        NORMAL_SPEED - initiative_system::QUICK_ACTION_COST
+    );
+
+    game.apply(PlayerCommand::PickUp);
+
+    assert_eq!(energy(&game), 0);
+    assert_eq!(
+        roguelike_tutorial::gui::backpack_items(&game.state.ecs).len(),
+        2
+    );
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use roguelike_tutorial::components::*;
+use roguelike_tutorial::gui;
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::RunState;
+use specs::prelude::*;
+
+#[test]
+fn picking_up_and_drinking_a_potion_heals() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let potion = common::spawn(&mut game, "Health Potion", pos.x, pos.y);
+
+    game.apply(PlayerCommand::PickUp);
+
+    assert_eq!(gui::backpack_items(&game.state.ecs), vec![potion]);
+
+    common::set_player_hp(&mut game, 10);
+
+    assert_eq!(
+        game.apply(PlayerCommand::ShowInventory),
+        RunState::ShowInventory
+    );
+    game.apply(PlayerCommand::SelectItem { index: 0 });
+
+    assert_eq!(common::player_stats(&game).hp, 18);
+    assert!(!common::is_alive(&game, potion));
+}
+
+#[test]
+fn equipping_and_removing_a_weapon() {
+    let mut game = common::isolated_game();
+    let dagger = common::use_item(&mut game, "Dagger");
+
+    assert_eq!(gui::equipped_items(&game.state.ecs), vec![dagger]);
+    assert!(gui::backpack_items(&game.state.ecs).is_empty());
+
+    game.apply(PlayerCommand::ShowRemoveItem);
+    game.apply(PlayerCommand::SelectItem { index: 0 });
+
+    assert!(gui::equipped_items(&game.state.ecs).is_empty());
+    assert_eq!(gui::backpack_items(&game.state.ecs), vec![dagger]);
+}
+
+#[test]
+fn ranged_scroll_hits_the_target() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let scroll = common::spawn(&mut game, "Magic Missile Scroll", pos.x, pos.y);
+    let goblin = common::spawn(&mut game, "Goblin", pos.x + 2, pos.y);
+
+    game.apply(PlayerCommand::PickUp);
+    game.apply(PlayerCommand::ShowInventory);
+
+    assert!(matches!(
+        game.apply(PlayerCommand::SelectItem { index: 0 }),
+        RunState::ShowTargeting { .. }
+    ));
+
+    // The goblin may have stepped closer while the scroll was picked up
+    let goblin_pos = common::position_of(&game, goblin);
+
+    game.apply(PlayerCommand::Target {
+        x: goblin_pos.x,
+        y: goblin_pos.y,
+    });
+
+    assert_eq!(common::hp_of(&game, goblin), 16 - 8);
+    assert!(!common::is_alive(&game, scroll));
+}
+
+#[test]
+fn cancelling_a_menu_returns_to_the_game() {
+    let mut game = common::isolated_game();
+
+    game.apply(PlayerCommand::ShowDropItem);
+
+    assert_eq!(game.apply(PlayerCommand::Cancel), RunState::AwaitingInput);
+}
+
+#[test]
+fn dropping_an_item_puts_it_under_the_player() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let rations = common::spawn(&mut game, "Rations", pos.x, pos.y);
+
+    game.apply(PlayerCommand::PickUp);
+    game.apply(PlayerCommand::ShowDropItem);
+    game.apply(PlayerCommand::SelectItem { index: 0 });
+
+    let positions = game.state.ecs.read_storage::<Position>();
+    let dropped = positions.get(rations).unwrap();
+
+    assert_eq!((dropped.x, dropped.y), (pos.x, pos.y));
+}
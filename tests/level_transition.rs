@@ -0,0 +1,158 @@
+mod common;
+
+use roguelike_tutorial::components::{Name, Position};
+use roguelike_tutorial::gui;
+use roguelike_tutorial::map::{Map, TileType};
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::saveload_system::{self, SaveEncoding};
+use roguelike_tutorial::RunState;
+use specs::prelude::*;
+
+fn tile_under_player(game: &roguelike_tutorial::headless::HeadlessGame) -> TileType {
+    let pos = common::player_position(game);
+    let map = game.state.ecs.fetch::<Map>();
+
+    map.tiles[map.xy_idx(pos.x, pos.y)]
+}
+
+fn put_stairs_under_player(game: &mut roguelike_tutorial::headless::HeadlessGame) {
+    let pos = common::player_position(game);
+    let mut map = game.state.ecs.write_resource::<Map>();
+    let idx = map.xy_idx(pos.x, pos.y);
+
+    map.tiles[idx] = TileType::DownStairs;
+}
+
+#[test]
+fn descending_needs_stairs() {
+    let mut game = common::isolated_game();
+
+    assert_eq!(game.apply(PlayerCommand::Descend), RunState::AwaitingInput);
+    assert_eq!(game.state.ecs.fetch::<Map>().depth, 1);
+    assert!(common::log_contains(
+        &game,
+        "There is no way down from here."
+    ));
+}
+
+#[test]
+fn descending_builds_the_next_level_and_keeps_the_backpack() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let potion = common::spawn(&mut game, "Health Potion", pos.x, pos.y);
+    let goblin = common::spawn(&mut game, "Goblin", pos.x + 3, pos.y + 3);
+
+    game.apply(PlayerCommand::PickUp);
+    common::set_player_hp(&mut game, 4);
+    put_stairs_under_player(&mut game);
+
+    assert_eq!(game.apply(PlayerCommand::Descend), RunState::AwaitingInput);
+
+    assert_eq!(game.state.ecs.fetch::<Map>().depth, 2);
+    assert_eq!(gui::backpack_items(&game.state.ecs), vec![potion]);
+    assert!(common::is_alive(&game, goblin));
+    assert!(game
+        .state
+        .ecs
+        .read_storage::<Position>()
+        .get(goblin)
+        .is_none());
+    assert_eq!(common::player_stats(&game).hp, 15);
+}
+
+#[test]
+fn ascending_needs_up_stairs() {
+    let mut game = common::isolated_game();
+
+    assert_eq!(game.apply(PlayerCommand::Ascend), RunState::AwaitingInput);
+    assert_eq!(game.state.ecs.fetch::<Map>().depth, 1);
+    assert!(common::log_contains(&game, "There is no way up from here."));
+}
+
+#[test]
+fn a_new_level_has_up_stairs_under_the_player() {
+    let mut game = common::isolated_game();
+
+    put_stairs_under_player(&mut game);
+    game.apply(PlayerCommand::Descend);
+
+    assert_eq!(tile_under_player(&game), TileType::UpStairs);
+}
+
+#[test]
+fn going_back_up_finds_the_level_as_it_was_left() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let potion = common::spawn(&mut game, "Health Potion", pos.x + 2, pos.y);
+    let goblin = common::spawn(&mut game, "Goblin", pos.x + 3, pos.y + 3);
+
+    put_stairs_under_player(&mut game);
+
+    let first_level = (*game.state.ecs.fetch::<Map>()).clone();
+
+    game.apply(PlayerCommand::Descend);
+
+    let second_level = game.state.ecs.fetch::<Map>().tiles.clone();
+
+    assert_eq!(game.apply(PlayerCommand::Ascend), RunState::AwaitingInput);
+
+    {
+        let map = game.state.ecs.fetch::<Map>();
+
+        assert_eq!(map.depth, 1);
+        assert!(map.tiles == first_level.tiles);
+        assert!(map.revealed_tiles == first_level.revealed_tiles);
+    }
+    assert_eq!(common::player_position(&game), pos);
+    assert_eq!(
+        common::position_of(&game, potion),
+        rltk::Point::new(pos.x + 2, pos.y)
+    );
+    assert!(game
+        .state
+        .ecs
+        .read_storage::<Position>()
+        .get(goblin)
+        .is_some());
+    assert!(common::log_contains(
+        &game,
+        "You ascend to the previous level."
+    ));
+
+    game.apply(PlayerCommand::Descend);
+
+    assert!(game.state.ecs.fetch::<Map>().tiles == second_level);
+    assert_eq!(tile_under_player(&game), TileType::UpStairs);
+}
+
+#[test]
+fn visited_levels_survive_saving_and_loading() {
+    let path = &common::temp_path("level_transition.json");
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+
+    common::spawn(&mut game, "Health Potion", pos.x + 2, pos.y);
+    put_stairs_under_player(&mut game);
+    game.apply(PlayerCommand::Descend);
+
+    saveload_system::save_game_to(&mut game.state.ecs, path, SaveEncoding::Binary).unwrap();
+    game.state.start_new_game(1);
+    saveload_system::load_game_from(&mut game.state.ecs, path).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!(game.state.ecs.fetch::<Map>().depth, 2);
+
+    game.apply(PlayerCommand::Ascend);
+
+    assert_eq!(game.state.ecs.fetch::<Map>().depth, 1);
+    assert_eq!(common::player_position(&game), pos);
+
+    let names = game.state.ecs.read_storage::<Name>();
+    let positions = game.state.ecs.read_storage::<Position>();
+
+    assert!((&names, &positions)
+        .join()
+        .any(|(name, position)| name.name == "Health Potion"
+            && position.x == pos.x + 2
+            && position.y == pos.y));
+}
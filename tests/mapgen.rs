@@ -0,0 +1,103 @@
+use roguelike_tutorial::headless::HeadlessGame;
+use roguelike_tutorial::map::{Map, TileType};
+use roguelike_tutorial::mapgen::{self, MapgenOptions};
+
+fn args(line: &str) -> Vec<String> {
+    line.split(' ').map(String::from).collect()
+}
+
+#[test]
+fn the_arguments_are_parsed() {
+    let options = mapgen::parse_arguments(&args(
+        "mapgen --seed 42 --depth 3 --size 40x30 --chain Shapes --history",
+    ))
+    .unwrap();
+
+    assert_eq!(
+        options,
+        MapgenOptions {
+            seed: 42,
+            depth: 3,
+            width: 40,
+            height: 30,
+            chain: Some("Shapes".to_string()),
+            history: true,
+        }
+    );
+    assert!(mapgen::parse_arguments(&args("mapgen --size 40")).is_err());
+    assert!(mapgen::parse_arguments(&args("mapgen --depth")).is_err());
+    assert!(mapgen::parse_arguments(&args("mapgen --depth 0")).is_err());
+    assert!(mapgen::parse_arguments(&args("mapgen --size 1x1")).is_err());
+    assert!(mapgen::parse_arguments(&args("mapgen --size 20x15")).is_err());
+    assert!(mapgen::parse_arguments(&args("mapgen --size 40x29")).is_err());
+    assert!(mapgen::parse_arguments(&args("mapgen --colour")).is_err());
+}
+
+#[test]
+fn mapgen_builds_the_level_the_game_builds() {
+    let options = MapgenOptions {
+        seed: 1234,
+        ..MapgenOptions::default()
+    };
+    let builder = mapgen::generate(&options).unwrap();
+    let game = HeadlessGame::with_seed(1234);
+
+    assert!(builder.build_data.map.tiles == game.state.ecs.fetch::<Map>().tiles);
+}
+
+#[test]
+fn below_the_first_level_the_start_has_the_way_up() {
+    for depth in 1..=3 {
+        let options = MapgenOptions {
+            seed: 1234,
+            depth,
+            ..MapgenOptions::default()
+        };
+        let builder = mapgen::generate(&options).unwrap();
+        let map = &builder.build_data.map;
+        let start = builder.build_data.starting_position.as_ref().unwrap();
+        let tile = map.tiles[map.xy_idx(start.x, start.y)];
+
+        assert_eq!(tile == TileType::UpStairs, depth > 1);
+    }
+}
+
+#[test]
+fn the_output_shows_the_map_start_and_spawns() {
+    let options = MapgenOptions {
+        seed: 7,
+        chain: Some("BSP interior".to_string()),
+        ..MapgenOptions::default()
+    };
+
+    let output = mapgen::run(&options).unwrap();
+
+    assert_eq!(output, mapgen::run(&options).unwrap());
+    assert!(output.starts_with("Seed: 7 Depth: 1 Size: 80x50 Chain: BSP interior\n"));
+    assert_eq!(output.matches('@').count(), 1);
+    assert!(output.contains('>'));
+    assert!(output.contains("\nSpawns:\n"));
+    assert!(!output.contains("Step 1"));
+}
+
+#[test]
+fn the_history_is_printed_when_asked_for() {
+    let options = MapgenOptions {
+        seed: 7,
+        chain: Some("BSP interior".to_string()),
+        history: true,
+        ..MapgenOptions::default()
+    };
+
+    assert!(mapgen::run(&options).unwrap().contains("Step 1\n"));
+}
+
+#[test]
+fn an_unknown_chain_is_an_error() {
+    let options = MapgenOptions {
+        chain: Some("Nope".to_string()),
+        ..MapgenOptions::default()
+    };
+
+    assert!(mapgen::generate(&options).is_err());
+}
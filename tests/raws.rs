@@ -0,0 +1,171 @@
+mod common;
+
+use roguelike_tutorial::components::*;
+use roguelike_tutorial::map_builders;
+use roguelike_tutorial::raws::{self, RawMaster};
+use specs::prelude::*;
+
+const MINIMAL_RAWS: &str = r##"{
+    "items": [
+        {
+            "name": "Healing Draught",
+            "renderable": { "glyph": "!", "fg": "#FF00FF", "bg": "#000000", "order": "Item" },
+            "consumable": { "effects": { "provides_healing": 5 } }
+        }
+    ],
+    "mobs": [],
+    "props": [],
+    "spawn_table": [ { "name": "Healing Draught", "weight": 3 } ],
+    "faction_table": [ { "name": "Player", "responses": {} } ]
+}"##;
+
+#[test]
+fn the_shipped_raws_load() {
+    let raws = raws::load_raws().unwrap();
+
+    for name in map_builders::NAMED_SPAWNS {
+        assert!(raws.contains(name), "{} is missing", name);
+    }
+}
+
+#[test]
+fn raws_with_valid_definitions_load() {
+    let raws = RawMaster::load(MINIMAL_RAWS).unwrap();
+
+    assert!(raws.contains("Healing Draught"));
+    assert!(!raws.contains("Goblin"));
+}
+
+#[test]
+fn a_typo_in_the_spawn_table_is_a_load_error() {
+    let raws = MINIMAL_RAWS.replace(
+        r#"{ "name": "Healing Draught", "weight": 3 }"#,
+        r#"{ "name": "Healing Draugth", "weight": 3 }"#,
+    );
+
+    let error = RawMaster::load(&raws).err().unwrap();
+
+    assert!(error.contains("Healing Draugth"), "{}", error);
+}
+
+#[test]
+fn a_typo_in_a_field_is_a_load_error() {
+    let raws = MINIMAL_RAWS.replace("provides_healing", "provides_heeling");
+
+    assert!(RawMaster::load(&raws).is_err());
+}
+
+#[test]
+fn duplicate_names_and_bad_colors_are_load_errors() {
+    let duplicated = MINIMAL_RAWS.replace(
+        r#""props": []"#,
+        r#""props": [ { "name": "Healing Draught" } ]"#,
+    );
+    let bad_color = MINIMAL_RAWS.replace("#FF00FF", "magenta");
+
+    assert!(RawMaster::load(&duplicated).is_err());
+    assert!(RawMaster::load(&bad_color).is_err());
+}
+
+#[test]
+fn unknown_factions_are_load_errors() {
+    let mob = r#""mobs": [ {
+        "name": "Rat",
+        "stats": { "max_hp": 1, "hp": 1, "defense": 0, "power": 1, "damage": "1d2" },
+        "vision_range": 4,
+        "faction": "Vermin"
+    } ]"#;
+    let unknown_mob_faction = MINIMAL_RAWS.replace(r#""mobs": []"#, mob);
+    let unknown_response = MINIMAL_RAWS.replace(
+        r#""responses": {}"#,
+        r#""responses": { "Vermin": "Attack" }"#,
+    );
+    let no_player = MINIMAL_RAWS.replace(r#""name": "Player""#, r#""name": "Vermin""#);
+
+    let error = RawMaster::load(&unknown_mob_faction).err().unwrap();
+
+    assert!(error.contains("Vermin"), "{}", error);
+    assert!(RawMaster::load(&unknown_response).is_err());
+    assert!(RawMaster::load(&no_player).is_err());
+}
+
+#[test]
+fn bad_damage_dice_are_load_errors() {
+    let mob = r#""mobs": [ {
+        "name": "Rat",
+        "stats": { "max_hp": 1, "hp": 1, "defense": 0, "power": 1, "damage": "a rat bite" },
+        "vision_range": 4,
+        "faction": "Player"
+    } ]"#;
+    let error = RawMaster::load(&MINIMAL_RAWS.replace(r#""mobs": []"#, mob))
+        .err()
+        .unwrap();
+
+    assert!(error.contains("Rat"), "{}", error);
+}
+
+#[test]
+fn unknown_damage_types_are_load_errors() {
+    let acid = MINIMAL_RAWS.replace(
+        r#""provides_healing": 5"#,
+        r#""damage": 5, "damage_type": "Acid""#,
+    );
+
+    assert!(RawMaster::load(&acid).is_err());
+}
+
+#[test]
+fn raw_definitions_become_components() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+
+    let scroll = common::spawn(&mut game, "Fireball Scroll", pos.x, pos.y);
+    let sword = common::spawn(&mut game, "Longsword", pos.x + 3, pos.y);
+    let orc = common::spawn(&mut game, "Orc", pos.x + 2, pos.y);
+    // Last, as every spawn gives the player another chance to spot it
+    let trap = common::spawn(&mut game, "Bear Trap", pos.x + 1, pos.y);
+
+    let ecs = &game.state.ecs;
+
+    assert_eq!(
+        ecs.read_storage::<InflictsDamage>()
+            .get(scroll)
+            .unwrap()
+            .damage,
+        20
+    );
+    assert_eq!(
+        ecs.read_storage::<AreaOfEffect>()
+            .get(scroll)
+            .unwrap()
+            .radius,
+        3
+    );
+    assert!(ecs.read_storage::<Consumable>().contains(scroll));
+
+    assert!(ecs.read_storage::<Hidden>().contains(trap));
+    assert!(ecs.read_storage::<SingleActivation>().contains(trap));
+
+    assert!(ecs.read_storage::<Monster>().contains(orc));
+    assert_eq!(ecs.read_storage::<Faction>().get(orc).unwrap().name, "Orcs");
+    assert_eq!(
+        ecs.read_storage::<CombatStats>().get(orc).unwrap().max_hp,
+        16
+    );
+    assert_eq!(
+        ecs.read_storage::<MeleeWeapon>().get(orc).unwrap().damage,
+        rltk::DiceType::new(1, 6, 0)
+    );
+
+    assert_eq!(
+        ecs.read_storage::<MeleeWeapon>().get(sword).unwrap().damage,
+        rltk::DiceType::new(1, 8, 0)
+    );
+    assert_eq!(
+        ecs.read_storage::<MeleePowerBonus>()
+            .get(sword)
+            .unwrap()
+            .power,
+        1
+    );
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use std::fs;
+
+use roguelike_tutorial::headless::HeadlessGame;
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::replay::{self, Recorder, ReplayEntry};
+
+const COMMANDS: [PlayerCommand; 12] = [
+    PlayerCommand::Move {
+        delta_x: -1,
+        delta_y: 1,
+    },
+    PlayerCommand::SkipTurn,
+    PlayerCommand::PickUp,
+    PlayerCommand::ShowInventory,
+    PlayerCommand::ShowDropItem,
+    PlayerCommand::ShowRemoveItem,
+    PlayerCommand::ShowCharacter,
+    PlayerCommand::SaveGame,
+    PlayerCommand::Descend,
+    PlayerCommand::SelectItem { index: 2 },
+    PlayerCommand::Target { x: 10, y: 12 },
+    PlayerCommand::Cancel,
+];
+
+#[test]
+fn commands_are_written_back_in_the_script_syntax() {
+    for command in COMMANDS {
+        assert_eq!(command.to_string().parse::<PlayerCommand>(), Ok(command));
+    }
+}
+
+#[test]
+fn a_replay_has_to_start_with_a_seed() {
+    assert!(replay::parse_replay("wait\n").is_err());
+    assert_eq!(
+        replay::parse_replay("# comment\nseed 7\nwait\n"),
+        Ok(vec![
+            ReplayEntry::NewGame { seed: 7 },
+            ReplayEntry::Command(PlayerCommand::SkipTurn)
+        ])
+    );
+}
+
+#[test]
+fn a_recorded_game_replays_to_the_same_state() {
+    let path = &common::temp_path("recorded.replay");
+
+    let mut game = HeadlessGame::with_seed(42);
+    game.state.record_to(Recorder::create(path, 42).unwrap());
+
+    let moves = [(1, 0), (1, 0), (0, 1), (0, 1), (-1, 0), (1, 1), (1, 1)];
+    game.run(
+        moves
+            .iter()
+            .map(|&(delta_x, delta_y)| PlayerCommand::Move { delta_x, delta_y }),
+    );
+    game.apply(PlayerCommand::ShowInventory);
+    game.apply(PlayerCommand::Cancel);
+
+    // Commands the game has no use for where it is are left out
+    game.run([
+        PlayerCommand::SelectItem { index: 0 },
+        PlayerCommand::Target { x: 1, y: 1 },
+        PlayerCommand::Cancel,
+    ]);
+
+    // Starting over is recorded as well
+    game.new_game(7);
+    game.run([
+        PlayerCommand::SkipTurn,
+        PlayerCommand::Move {
+            delta_x: 0,
+            delta_y: -1,
+        },
+    ]);
+
+    let entries = replay::parse_replay(&fs::read_to_string(path).unwrap()).unwrap();
+    fs::remove_file(path).unwrap();
+
+    assert_eq!(entries.len(), 13);
+    assert_eq!(replay::run_replay(&entries).summary(), game.summary());
+}
+
+/// Replays kept from real play, add the replay of every crash that gets fixed
+#[test]
+fn saved_replays_run_to_the_end() {
+    for file in fs::read_dir("tests/replays").unwrap() {
+        let path = file.unwrap().path();
+        let entries = replay::parse_replay(&fs::read_to_string(&path).unwrap()).unwrap();
+
+        replay::run_replay(&entries);
+    }
+}
+
+#[test]
+fn a_paused_recording_goes_on_with_the_next_new_game() {
+    let path = &common::temp_path("paused.replay");
+    let mut recorder = Recorder::create(path, 3).unwrap();
+
+    recorder.record(PlayerCommand::SkipTurn);
+    recorder.pause("A saved game was loaded");
+    recorder.record(PlayerCommand::PickUp);
+    recorder.new_game(4);
+    recorder.record(PlayerCommand::Descend);
+
+    let entries = replay::parse_replay(&fs::read_to_string(path).unwrap()).unwrap();
+    fs::remove_file(path).unwrap();
+
+    assert_eq!(
+        entries,
+        vec![
+            ReplayEntry::NewGame { seed: 3 },
+            ReplayEntry::Command(PlayerCommand::SkipTurn),
+            ReplayEntry::NewGame { seed: 4 },
+            ReplayEntry::Command(PlayerCommand::Descend),
+        ]
+    );
+}
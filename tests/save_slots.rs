@@ -0,0 +1,334 @@
+mod common;
+
+use roguelike_tutorial::components::{CombatStats, Name, StatusEffect, StatusEffects, StatusKind};
+use roguelike_tutorial::headless::HeadlessGame;
+use roguelike_tutorial::map::{Map, TileType};
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::save_slots::{self, AutosaveSettings, SaveSlots, SlotId};
+use roguelike_tutorial::save_storage::{FileStorage, MemoryStorage};
+use roguelike_tutorial::saveload_system::SaveEncoding;
+use roguelike_tutorial::status_effect_system;
+use roguelike_tutorial::TurnCount;
+use specs::prelude::*;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+fn memory_slots() -> SaveSlots {
+    SaveSlots::new(Rc::new(MemoryStorage::new()))
+}
+
+#[test]
+fn slots_are_numbered_and_keep_metadata() {
+    let slots = memory_slots();
+    let mut game = HeadlessGame::with_seed(7);
+
+    assert!(!slots.has_saves());
+
+    game.run([PlayerCommand::SkipTurn, PlayerCommand::SkipTurn]);
+
+    assert_eq!(slots.save_new(&mut game.state.ecs), Ok(1));
+    assert_eq!(slots.save_new(&mut game.state.ecs), Ok(2));
+
+    let listed = slots.list();
+
+    assert_eq!(
+        listed.iter().map(|slot| slot.id).collect::<Vec<_>>(),
+        vec![SlotId::Numbered(1), SlotId::Numbered(2)]
+    );
+
+    let metadata = listed[0].metadata.clone().unwrap();
+
+    assert_eq!(metadata.character_name, "Player");
+    assert_eq!(metadata.depth, 1);
+    assert_eq!(metadata.turns, 2);
+    assert_eq!(metadata.seed, 7);
+    assert!(metadata.timestamp > 0);
+}
+
+#[test]
+fn loading_a_slot_keeps_it() {
+    let slots = memory_slots();
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+
+    game.apply(PlayerCommand::SkipTurn);
+    slots
+        .save(&mut game.state.ecs, SlotId::Numbered(3))
+        .unwrap();
+    game.state.start_new_game(1);
+
+    assert_eq!(slots.load(&mut game.state.ecs, SlotId::Numbered(3)), Ok(()));
+    assert_eq!(common::player_position(&game), pos);
+    assert_eq!(*game.state.ecs.fetch::<TurnCount>(), TurnCount(1));
+    assert!(slots.has_saves());
+}
+
+#[test]
+fn overwriting_a_slot_replaces_its_metadata() {
+    let slots = memory_slots();
+    let mut game = HeadlessGame::with_seed(3);
+
+    slots
+        .save(&mut game.state.ecs, SlotId::Numbered(1))
+        .unwrap();
+    game.state.ecs.fetch_mut::<Map>().depth = 4;
+    slots
+        .save(&mut game.state.ecs, SlotId::Numbered(1))
+        .unwrap();
+
+    let listed = slots.list();
+
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].metadata.as_ref().unwrap().depth, 4);
+}
+
+#[test]
+fn deleted_and_broken_slots() {
+    let slots = memory_slots();
+    let mut game = HeadlessGame::with_seed(3);
+
+    slots.save_new(&mut game.state.ecs).unwrap();
+    slots.storage().write("slot_2.sav", b"not a save").unwrap();
+
+    let listed = slots.list();
+
+    assert!(listed[0].metadata.is_ok());
+    assert!(listed[1].metadata.is_err());
+    assert!(slots
+        .load(&mut game.state.ecs, SlotId::Numbered(2))
+        .is_err());
+
+    slots.delete(SlotId::Numbered(1)).unwrap();
+    slots.delete(SlotId::Numbered(2)).unwrap();
+
+    assert!(!slots.has_saves());
+    assert!(slots.delete(SlotId::Numbered(1)).is_err());
+}
+
+#[test]
+fn saving_headless_takes_a_new_slot() {
+    let slots = memory_slots();
+    let mut game = HeadlessGame::with_seed(11);
+
+    game.state.use_save_slots(slots.clone());
+    game.apply(PlayerCommand::SaveGame);
+
+    assert!(game.is_finished());
+    assert_eq!(slots.list().len(), 1);
+}
+
+#[test]
+fn timestamps_are_shown_in_utc() {
+    assert_eq!(save_slots::format_timestamp(0), "unknown time");
+    assert_eq!(save_slots::format_timestamp(86_400), "1970-01-02 00:00");
+    assert_eq!(
+        save_slots::format_timestamp(1_709_210_096),
+        "2024-02-29 12:34"
+    );
+}
+
+#[test]
+fn a_slot_can_be_saved_as_json_and_back() {
+    let slots = memory_slots();
+    let mut game = HeadlessGame::with_seed(5);
+    let json_slots = slots.clone().with_encoding(SaveEncoding::Json);
+
+    json_slots
+        .save(&mut game.state.ecs, SlotId::Numbered(1))
+        .unwrap();
+
+    assert_eq!(slots.name(SlotId::Numbered(1)), "slot_1.json");
+    assert!(slots.load(&mut game.state.ecs, SlotId::Numbered(1)).is_ok());
+
+    slots
+        .save(&mut game.state.ecs, SlotId::Numbered(1))
+        .unwrap();
+
+    assert_eq!(slots.name(SlotId::Numbered(1)), "slot_1.sav");
+    assert_eq!(slots.list().len(), 1);
+}
+
+#[test]
+fn every_save_picks_its_own_encoding() {
+    let slots = memory_slots();
+    let mut game = HeadlessGame::with_seed(5);
+
+    assert_eq!(
+        slots.save_new_as(&mut game.state.ecs, SaveEncoding::Json),
+        Ok(1)
+    );
+    assert_eq!(slots.save_new(&mut game.state.ecs), Ok(2));
+    assert_eq!(slots.name(SlotId::Numbered(1)), "slot_1.json");
+    assert_eq!(slots.name(SlotId::Numbered(2)), "slot_2.sav");
+
+    slots
+        .save_as(&mut game.state.ecs, SlotId::Numbered(2), SaveEncoding::Json)
+        .unwrap();
+
+    assert_eq!(slots.name(SlotId::Numbered(2)), "slot_2.json");
+    assert_eq!(slots.storage().names().len(), 2);
+    assert!(slots.load(&mut game.state.ecs, SlotId::Numbered(2)).is_ok());
+}
+
+#[test]
+fn slots_can_be_kept_in_files() {
+    let dir = PathBuf::from(common::temp_path("save_slots"));
+    let slots = SaveSlots::new(Rc::new(FileStorage::new(&dir)));
+    let mut game = HeadlessGame::with_seed(9);
+
+    assert!(!slots.has_saves());
+    assert_eq!(slots.save_new(&mut game.state.ecs), Ok(1));
+    assert!(dir.join("slot_1.sav").is_file());
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    assert_eq!(slots.load(&mut game.state.ecs, SlotId::Numbered(1)), Ok(()));
+    assert_eq!(slots.list()[0].metadata.as_ref().unwrap().seed, 9);
+
+    slots.delete(SlotId::Numbered(1)).unwrap();
+    std::fs::remove_dir(&dir).unwrap();
+}
+
+#[test]
+fn an_autosave_keeps_the_one_before() {
+    let slots = memory_slots();
+    let mut game = HeadlessGame::with_seed(4);
+
+    slots.autosave(&mut game.state.ecs).unwrap();
+    game.run([PlayerCommand::SkipTurn]);
+    slots.autosave(&mut game.state.ecs).unwrap();
+    game.run([PlayerCommand::SkipTurn]);
+    slots.autosave(&mut game.state.ecs).unwrap();
+
+    let listed = slots.list();
+
+    assert_eq!(
+        listed.iter().map(|slot| slot.id).collect::<Vec<_>>(),
+        vec![SlotId::Autosave, SlotId::PreviousAutosave]
+    );
+    assert_eq!(listed[0].metadata.as_ref().unwrap().turns, 2);
+    assert_eq!(listed[1].metadata.as_ref().unwrap().turns, 1);
+    assert_eq!(listed[0].title(), "Autosave");
+    assert_eq!(slots.save_new(&mut game.state.ecs), Ok(1));
+}
+
+#[test]
+fn descending_autosaves() {
+    let slots = memory_slots();
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+
+    game.state.use_save_slots(slots.clone());
+    game.state.set_autosave(AutosaveSettings {
+        on_next_level: true,
+        every_turns: None,
+    });
+
+    {
+        let mut map = game.state.ecs.fetch_mut::<Map>();
+        let idx = map.xy_idx(pos.x, pos.y);
+
+        map.tiles[idx] = TileType::DownStairs;
+    }
+
+    game.apply(PlayerCommand::Descend);
+
+    let listed = slots.list();
+
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].metadata.as_ref().unwrap().depth, 2);
+}
+
+#[test]
+fn autosaves_follow_the_turn_interval() {
+    let slots = memory_slots();
+    let mut game = common::isolated_game();
+
+    game.state.use_save_slots(slots.clone());
+    game.state.set_autosave(AutosaveSettings::every(3));
+    game.run([PlayerCommand::SkipTurn, PlayerCommand::SkipTurn]);
+
+    assert!(!slots.has_saves());
+
+    game.run([PlayerCommand::SkipTurn]);
+
+    assert_eq!(slots.list()[0].metadata.as_ref().unwrap().turns, 3);
+
+    game.run(std::iter::repeat_n(PlayerCommand::SkipTurn, 3));
+
+    assert_eq!(slots.list().len(), 2);
+    assert_eq!(slots.list()[0].metadata.as_ref().unwrap().turns, 6);
+}
+
+#[test]
+fn an_autosave_holds_no_one_who_died_that_turn() {
+    let slots = memory_slots();
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let goblin = common::spawn(&mut game, "Goblin", pos.x + 5, pos.y);
+
+    let player = common::player_entity(&game);
+
+    // A slowed player waits while the goblin acts, so the poison kills it on a monster turn
+    common::set_hp(&mut game, goblin, 1);
+    {
+        let mut statuses = game.state.ecs.write_storage::<StatusEffects>();
+
+        for (entity, kind) in [(goblin, StatusKind::Poison), (player, StatusKind::Slow)] {
+            status_effect_system::apply_status(
+                &mut statuses,
+                entity,
+                StatusEffect { kind, turns: 20 },
+            );
+        }
+    }
+    game.state.use_save_slots(slots.clone());
+    game.state.set_autosave(AutosaveSettings::every(1));
+
+    for _ in 0..5 {
+        if !common::is_alive(&game, goblin) {
+            break;
+        }
+
+        game.apply(PlayerCommand::SkipTurn);
+    }
+
+    assert!(!common::is_alive(&game, goblin));
+
+    slots.load(&mut game.state.ecs, SlotId::Autosave).unwrap();
+
+    let names = game.state.ecs.read_storage::<Name>();
+    let combat_stats = game.state.ecs.read_storage::<CombatStats>();
+
+    assert!(!names.join().any(|name| name.name == "Goblin"));
+    assert!(combat_stats.join().all(|stats| stats.hp > 0));
+}
+
+#[test]
+fn a_save_from_before_slots_is_imported_once() {
+    let path = &common::temp_path("savegame.json");
+    let imported = format!("{}.imported", path);
+    let slots = memory_slots();
+    let mut game = common::isolated_game();
+
+    std::fs::copy("tests/saves/format_1.json", path).unwrap();
+    slots.save_new(&mut game.state.ecs).unwrap();
+
+    assert_eq!(slots.import_legacy_save(path), Ok(Some(2)));
+    assert_eq!(slots.import_legacy_save(path), Ok(None));
+    assert!(std::fs::metadata(&imported).is_ok());
+    assert!(slots.list()[1].metadata.is_ok());
+    assert_eq!(slots.load(&mut game.state.ecs, SlotId::Numbered(2)), Ok(()));
+    std::fs::remove_file(&imported).unwrap();
+}
+
+#[test]
+fn autosave_arguments() {
+    let args = |line: &str| line.split(' ').map(str::to_string).collect::<Vec<_>>();
+
+    assert_eq!(save_slots::autosave_turns_argument(&args("game")), Ok(None));
+    assert_eq!(
+        save_slots::autosave_turns_argument(&args("game --autosave-turns 50")),
+        Ok(Some(50))
+    );
+    assert!(save_slots::autosave_turns_argument(&args("game --autosave-turns many")).is_err());
+    assert_eq!(AutosaveSettings::every(0).every_turns, None);
+}
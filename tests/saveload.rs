@@ -0,0 +1,552 @@
+mod common;
+
+use roguelike_tutorial::components::{
+    Ai, AiState, Attributes, DamageType, Experience, Faction, InflictsDamage, InflictsStatus,
+    Initiative, MeleeWeapon, Monster, Name, Resistances, Skills, StatusEffect, StatusEffects,
+    StatusKind, Vulnerabilities, XpValue,
+};
+use roguelike_tutorial::headless::HeadlessGame;
+use roguelike_tutorial::initiative_system::NORMAL_SPEED;
+use roguelike_tutorial::map::{Map, TileType};
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::rng::{AiRng, CombatRng, GameSeed, PerceptionRng, StatusRng};
+use roguelike_tutorial::saveload_system::{self, SaveEncoding, SlotMetadata, SAVE_FORMAT_VERSION};
+use specs::prelude::*;
+
+const FORMAT_1_SAVE: &str = "tests/saves/format_1.json";
+
+#[test]
+fn a_game_saves_after_the_player_kills_whoever_attacked_them() {
+    let path = common::temp_path("saveload_after_a_fight.json");
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let goblin = common::spawn(&mut game, "Goblin", pos.x, pos.y + 1);
+
+    // A badly hurt goblin runs, so corner it
+    {
+        let mut map = game.state.ecs.fetch_mut::<Map>();
+
+        for (x, y) in [(-1, 1), (1, 1), (-1, 2), (0, 2), (1, 2)] {
+            let idx = map.xy_idx(pos.x + x, pos.y + y);
+
+            map.tiles[idx] = TileType::Wall;
+        }
+    }
+    common::refresh(&mut game);
+
+    for _ in 0..40 {
+        if common::attacks(&game, "Goblin", "Player") > 0 {
+            break;
+        }
+
+        game.apply(PlayerCommand::SkipTurn);
+    }
+
+    assert!(common::attacks(&game, "Goblin", "Player") > 0);
+
+    for _ in 0..40 {
+        if !common::is_alive(&game, goblin) {
+            break;
+        }
+
+        common::set_player_hp(&mut game, 30);
+        game.apply(PlayerCommand::Move {
+            delta_x: 0,
+            delta_y: 1,
+        });
+    }
+
+    assert!(!common::is_alive(&game, goblin));
+    assert_eq!(
+        saveload_system::save_game_to(&mut game.state.ecs, &path, SaveEncoding::Binary),
+        Ok(())
+    );
+
+    game.state.start_new_game(1);
+
+    assert_eq!(
+        saveload_system::load_game_from(&mut game.state.ecs, &path),
+        Ok(())
+    );
+    std::fs::remove_file(&path).unwrap();
+}
+
+fn player_name(game: &roguelike_tutorial::headless::HeadlessGame) -> String {
+    let names = game.state.ecs.read_storage::<Name>();
+
+    names.get(common::player_entity(game)).unwrap().name.clone()
+}
+
+#[test]
+fn a_save_has_a_header_and_loads_back() {
+    for encoding in [SaveEncoding::Binary, SaveEncoding::Json] {
+        let path = common::temp_path("saveload_round_trip.json");
+        let mut game = common::isolated_game();
+        let pos = common::player_position(&game);
+
+        common::spawn(&mut game, "Goblin", pos.x + 2, pos.y);
+        {
+            // Set both, the player may have seen either from where the level first put them
+            let mut map = game.state.ecs.fetch_mut::<Map>();
+
+            map.revealed_tiles[5] = true;
+            map.revealed_tiles[6] = false;
+        }
+        saveload_system::save_game_to(&mut game.state.ecs, &path, encoding).unwrap();
+
+        let data = std::fs::read(&path).unwrap();
+        let save = saveload_system::parse_save(&data).unwrap();
+
+        assert_eq!(save.header.format_version, SAVE_FORMAT_VERSION);
+        assert_eq!(save.header.game_version, env!("CARGO_PKG_VERSION"));
+        assert!(save.components.contains_key("Position"));
+
+        let tiles = game.state.ecs.fetch::<Map>().tiles.clone();
+
+        game.state.start_new_game(1);
+
+        assert_eq!(
+            saveload_system::load_game_from(&mut game.state.ecs, &path),
+            Ok(())
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(common::player_position(&game), pos);
+        assert_eq!(player_name(&game), "Player");
+
+        let map = game.state.ecs.fetch::<Map>();
+
+        assert_eq!(map.tiles, tiles);
+        assert_eq!(map.revealed_tiles.len(), map.map_length);
+        assert!(map.revealed_tiles[5]);
+        assert!(!map.revealed_tiles[6]);
+    }
+}
+
+#[test]
+fn a_binary_save_is_smaller_than_json() {
+    let mut game = common::isolated_game();
+    let save_size = |game: &mut roguelike_tutorial::headless::HeadlessGame, encoding| {
+        let path = common::temp_path("saveload_size.json");
+
+        saveload_system::save_game_to(&mut game.state.ecs, &path, encoding).unwrap();
+
+        let size = std::fs::metadata(&path).unwrap().len();
+
+        std::fs::remove_file(&path).unwrap();
+        size
+    };
+
+    assert!(
+        save_size(&mut game, SaveEncoding::Binary) * 4 < save_size(&mut game, SaveEncoding::Json)
+    );
+}
+
+#[test]
+fn a_save_without_a_header_is_migrated() {
+    let mut game = common::isolated_game();
+
+    assert_eq!(
+        saveload_system::load_game_from(&mut game.state.ecs, FORMAT_1_SAVE),
+        Ok(())
+    );
+    assert_eq!(*game.state.ecs.fetch::<GameSeed>(), GameSeed(5));
+    assert_eq!(game.state.ecs.fetch::<Map>().depth, 1);
+    assert_eq!(player_name(&game), "Player");
+}
+
+#[test]
+fn a_migrated_save_gives_everyone_that_acts_initiative() {
+    let mut game = common::isolated_game();
+
+    saveload_system::load_game_from(&mut game.state.ecs, FORMAT_1_SAVE).unwrap();
+
+    let player = common::player_entity(&game);
+    let initiatives = game.state.ecs.read_storage::<Initiative>();
+    let monsters = game.state.ecs.read_storage::<Monster>();
+    let names = game.state.ecs.read_storage::<Name>();
+
+    assert_eq!(initiatives.get(player).unwrap().speed, NORMAL_SPEED);
+    assert!(monsters.join().count() > 0);
+    assert_eq!(
+        (&monsters, &initiatives).join().count(),
+        monsters.join().count()
+    );
+
+    for (_monster, name, initiative) in (&monsters, &names, &initiatives).join() {
+        let speed = match name.name.as_str() {
+            "Goblin" => 125,
+            "Orc" => 80,
+            _ => NORMAL_SPEED,
+        };
+
+        assert_eq!(initiative.speed, speed, "{}", name.name);
+    }
+}
+
+#[test]
+fn a_migrated_save_gives_monsters_an_idle_mind() {
+    let mut game = common::isolated_game();
+
+    saveload_system::load_game_from(&mut game.state.ecs, FORMAT_1_SAVE).unwrap();
+
+    let ai = game.state.ecs.read_storage::<Ai>();
+    let monsters = game.state.ecs.read_storage::<Monster>();
+
+    assert!(monsters.join().count() > 0);
+    assert!((&monsters, &ai)
+        .join()
+        .all(|(_, ai)| ai.state == AiState::Idle { turns: 0 }));
+    assert_eq!((&monsters, &ai).join().count(), monsters.join().count());
+}
+
+#[test]
+fn a_migrated_save_puts_everyone_in_a_faction() {
+    let mut game = common::isolated_game();
+
+    saveload_system::load_game_from(&mut game.state.ecs, FORMAT_1_SAVE).unwrap();
+
+    let player = common::player_entity(&game);
+    let factions = game.state.ecs.read_storage::<Faction>();
+    let names = game.state.ecs.read_storage::<Name>();
+    let monsters = game.state.ecs.read_storage::<Monster>();
+
+    assert_eq!(factions.get(player).unwrap().name, "Player");
+    assert!(monsters.join().count() > 0);
+
+    for (_, name, faction) in (&monsters, &names, &factions).join() {
+        let expected = match name.name.as_str() {
+            "Goblin" => "Goblins",
+            _ => "Orcs",
+        };
+
+        assert_eq!(faction.name, expected);
+    }
+
+    assert_eq!(
+        (&monsters, &factions).join().count(),
+        monsters.join().count()
+    );
+}
+
+#[test]
+fn a_migrated_save_arms_monsters_with_their_raw_damage() {
+    let mut game = common::isolated_game();
+
+    saveload_system::load_game_from(&mut game.state.ecs, FORMAT_1_SAVE).unwrap();
+
+    let monsters = game.state.ecs.read_storage::<Monster>();
+    let weapons = game.state.ecs.read_storage::<MeleeWeapon>();
+
+    assert!(monsters.join().count() > 0);
+    assert_eq!(
+        (&monsters, &weapons).join().count(),
+        monsters.join().count()
+    );
+}
+
+#[test]
+fn a_migrated_save_turns_confusion_into_status_effects() {
+    let path = common::temp_path("saveload_confused_orc.json");
+    let data = std::fs::read_to_string(FORMAT_1_SAVE).unwrap();
+    let mut storages: Vec<serde_json::Value> = serde_json::Deserializer::from_str(&data)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    // The orc is marker 12, and Confusion is the 16th storage
+    storages[15][12]["components"][0] = serde_json::json!({ "turns": 3 });
+
+    let old_save: String = storages.iter().map(|storage| storage.to_string()).collect();
+
+    std::fs::write(&path, old_save).unwrap();
+
+    let mut game = common::isolated_game();
+    let loaded = saveload_system::load_game_from(&mut game.state.ecs, &path);
+
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, Ok(()));
+
+    let names = game.state.ecs.read_storage::<Name>();
+    let statuses = game.state.ecs.read_storage::<StatusEffects>();
+    let inflicts = game.state.ecs.read_storage::<InflictsStatus>();
+    let confusion = StatusEffect {
+        kind: StatusKind::Confusion,
+        turns: 4,
+    };
+    let mut scrolls = 0;
+
+    for (name, inflicts) in (&names, &inflicts).join() {
+        assert_eq!(name.name, "Confusion Scroll");
+        assert_eq!(inflicts.effects, vec![confusion]);
+        scrolls += 1;
+    }
+
+    let confused: Vec<(&str, &StatusEffects)> = (&names, &statuses)
+        .join()
+        .map(|(name, statuses)| (name.name.as_str(), statuses))
+        .collect();
+
+    assert_eq!(scrolls, 3);
+    assert_eq!(confused.len(), 1);
+    assert_eq!(confused[0].0, "Orc");
+    assert_eq!(confused[0].1.turns_left(StatusKind::Confusion), 3);
+}
+
+#[test]
+fn a_migrated_save_gets_damage_types_and_resistances() {
+    let mut game = common::isolated_game();
+
+    saveload_system::load_game_from(&mut game.state.ecs, FORMAT_1_SAVE).unwrap();
+
+    let names = game.state.ecs.read_storage::<Name>();
+    let inflicts = game.state.ecs.read_storage::<InflictsDamage>();
+    let weapons = game.state.ecs.read_storage::<MeleeWeapon>();
+    let resistances = game.state.ecs.read_storage::<Resistances>();
+    let vulnerabilities = game.state.ecs.read_storage::<Vulnerabilities>();
+
+    for (name, inflicts) in (&names, &inflicts).join() {
+        let expected = match name.name.as_str() {
+            "Fireball Scroll" => DamageType::Fire,
+            _ => DamageType::Physical,
+        };
+
+        assert_eq!(inflicts.damage_type, expected, "{}", name.name);
+    }
+    for weapon in weapons.join() {
+        assert_eq!(weapon.damage_type, DamageType::Physical);
+    }
+    for (name, _) in (&names, &resistances).join() {
+        assert_eq!(name.name, "Orc");
+    }
+    for (name, _) in (&names, &vulnerabilities).join() {
+        assert_eq!(name.name, "Goblin");
+    }
+
+    assert_eq!(resistances.join().count(), 1);
+    assert_eq!(vulnerabilities.join().count(), 7);
+}
+
+#[test]
+fn a_migrated_save_gives_experience_to_the_player_and_xp_to_monsters() {
+    let mut game = common::isolated_game();
+
+    saveload_system::load_game_from(&mut game.state.ecs, FORMAT_1_SAVE).unwrap();
+
+    let player = common::player_entity(&game);
+    let experiences = game.state.ecs.read_storage::<Experience>();
+    let experience = experiences.get(player).unwrap();
+    let names = game.state.ecs.read_storage::<Name>();
+    let xp_values = game.state.ecs.read_storage::<XpValue>();
+
+    assert_eq!((experience.level, experience.xp), (1, 0));
+    assert_eq!(experiences.join().count(), 1);
+
+    for (name, xp_value) in (&names, &xp_values).join() {
+        let expected = match name.name.as_str() {
+            "Goblin" => 10,
+            "Orc" => 20,
+            _ => panic!("{} should not be worth any XP", name.name),
+        };
+
+        assert_eq!(xp_value.xp, expected);
+    }
+
+    assert_eq!(xp_values.join().count(), 8);
+}
+
+#[test]
+fn a_migrated_save_makes_the_player_average_and_unskilled() {
+    let mut game = common::isolated_game();
+
+    saveload_system::load_game_from(&mut game.state.ecs, FORMAT_1_SAVE).unwrap();
+
+    let player = common::player_entity(&game);
+    let attributes = game.state.ecs.read_storage::<Attributes>();
+    let skills = game.state.ecs.read_storage::<Skills>();
+
+    assert_eq!(attributes.get(player), Some(&Attributes::default()));
+    assert_eq!(skills.get(player), Some(&Skills::default()));
+    assert_eq!(attributes.join().count(), 1);
+    assert_eq!(skills.join().count(), 1);
+}
+
+#[test]
+fn a_migrated_save_gets_slot_metadata() {
+    let data = std::fs::read_to_string(FORMAT_1_SAVE).unwrap();
+    let save = saveload_system::parse_save(data.as_bytes()).unwrap();
+
+    assert_eq!(
+        save.header.metadata,
+        SlotMetadata {
+            character_name: "Player".to_string(),
+            depth: 1,
+            turns: 0,
+            timestamp: 0,
+            seed: 5,
+        }
+    );
+}
+
+#[test]
+fn a_save_from_before_the_master_dungeon_is_migrated() {
+    let path = common::temp_path("saveload_before_dungeon.json");
+    let data = std::fs::read_to_string(FORMAT_1_SAVE).unwrap();
+    let mut storages: Vec<serde_json::Value> = serde_json::Deserializer::from_str(&data)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    // No OtherLevelPosition storage, no seed and no dungeon in the helper
+    storages.remove(35);
+    for entity in storages[21].as_array_mut().unwrap() {
+        if let Some(helper) = entity.pointer_mut("/components/0") {
+            if let Some(helper) = helper.as_object_mut() {
+                helper.remove("seed");
+                helper.remove("dungeon");
+            }
+        }
+    }
+
+    let old_save: String = storages.iter().map(|storage| storage.to_string()).collect();
+
+    std::fs::write(&path, old_save).unwrap();
+
+    let mut game = common::isolated_game();
+    let loaded = saveload_system::load_game_from(&mut game.state.ecs, &path);
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded, Ok(()));
+    assert_eq!(*game.state.ecs.fetch::<GameSeed>(), GameSeed(0));
+    assert_eq!(player_name(&game), "Player");
+}
+
+#[test]
+fn a_save_from_a_newer_game_is_an_error_and_changes_nothing() {
+    let path = common::temp_path("saveload_newer.json");
+    let mut game = common::isolated_game();
+
+    saveload_system::save_game_to(&mut game.state.ecs, &path, SaveEncoding::Json).unwrap();
+
+    let data = std::fs::read_to_string(&path).unwrap();
+    let newer = data.replacen(
+        &format!("\"format_version\":{}", SAVE_FORMAT_VERSION),
+        "\"format_version\":999",
+        1,
+    );
+
+    std::fs::write(&path, newer).unwrap();
+
+    let player = common::player_entity(&game);
+    let loaded = saveload_system::load_game_from(&mut game.state.ecs, &path);
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(loaded.unwrap_err().contains("newer version"));
+    assert!(common::is_alive(&game, player));
+}
+
+#[test]
+fn an_unreadable_save_is_an_error() {
+    let path = common::temp_path("saveload_garbage.json");
+    let mut game = common::isolated_game();
+
+    std::fs::write(&path, "this is not a save").unwrap();
+
+    let loaded = saveload_system::load_game_from(&mut game.state.ecs, &path);
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(loaded.is_err());
+    assert!(saveload_system::load_game_from(
+        &mut game.state.ecs,
+        &common::temp_path("saveload_missing.json")
+    )
+    .is_err());
+}
+
+#[test]
+fn a_save_with_more_or_fewer_tiles_than_its_map_is_an_error() {
+    let path = common::temp_path("saveload_tiles.json");
+    let mut game = common::isolated_game();
+
+    saveload_system::save_game_to(&mut game.state.ecs, &path, SaveEncoding::Json).unwrap();
+
+    let data = std::fs::read(&path).unwrap();
+    let tampered = |runs: serde_json::Value| {
+        let mut save = saveload_system::parse_save(&data).unwrap();
+
+        *save.components["SerializationHelper"]
+            .as_array_mut()
+            .unwrap()
+            .iter_mut()
+            .find_map(|entity| entity.pointer_mut("/components/0/map/tiles"))
+            .unwrap() = runs;
+
+        saveload_system::encode_save(&save, SaveEncoding::Json).unwrap()
+    };
+
+    for (runs, error) in [
+        (serde_json::json!([["Wall", 10]]), "The map has 10 tiles"),
+        (
+            serde_json::json!([["Wall", usize::MAX], ["Floor", 2]]),
+            "more than a map can have",
+        ),
+    ] {
+        std::fs::write(&path, tampered(runs)).unwrap();
+
+        let loaded = saveload_system::load_game_from(&mut game.state.ecs, &path);
+
+        assert!(loaded.unwrap_err().contains(error));
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// The next roll of every stream drawn from during play
+fn next_rolls(game: &mut HeadlessGame) -> [u64; 4] {
+    let ecs = &game.state.ecs;
+
+    [
+        ecs.fetch_mut::<CombatRng>().next_u64(),
+        ecs.fetch_mut::<PerceptionRng>().next_u64(),
+        ecs.fetch_mut::<AiRng>().next_u64(),
+        ecs.fetch_mut::<StatusRng>().next_u64(),
+    ]
+}
+
+#[test]
+fn a_loaded_game_rolls_on_where_the_saved_one_stood() {
+    let path = common::temp_path("saveload_rng_streams.json");
+    // Seed 36 has a goblin fight the player right at the start
+    let mut game = HeadlessGame::with_seed(36);
+
+    game.run(
+        [(1, 1), (1, 0), (1, 0)].map(|(delta_x, delta_y)| PlayerCommand::Move { delta_x, delta_y }),
+    );
+    saveload_system::save_game_to(&mut game.state.ecs, &path, SaveEncoding::Binary).unwrap();
+
+    let rolls = next_rolls(&mut game);
+
+    game.state.start_new_game(36);
+    saveload_system::load_game_from(&mut game.state.ecs, &path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(next_rolls(&mut game), rolls);
+}
+
+#[test]
+fn a_migrated_save_rolls_from_the_start_of_its_seed() {
+    let mut game = HeadlessGame::with_seed(5);
+
+    game.state.ecs.fetch_mut::<CombatRng>().next_u64();
+    saveload_system::load_game_from(&mut game.state.ecs, FORMAT_1_SAVE).unwrap();
+
+    let seed = game.state.ecs.fetch::<GameSeed>().0;
+
+    assert_eq!(
+        game.state.ecs.fetch_mut::<CombatRng>().next_u64(),
+        CombatRng::new(seed).next_u64()
+    );
+}
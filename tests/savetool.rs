@@ -0,0 +1,236 @@
+mod common;
+
+use roguelike_tutorial::components::Name;
+use roguelike_tutorial::gui;
+use roguelike_tutorial::headless::HeadlessGame;
+use roguelike_tutorial::map::Map;
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::saveload_system::{self, SaveEncoding};
+use roguelike_tutorial::savetool::{self, SaveEdit, SavetoolCommand, SavetoolOptions};
+use specs::prelude::*;
+
+fn args(line: &str) -> Vec<String> {
+    std::iter::once("savetool")
+        .chain(line.split('|'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// A game with a potion in the backpack, saved to `path`
+fn saved_game(path: &str, encoding: SaveEncoding) -> HeadlessGame {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+
+    common::spawn(&mut game, "Health Potion", pos.x, pos.y);
+    common::spawn(&mut game, "Goblin", pos.x + 2, pos.y);
+    game.apply(PlayerCommand::PickUp);
+    saveload_system::save_game_to(&mut game.state.ecs, path, encoding).unwrap();
+
+    game
+}
+
+fn run(path: &str, command: SavetoolCommand) -> Result<String, String> {
+    savetool::run(&SavetoolOptions {
+        path: path.to_string(),
+        command,
+    })
+}
+
+#[test]
+fn arguments_are_parsed() {
+    assert_eq!(
+        savetool::parse_arguments(&args("save.sav|map|--depth|2")),
+        Ok(SavetoolOptions {
+            path: "save.sav".to_string(),
+            command: SavetoolCommand::Map { depth: Some(2) },
+        })
+    );
+    assert_eq!(
+        savetool::parse_arguments(&args(
+            "save.sav|edit|hp 30|give Health Potion|--out|new.sav"
+        )),
+        Ok(SavetoolOptions {
+            path: "save.sav".to_string(),
+            command: SavetoolCommand::Edit {
+                edits: vec![
+                    SaveEdit::Hp(30),
+                    SaveEdit::Give("Health Potion".to_string())
+                ],
+                script: None,
+                out: Some("new.sav".to_string()),
+            },
+        })
+    );
+    assert!(savetool::parse_arguments(&args("save.sav|edit|depth 0")).is_err());
+    assert!(savetool::parse_arguments(&args("save.sav|edit|hp 0")).is_err());
+    assert!(savetool::parse_arguments(&args("save.sav|edit|hp -5")).is_err());
+    assert!(savetool::parse_arguments(&args("save.sav|explode")).is_err());
+    assert_eq!(
+        savetool::parse_edit_script("# setup\nhp 5\n\ndepth 3\n"),
+        Ok(vec![SaveEdit::Hp(5), SaveEdit::Depth(3)])
+    );
+}
+
+#[test]
+fn entities_and_the_map_are_printed() {
+    let path = common::temp_path("savetool_print.sav");
+    let game = saved_game(&path, SaveEncoding::Binary);
+    let pos = common::player_position(&game);
+
+    let entities = run(&path, SavetoolCommand::Entities).unwrap();
+    let map = run(&path, SavetoolCommand::Map { depth: None }).unwrap();
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(entities.contains(" Player\n"), "{}", entities);
+    assert!(entities.contains(" Goblin\n"), "{}", entities);
+    assert!(
+        entities.contains("  InBackpack {\"owner\":["),
+        "{}",
+        entities
+    );
+
+    let rows: Vec<&str> = map.lines().skip(1).collect();
+
+    assert_eq!(rows[pos.y as usize].chars().nth(pos.x as usize), Some('@'));
+    assert_eq!(rows.iter().filter(|row| row.contains('g')).count(), 1);
+}
+
+#[test]
+fn broken_references_do_not_validate() {
+    let path = common::temp_path("savetool_validate.sav");
+
+    saved_game(&path, SaveEncoding::Json);
+
+    assert!(run(&path, SavetoolCommand::Validate).is_ok());
+
+    let data = std::fs::read(&path).unwrap();
+    let mut save = saveload_system::parse_save(&data).unwrap();
+
+    for entity in save.components["InBackpack"].as_array_mut().unwrap() {
+        if let Some(owner) = entity.pointer_mut("/components/0/owner") {
+            *owner = serde_json::json!([999]);
+        }
+    }
+
+    std::fs::write(
+        &path,
+        saveload_system::encode_save(&save, SaveEncoding::Json).unwrap(),
+    )
+    .unwrap();
+
+    let problems = run(&path, SavetoolCommand::Validate).unwrap_err();
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(
+        problems.contains("InBackpack.owner points at entity 999"),
+        "{}",
+        problems
+    );
+}
+
+#[test]
+fn edits_are_saved() {
+    let path = common::temp_path("savetool_edit.sav");
+    let out = common::temp_path("savetool_edited.sav");
+
+    saved_game(&path, SaveEncoding::Binary);
+
+    let edited = run(
+        &path,
+        SavetoolCommand::Edit {
+            edits: vec![
+                SaveEdit::Hp(3),
+                SaveEdit::Give("Dagger".to_string()),
+                SaveEdit::Depth(3),
+            ],
+            script: None,
+            out: Some(out.clone()),
+        },
+    );
+
+    assert!(edited.is_ok(), "{:?}", edited);
+
+    let mut game = common::isolated_game();
+
+    saveload_system::load_game_from(&mut game.state.ecs, &out).unwrap();
+
+    let encoding = saveload_system::encoding_of(&std::fs::read(&out).unwrap());
+
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&out).unwrap();
+
+    assert_eq!(encoding, SaveEncoding::Binary);
+    assert_eq!(common::player_stats(&game).hp, 3);
+    assert_eq!(game.state.ecs.fetch::<Map>().depth, 3);
+
+    let names = game.state.ecs.read_storage::<Name>();
+    let mut backpack: Vec<String> = gui::backpack_items(&game.state.ecs)
+        .iter()
+        .map(|item| names.get(*item).unwrap().name.clone())
+        .collect();
+
+    backpack.sort();
+
+    assert_eq!(backpack, vec!["Dagger", "Health Potion"]);
+}
+
+#[test]
+fn only_items_can_be_given() {
+    let path = common::temp_path("savetool_give.sav");
+
+    saved_game(&path, SaveEncoding::Binary);
+
+    let given = run(
+        &path,
+        SavetoolCommand::Edit {
+            edits: vec![SaveEdit::Give("Goblin".to_string())],
+            script: None,
+            out: None,
+        },
+    );
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(given.unwrap_err().contains("not an item"));
+}
+
+#[test]
+fn hit_points_above_the_maximum_raise_it() {
+    let path = common::temp_path("savetool_hp.sav");
+    let out = common::temp_path("savetool_hp_edited.sav");
+
+    saved_game(&path, SaveEncoding::Binary);
+
+    let edited = run(
+        &path,
+        SavetoolCommand::Edit {
+            edits: vec![SaveEdit::Hp(500)],
+            script: None,
+            out: Some(out.clone()),
+        },
+    );
+
+    assert!(edited.is_ok(), "{:?}", edited);
+
+    let refused = run(
+        &path,
+        SavetoolCommand::Edit {
+            edits: vec![SaveEdit::Hp(0)],
+            script: None,
+            out: None,
+        },
+    );
+
+    let mut game = common::isolated_game();
+
+    saveload_system::load_game_from(&mut game.state.ecs, &out).unwrap();
+
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&out).unwrap();
+
+    assert_eq!(common::player_stats(&game).hp, 500);
+    assert_eq!(common::player_stats(&game).max_hp, 500);
+    assert!(refused.unwrap_err().contains("positive"));
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use roguelike_tutorial::components::{Name, Position};
+use roguelike_tutorial::headless::HeadlessGame;
+use roguelike_tutorial::map::{Map, TileType};
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::rng::{self, GameSeed};
+use specs::prelude::*;
+
+/// Everything that makes up a level: its tiles, where the player starts and what was spawned
+fn level_layout(game: &HeadlessGame) -> (Vec<TileType>, rltk::Point, Vec<(String, i32, i32)>) {
+    let map = game.state.ecs.fetch::<Map>();
+    let names = game.state.ecs.read_storage::<Name>();
+    let positions = game.state.ecs.read_storage::<Position>();
+
+    let mut spawns: Vec<(String, i32, i32)> = (&names, &positions)
+        .join()
+        .map(|(name, pos)| (name.name.clone(), pos.x, pos.y))
+        .collect();
+
+    spawns.sort();
+
+    (map.tiles.clone(), common::player_position(game), spawns)
+}
+
+fn descend(game: &mut HeadlessGame) {
+    let pos = common::player_position(game);
+    {
+        let mut map = game.state.ecs.write_resource::<Map>();
+        let idx = map.xy_idx(pos.x, pos.y);
+
+        map.tiles[idx] = TileType::DownStairs;
+    }
+
+    game.apply(PlayerCommand::Descend);
+}
+
+#[test]
+fn the_same_seed_builds_the_same_dungeon() {
+    let mut first = HeadlessGame::with_seed(1234);
+    let mut second = HeadlessGame::with_seed(1234);
+
+    for depth in 1..=3 {
+        assert_eq!(first.state.ecs.fetch::<Map>().depth, depth);
+        assert!(
+            level_layout(&first) == level_layout(&second),
+            "Depth {} differs",
+            depth
+        );
+
+        descend(&mut first);
+        descend(&mut second);
+    }
+}
+
+#[test]
+fn what_the_player_does_does_not_change_the_next_level() {
+    let mut idle = HeadlessGame::with_seed(99);
+    let mut busy = HeadlessGame::with_seed(99);
+
+    for _ in 0..20 {
+        busy.apply(PlayerCommand::SkipTurn);
+    }
+
+    descend(&mut idle);
+    descend(&mut busy);
+
+    assert_eq!(busy.state.ecs.fetch::<Map>().depth, 2);
+    assert!(level_layout(&idle) == level_layout(&busy));
+}
+
+#[test]
+fn different_seeds_build_different_dungeons() {
+    let first = HeadlessGame::with_seed(1);
+    let second = HeadlessGame::with_seed(2);
+
+    assert!(level_layout(&first) != level_layout(&second));
+}
+
+#[test]
+fn a_new_game_uses_the_given_seed() {
+    let mut game = HeadlessGame::with_seed(1);
+
+    game.state.start_new_game(1234);
+
+    assert_eq!(*game.state.ecs.fetch::<GameSeed>(), GameSeed(1234));
+    assert!(level_layout(&game) == level_layout(&HeadlessGame::with_seed(1234)));
+}
+
+#[test]
+fn the_seed_argument_is_parsed() {
+    let args = |line: &str| line.split(' ').map(String::from).collect::<Vec<_>>();
+
+    assert_eq!(rng::seed_argument(&args("game --seed 42")), Ok(Some(42)));
+    assert_eq!(rng::seed_argument(&args("game")), Ok(None));
+    assert!(rng::seed_argument(&args("game --seed forty")).is_err());
+    assert!(rng::seed_argument(&args("game --seed")).is_err());
+}
@@ -0,0 +1,191 @@
+mod common;
+
+use roguelike_tutorial::components::*;
+use roguelike_tutorial::headless::HeadlessGame;
+use roguelike_tutorial::map::{Map, TileType};
+use roguelike_tutorial::player::PlayerCommand;
+use roguelike_tutorial::saveload_system::{self, SaveEncoding};
+use roguelike_tutorial::status_effect_system;
+use roguelike_tutorial::TurnCount;
+use specs::prelude::*;
+
+fn afflict(game: &mut HeadlessGame, entity: Entity, kind: StatusKind, turns: i32) {
+    let mut statuses = game.state.ecs.write_storage::<StatusEffects>();
+
+    status_effect_system::apply_status(&mut statuses, entity, StatusEffect { kind, turns });
+}
+
+fn statuses_of(game: &HeadlessGame, entity: Entity) -> StatusEffects {
+    game.state
+        .ecs
+        .read_storage::<StatusEffects>()
+        .get(entity)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Passes turns by walking into a wall, as waiting would heal the player
+fn bump_into_wall(game: &mut HeadlessGame, turns: usize) {
+    let pos = common::player_position(game);
+
+    {
+        let mut map = game.state.ecs.fetch_mut::<Map>();
+        let idx = map.xy_idx(pos.x - 1, pos.y);
+
+        map.tiles[idx] = TileType::Wall;
+    }
+    common::refresh(game);
+
+    game.run(std::iter::repeat_n(
+        PlayerCommand::Move {
+            delta_x: -1,
+            delta_y: 0,
+        },
+        turns,
+    ));
+}
+
+#[test]
+fn poison_stacks_and_other_effects_refresh() {
+    let mut statuses = StatusEffects::default();
+
+    statuses.add(StatusEffect {
+        kind: StatusKind::Poison,
+        turns: 5,
+    });
+    statuses.add(StatusEffect {
+        kind: StatusKind::Poison,
+        turns: 3,
+    });
+    statuses.add(StatusEffect {
+        kind: StatusKind::Haste,
+        turns: 5,
+    });
+    statuses.add(StatusEffect {
+        kind: StatusKind::Haste,
+        turns: 8,
+    });
+    statuses.add(StatusEffect {
+        kind: StatusKind::Haste,
+        turns: 2,
+    });
+
+    assert_eq!(statuses.doses(StatusKind::Poison), 2);
+    assert_eq!(statuses.turns_left(StatusKind::Poison), 5);
+    assert_eq!(statuses.doses(StatusKind::Haste), 1);
+    assert_eq!(statuses.turns_left(StatusKind::Haste), 8);
+}
+
+#[test]
+fn poison_hurts_every_turn_until_it_wears_off() {
+    let mut game = common::isolated_game();
+    let player = common::player_entity(&game);
+
+    afflict(&mut game, player, StatusKind::Poison, 3);
+    afflict(&mut game, player, StatusKind::Poison, 3);
+    bump_into_wall(&mut game, 1);
+
+    assert!(game.summary().contains("Status: Poisoned x2"));
+
+    bump_into_wall(&mut game, 4);
+
+    assert_eq!(common::player_stats(&game).hp, 30 - 6);
+    assert!(!statuses_of(&game, player).has(StatusKind::Poison));
+    assert!(common::log_contains(&game, "You are no longer poisoned."));
+}
+
+#[test]
+fn a_poison_dart_poisons_a_monster() {
+    let mut game = common::isolated_game();
+    let pos = common::player_position(&game);
+    let orc = common::spawn(&mut game, "Orc", pos.x + 2, pos.y);
+
+    common::use_item_on(&mut game, "Poison Dart", orc);
+
+    assert!(common::log_contains(
+        &game,
+        "You use Poison Dart on Orc, poisoning them."
+    ));
+    assert!(statuses_of(&game, orc).has(StatusKind::Poison));
+}
+
+#[test]
+fn a_regeneration_potion_heals_over_time() {
+    let mut game = common::isolated_game();
+
+    common::set_player_hp(&mut game, 10);
+    common::use_item(&mut game, "Regeneration Potion");
+
+    assert!(common::log_contains(&game, "your wounds begin to close"));
+    assert!(game.summary().contains("Status: Regenerating"));
+
+    let hp = common::player_stats(&game).hp;
+
+    bump_into_wall(&mut game, 5);
+
+    assert_eq!(common::player_stats(&game).hp, hp + 5);
+}
+
+#[test]
+fn a_paralysed_player_loses_their_turns() {
+    let mut game = common::isolated_game();
+    let player = common::player_entity(&game);
+    let turns = game.state.ecs.fetch::<TurnCount>().0;
+
+    afflict(&mut game, player, StatusKind::Paralysis, 3);
+    game.apply(PlayerCommand::SkipTurn);
+
+    assert!(game.state.ecs.fetch::<TurnCount>().0 >= turns + 3);
+    assert!(common::log_contains(&game, "You are no longer paralysed."));
+    assert!(!statuses_of(&game, player).has(StatusKind::Paralysis));
+}
+
+#[test]
+fn a_confused_or_paralysed_monster_does_not_attack() {
+    for kind in [StatusKind::Confusion, StatusKind::Paralysis] {
+        let mut game = common::isolated_game();
+        let pos = common::player_position(&game);
+        let goblin = common::spawn(&mut game, "Goblin", pos.x + 1, pos.y);
+
+        afflict(&mut game, goblin, kind, 10);
+        game.run(std::iter::repeat_n(PlayerCommand::SkipTurn, 5));
+
+        assert_eq!(common::attacks(&game, "Goblin", "Player"), 0, "{:?}", kind);
+    }
+}
+
+#[test]
+fn a_confused_player_sometimes_stumbles() {
+    let mut game = common::isolated_game();
+    let player = common::player_entity(&game);
+
+    afflict(&mut game, player, StatusKind::Confusion, 100);
+
+    bump_into_wall(&mut game, 20);
+
+    assert!(common::log_contains(
+        &game,
+        "You stumble around in confusion."
+    ));
+}
+
+#[test]
+fn status_effects_are_saved() {
+    let path = &common::temp_path("status_effects.sav");
+    let mut game = common::isolated_game();
+    let player = common::player_entity(&game);
+
+    afflict(&mut game, player, StatusKind::Poison, 4);
+    afflict(&mut game, player, StatusKind::Slow, 7);
+
+    let before = statuses_of(&game, player);
+
+    saveload_system::save_game_to(&mut game.state.ecs, path, SaveEncoding::Binary).unwrap();
+    game.state.start_new_game(1);
+    saveload_system::load_game_from(&mut game.state.ecs, path).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    let player = common::player_entity(&game);
+
+    assert_eq!(statuses_of(&game, player).effects, before.effects);
+}